@@ -0,0 +1,172 @@
+//! Amount 基准测试：对比 u128 存储与原先的 BigUint 存储
+//!
+//! 除了 criterion 的耗时统计外，启动时还会打印每次操作的堆分配次数。
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use faic_core::types::Amount;
+use num_bigint::BigUint;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// 统计堆分配次数的分配器
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const ITERATIONS: usize = 10_000;
+
+// 统计执行 ITERATIONS 次操作的平均分配次数
+fn allocations_per_op<F: FnMut()>(mut op: F) -> f64 {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for _ in 0..ITERATIONS {
+        op();
+    }
+    let after = ALLOCATIONS.load(Ordering::Relaxed);
+    (after - before) as f64 / ITERATIONS as f64
+}
+
+fn sample_values() -> (u128, u128) {
+    (123_456_789_012_345_678_901_234, 987_654_321_098_765)
+}
+
+fn report_allocations() {
+    let (a, b) = sample_values();
+    let (big_a, big_b) = (BigUint::from(a), BigUint::from(b));
+    let (amount_a, amount_b) = (Amount::from_u128(a), Amount::from_u128(b));
+    let big_json = serde_json::to_string(&big_a).unwrap();
+    let json = serde_json::to_string(&amount_a).unwrap();
+
+    let rows = [
+        (
+            "checked_add",
+            allocations_per_op(|| {
+                black_box(&big_a + &big_b);
+            }),
+            allocations_per_op(|| {
+                black_box(amount_a.checked_add(&amount_b));
+            }),
+        ),
+        (
+            "checked_mul_u64",
+            allocations_per_op(|| {
+                black_box(&big_a * BigUint::from(3u64));
+            }),
+            allocations_per_op(|| {
+                black_box(amount_a.checked_mul_u64(3));
+            }),
+        ),
+        (
+            "compare",
+            allocations_per_op(|| {
+                black_box(big_a == big_b);
+            }),
+            allocations_per_op(|| {
+                black_box(amount_a == amount_b);
+            }),
+        ),
+        (
+            "clone",
+            allocations_per_op(|| {
+                black_box(big_a.clone());
+            }),
+            allocations_per_op(|| {
+                black_box(amount_a);
+            }),
+        ),
+        (
+            "deserialize",
+            allocations_per_op(|| {
+                black_box(serde_json::from_str::<BigUint>(&big_json).unwrap());
+            }),
+            allocations_per_op(|| {
+                black_box(serde_json::from_str::<Amount>(&json).unwrap());
+            }),
+        ),
+    ];
+
+    println!("allocations per operation ({} iterations)", ITERATIONS);
+    println!("{:<16} {:>10} {:>10}", "operation", "BigUint", "Amount");
+    for (name, big, amount) in rows {
+        println!("{:<16} {:>10.2} {:>10.2}", name, big, amount);
+    }
+}
+
+fn bench_arithmetic(c: &mut Criterion) {
+    let (a, b) = sample_values();
+    let (big_a, big_b) = (BigUint::from(a), BigUint::from(b));
+    let (amount_a, amount_b) = (Amount::from_u128(a), Amount::from_u128(b));
+
+    let mut group = c.benchmark_group("arithmetic");
+    group.bench_function("biguint_add", |bench| bench.iter(|| black_box(&big_a) + black_box(&big_b)));
+    group.bench_function("amount_checked_add", |bench| {
+        bench.iter(|| black_box(&amount_a).checked_add(black_box(&amount_b)))
+    });
+    group.bench_function("biguint_sub", |bench| bench.iter(|| black_box(&big_a) - black_box(&big_b)));
+    group.bench_function("amount_checked_sub", |bench| {
+        bench.iter(|| black_box(&amount_a).checked_sub(black_box(&amount_b)))
+    });
+    group.bench_function("biguint_mul", |bench| bench.iter(|| black_box(&big_a) * BigUint::from(black_box(3u64))));
+    group.bench_function("amount_checked_mul_u64", |bench| {
+        bench.iter(|| black_box(&amount_a).checked_mul_u64(black_box(3)))
+    });
+    group.finish();
+}
+
+fn bench_comparison(c: &mut Criterion) {
+    let (a, b) = sample_values();
+    let (big_a, big_b) = (BigUint::from(a), BigUint::from(b));
+    let (amount_a, amount_b) = (Amount::from_u128(a), Amount::from_u128(b));
+
+    let mut group = c.benchmark_group("comparison");
+    group.bench_function("biguint_eq", |bench| bench.iter(|| black_box(&big_a) == black_box(&big_b)));
+    group.bench_function("amount_eq", |bench| bench.iter(|| black_box(&amount_a) == black_box(&amount_b)));
+    group.finish();
+}
+
+fn bench_serialization(c: &mut Criterion) {
+    let (a, _) = sample_values();
+    let big = BigUint::from(a);
+    let amount = Amount::from_u128(a);
+    let big_json = serde_json::to_string(&big).unwrap();
+    let amount_json = serde_json::to_string(&amount).unwrap();
+
+    let mut group = c.benchmark_group("serialization");
+    group.bench_function("biguint_to_json", |bench| bench.iter(|| serde_json::to_string(black_box(&big)).unwrap()));
+    group.bench_function("amount_to_json", |bench| {
+        bench.iter(|| serde_json::to_string(black_box(&amount)).unwrap())
+    });
+    group.bench_function("biguint_from_json", |bench| {
+        bench.iter(|| serde_json::from_str::<BigUint>(black_box(&big_json)).unwrap())
+    });
+    group.bench_function("amount_from_json", |bench| {
+        bench.iter(|| serde_json::from_str::<Amount>(black_box(&amount_json)).unwrap())
+    });
+    group.bench_function("amount_to_string", |bench| bench.iter(|| black_box(&amount).to_string()));
+    group.finish();
+}
+
+fn bench_allocations(_: &mut Criterion) {
+    report_allocations();
+}
+
+criterion_group!(benches, bench_allocations, bench_arithmetic, bench_comparison, bench_serialization);
+criterion_main!(benches);
@@ -0,0 +1,418 @@
+use crate::chain::merkle::{merkle_root, MerkleProof, MerkleTree};
+use crate::types::encoding::{DecodeError, Reader};
+use crate::types::{Hash256, Transaction, TxId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+
+/// 区块哈希：区块头规范编码的双重 SHA-256
+pub type BlockHash = Hash256;
+
+/// 区块头
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockHeader {
+    /// 区块格式版本
+    pub version: u32,
+    /// 父区块哈希，创世区块为全零
+    pub parent_hash: BlockHash,
+    /// 区块高度，创世区块为 0
+    pub height: u64,
+    /// 出块时间（Unix 时间戳，秒）
+    pub timestamp: u64,
+    /// 区块内交易 ID 的默克尔根
+    pub merkle_root: Hash256,
+    /// 执行完本区块后的状态根
+    pub state_root: Hash256,
+    /// 共识字段：难度目标（紧凑格式）
+    pub bits: u32,
+    /// 共识字段：工作量证明的随机数
+    pub nonce: u64,
+}
+
+/// 区块：区块头 + 交易列表
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Block {
+    /// 区块头
+    pub header: BlockHeader,
+    /// 区块内的交易
+    pub transactions: Vec<Transaction>,
+}
+
+/// 区块结构校验的限制参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockLimits {
+    /// 区块规范编码的最大长度（字节）
+    pub max_block_size: usize,
+    /// 单个区块的最大交易数
+    pub max_transactions: usize,
+    /// 区块时间戳允许超前本地时间的最大秒数
+    pub max_future_drift: u64,
+}
+
+impl Default for BlockLimits {
+    fn default() -> Self {
+        BlockLimits {
+            max_block_size: 1_000_000,
+            max_transactions: 5_000,
+            max_future_drift: 2 * 60 * 60,
+        }
+    }
+}
+
+// 自定义错误类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockError {
+    BlockTooLarge { size: usize, max: usize },  // 区块编码超过大小限制
+    TooManyTransactions { count: usize, max: usize },  // 交易数超过限制
+    TimestampTooFarInFuture { timestamp: u64, max: u64 },  // 时间戳超前本地时间过多
+    TimestampBeforeParent { timestamp: u64, parent_timestamp: u64 },  // 时间戳早于父区块
+    ParentHashMismatch,  // 父区块哈希不匹配
+    HeightMismatch { expected: u64, actual: u64 },  // 高度不是父区块高度 + 1
+    HeightOverflow,  // 父区块高度已达上限，无法再延伸
+    MerkleRootMismatch,  // 默克尔根与交易列表不一致
+    DuplicateTransaction(TxId),  // 区块内有重复交易
+    Decode(DecodeError),  // 二进制解码错误
+}
+
+// 为 BlockError 实现 Display trait，用于打印错误信息
+impl fmt::Display for BlockError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BlockError::BlockTooLarge { size, max } => {
+                write!(f, "Block size {} exceeds limit {}", size, max)
+            }
+            BlockError::TooManyTransactions { count, max } => {
+                write!(f, "Block has {} transactions, limit is {}", count, max)
+            }
+            BlockError::TimestampTooFarInFuture { timestamp, max } => {
+                write!(f, "Block timestamp {} is later than allowed {}", timestamp, max)
+            }
+            BlockError::TimestampBeforeParent { timestamp, parent_timestamp } => {
+                write!(f, "Block timestamp {} is before parent timestamp {}", timestamp, parent_timestamp)
+            }
+            BlockError::ParentHashMismatch => write!(f, "Parent hash mismatch"),
+            BlockError::HeightMismatch { expected, actual } => {
+                write!(f, "Wrong block height: expected {}, got {}", expected, actual)
+            }
+            BlockError::HeightOverflow => write!(f, "Parent block height overflows"),
+            BlockError::MerkleRootMismatch => write!(f, "Merkle root does not match transactions"),
+            BlockError::DuplicateTransaction(txid) => write!(f, "Duplicate transaction {}", txid),
+            BlockError::Decode(e) => write!(f, "Block decode error: {}", e),
+        }
+    }
+}
+
+// 为 BlockError 实现 Error trait
+impl std::error::Error for BlockError {}
+
+// 实现从 DecodeError 到 BlockError 的转换
+impl From<DecodeError> for BlockError {
+    fn from(err: DecodeError) -> Self {
+        BlockError::Decode(err)
+    }
+}
+
+impl BlockHeader {
+    /// 规范二进制编码的长度（字节）
+    pub const ENCODED_LEN: usize = 4 + Hash256::LEN + 8 + 8 + Hash256::LEN + Hash256::LEN + 4 + 8;
+
+    /// 规范二进制编码：所有字段按声明顺序定长拼接，整数为大端序
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::ENCODED_LEN);
+        bytes.extend_from_slice(&self.version.to_be_bytes());
+        bytes.extend_from_slice(self.parent_hash.as_bytes());
+        bytes.extend_from_slice(&self.height.to_be_bytes());
+        bytes.extend_from_slice(&self.timestamp.to_be_bytes());
+        bytes.extend_from_slice(self.merkle_root.as_bytes());
+        bytes.extend_from_slice(self.state_root.as_bytes());
+        bytes.extend_from_slice(&self.bits.to_be_bytes());
+        bytes.extend_from_slice(&self.nonce.to_be_bytes());
+        bytes
+    }
+
+    /// 从规范二进制编码解码
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader = Reader::new(bytes);
+        let header = Self::read_from(&mut reader)?;
+        reader.finish()?;
+        Ok(header)
+    }
+
+    // 从 reader 中读取区块头
+    pub(crate) fn read_from(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(BlockHeader {
+            version: reader.read_u32()?,
+            parent_hash: Hash256::from_array(reader.read_array()?),
+            height: reader.read_u64()?,
+            timestamp: reader.read_u64()?,
+            merkle_root: Hash256::from_array(reader.read_array()?),
+            state_root: Hash256::from_array(reader.read_array()?),
+            bits: reader.read_u32()?,
+            nonce: reader.read_u64()?,
+        })
+    }
+
+    /// 区块哈希
+    pub fn hash(&self) -> BlockHash {
+        Hash256::double_sha256(&self.encode())
+    }
+
+    /// 校验与父区块的衔接关系：父哈希、高度和时间戳
+    pub fn validate_against_parent(&self, parent: &BlockHeader) -> Result<(), BlockError> {
+        if self.parent_hash != parent.hash() {
+            return Err(BlockError::ParentHashMismatch);
+        }
+        let expected = parent.height.checked_add(1).ok_or(BlockError::HeightOverflow)?;
+        if self.height != expected {
+            return Err(BlockError::HeightMismatch {
+                expected,
+                actual: self.height,
+            });
+        }
+        if self.timestamp < parent.timestamp {
+            return Err(BlockError::TimestampBeforeParent {
+                timestamp: self.timestamp,
+                parent_timestamp: parent.timestamp,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Block {
+    /// 创建区块，并根据交易列表填写区块头中的默克尔根
+    pub fn new(mut header: BlockHeader, transactions: Vec<Transaction>) -> Self {
+        header.merkle_root = Self::compute_merkle_root(&transactions);
+        Block { header, transactions }
+    }
+
+    /// 计算交易列表的默克尔根
+    pub fn compute_merkle_root(transactions: &[Transaction]) -> Hash256 {
+        let txids: Vec<TxId> = transactions.iter().map(Transaction::txid).collect();
+        merkle_root(&txids)
+    }
+
+    /// 生成第 `index` 笔交易的包含证明，可对照区块头中的默克尔根校验
+    pub fn transaction_proof(&self, index: usize) -> Option<MerkleProof> {
+        let txids: Vec<TxId> = self.transactions.iter().map(Transaction::txid).collect();
+        MerkleTree::new(&txids).proof(index)
+    }
+
+    /// 区块哈希
+    pub fn hash(&self) -> BlockHash {
+        self.header.hash()
+    }
+
+    /// 区块高度
+    pub fn height(&self) -> u64 {
+        self.header.height
+    }
+
+    /// 规范二进制编码的长度（字节）
+    pub fn encoded_len(&self) -> usize {
+        BlockHeader::ENCODED_LEN + 4 + self.transactions.len() * Transaction::ENCODED_LEN
+    }
+
+    /// 规范二进制编码：区块头 + 交易数（u32） + 各交易的编码
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.encoded_len());
+        bytes.extend_from_slice(&self.header.encode());
+        bytes.extend_from_slice(&(self.transactions.len() as u32).to_be_bytes());
+        for transaction in &self.transactions {
+            bytes.extend_from_slice(&transaction.encode());
+        }
+        bytes
+    }
+
+    /// 从规范二进制编码解码，不做结构校验
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader = Reader::new(bytes);
+        let header = BlockHeader::read_from(&mut reader)?;
+        let count = reader.read_u32()? as usize;
+        // 交易为定长编码，先检查剩余长度，避免按恶意的交易数预分配内存
+        if reader.remaining() < count.saturating_mul(Transaction::ENCODED_LEN) {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let mut transactions = Vec::with_capacity(count);
+        for _ in 0..count {
+            transactions.push(Transaction::read_from(&mut reader)?);
+        }
+        reader.finish()?;
+        Ok(Block { header, transactions })
+    }
+
+    /// 结构校验：大小限制、时间戳上限、交易不重复且默克尔根与交易列表一致
+    ///
+    /// # Arguments
+    ///
+    /// * `limits` - 区块限制参数
+    /// * `now` - 本地当前时间（Unix 时间戳，秒）
+    pub fn validate_structure(&self, limits: &BlockLimits, now: u64) -> Result<(), BlockError> {
+        if self.transactions.len() > limits.max_transactions {
+            return Err(BlockError::TooManyTransactions {
+                count: self.transactions.len(),
+                max: limits.max_transactions,
+            });
+        }
+        let size = self.encoded_len();
+        if size > limits.max_block_size {
+            return Err(BlockError::BlockTooLarge {
+                size,
+                max: limits.max_block_size,
+            });
+        }
+        let max_timestamp = now.saturating_add(limits.max_future_drift);
+        if self.header.timestamp > max_timestamp {
+            return Err(BlockError::TimestampTooFarInFuture {
+                timestamp: self.header.timestamp,
+                max: max_timestamp,
+            });
+        }
+
+        let mut seen = HashSet::with_capacity(self.transactions.len());
+        let mut txids = Vec::with_capacity(self.transactions.len());
+        for transaction in &self.transactions {
+            let txid = transaction.txid();
+            if !seen.insert(txid) {
+                return Err(BlockError::DuplicateTransaction(txid));
+            }
+            txids.push(txid);
+        }
+        if merkle_root(&txids) != self.header.merkle_root {
+            return Err(BlockError::MerkleRootMismatch);
+        }
+        Ok(())
+    }
+
+    /// 完整的结构校验：在 `validate_structure` 之外检查与父区块的衔接关系
+    pub fn validate(&self, parent: &BlockHeader, limits: &BlockLimits, now: u64) -> Result<(), BlockError> {
+        self.header.validate_against_parent(parent)?;
+        self.validate_structure(limits, now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::testing::{child_header, genesis_header, GENESIS_TIMESTAMP};
+    use crate::types::{Address, Amount, KeyPair, NetworkKind};
+
+    const NOW: u64 = GENESIS_TIMESTAMP + 600;
+
+    fn sample_transactions(count: u64) -> Vec<Transaction> {
+        let keypair = KeyPair::from_secret_bytes(&[5u8; 32]);
+        let recipient = Address::from_hash([6u8; 20], NetworkKind::Regtest);
+        (0..count)
+            .map(|nonce| Transaction::new_signed(&keypair, 3, nonce, recipient, "1".parse().unwrap(), Amount::zero()))
+            .collect()
+    }
+
+    fn child_block(parent: &BlockHeader, transactions: Vec<Transaction>) -> Block {
+        Block::new(child_header(parent, 60), transactions)
+    }
+
+    #[test]
+    fn test_block_encoding() {
+        let block = child_block(&genesis_header(0x207f_ffff), sample_transactions(3));
+        let bytes = block.encode();
+        assert_eq!(bytes.len(), block.encoded_len());
+        assert_eq!(Block::decode(&bytes).unwrap(), block);
+        assert_eq!(BlockHeader::decode(&block.header.encode()).unwrap(), block.header);
+        assert_eq!(block.header.encode().len(), BlockHeader::ENCODED_LEN);
+
+        assert_eq!(Block::decode(&bytes[..bytes.len() - 1]), Err(DecodeError::UnexpectedEnd));
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert_eq!(Block::decode(&extended), Err(DecodeError::TrailingBytes));
+
+        let proof = block.transaction_proof(1).unwrap();
+        assert!(proof.verify(&block.transactions[1].txid(), &block.header.merkle_root));
+        assert!(block.transaction_proof(3).is_none());
+
+        // 伪造的巨大交易数不会导致预分配
+        let mut forged = block.header.encode();
+        forged.extend_from_slice(&u32::MAX.to_be_bytes());
+        assert_eq!(Block::decode(&forged), Err(DecodeError::UnexpectedEnd));
+    }
+
+    #[test]
+    fn test_header_hash_commits_to_all_fields() {
+        let header = genesis_header(0x207f_ffff);
+        let mut changed = header.clone();
+        changed.nonce += 1;
+        assert_ne!(changed.hash(), header.hash());
+        changed = header.clone();
+        changed.state_root = Hash256::double_sha256(b"state");
+        assert_ne!(changed.hash(), header.hash());
+    }
+
+    #[test]
+    fn test_validate_block() {
+        let genesis = genesis_header(0x207f_ffff);
+        let limits = BlockLimits::default();
+        let block = child_block(&genesis, sample_transactions(2));
+        block.validate(&genesis, &limits, NOW).unwrap();
+
+        // 默克尔根与交易不一致
+        let mut tampered = block.clone();
+        tampered.transactions.pop();
+        assert_eq!(tampered.validate(&genesis, &limits, NOW), Err(BlockError::MerkleRootMismatch));
+
+        // 重复交易
+        let mut transactions = sample_transactions(1);
+        transactions.push(transactions[0].clone());
+        let duplicated = child_block(&genesis, transactions);
+        assert!(matches!(
+            duplicated.validate(&genesis, &limits, NOW),
+            Err(BlockError::DuplicateTransaction(_))
+        ));
+
+        // 时间戳
+        let mut future = block.clone();
+        future.header.timestamp = NOW + limits.max_future_drift + 1;
+        assert!(matches!(
+            future.validate(&genesis, &limits, NOW),
+            Err(BlockError::TimestampTooFarInFuture { .. })
+        ));
+        let mut past = block.clone();
+        past.header.timestamp = genesis.timestamp - 1;
+        assert!(matches!(
+            past.validate(&genesis, &limits, NOW),
+            Err(BlockError::TimestampBeforeParent { .. })
+        ));
+
+        // 与父区块的衔接
+        let mut orphan = block.clone();
+        orphan.header.parent_hash = Hash256::ZERO;
+        assert_eq!(orphan.validate(&genesis, &limits, NOW), Err(BlockError::ParentHashMismatch));
+        let mut skipped = block.clone();
+        skipped.header.height = 2;
+        assert_eq!(
+            skipped.validate(&genesis, &limits, NOW),
+            Err(BlockError::HeightMismatch { expected: 1, actual: 2 })
+        );
+        let mut tip = genesis.clone();
+        tip.height = u64::MAX;
+        let mut overflow = block.header.clone();
+        overflow.parent_hash = tip.hash();
+        assert_eq!(overflow.validate_against_parent(&tip), Err(BlockError::HeightOverflow));
+
+        // 大小限制
+        let small = BlockLimits {
+            max_transactions: 1,
+            ..limits
+        };
+        assert!(matches!(
+            block.validate(&genesis, &small, NOW),
+            Err(BlockError::TooManyTransactions { count: 2, max: 1 })
+        ));
+        let small = BlockLimits {
+            max_block_size: BlockHeader::ENCODED_LEN + 4 + Transaction::ENCODED_LEN,
+            ..limits
+        };
+        assert!(matches!(
+            block.validate(&genesis, &small, NOW),
+            Err(BlockError::BlockTooLarge { .. })
+        ));
+    }
+}
@@ -0,0 +1,231 @@
+use crate::types::{Hash256, TxId};
+use serde::{Deserialize, Serialize};
+
+// 叶子节点和内部节点使用不同的前缀，防止把内部节点伪装成叶子（第二原像攻击）
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+// 叶子节点哈希：H(0x00 || txid)
+fn hash_leaf(leaf: &TxId) -> Hash256 {
+    let mut data = [0u8; 1 + Hash256::LEN];
+    data[0] = LEAF_PREFIX;
+    data[1..].copy_from_slice(leaf.as_bytes());
+    Hash256::double_sha256(&data)
+}
+
+// 内部节点哈希：H(0x01 || left || right)
+fn hash_node(left: &Hash256, right: &Hash256) -> Hash256 {
+    let mut data = [0u8; 1 + 2 * Hash256::LEN];
+    data[0] = NODE_PREFIX;
+    data[1..1 + Hash256::LEN].copy_from_slice(left.as_bytes());
+    data[1 + Hash256::LEN..].copy_from_slice(right.as_bytes());
+    Hash256::double_sha256(&data)
+}
+
+/// 交易 ID 上的默克尔树
+///
+/// 某一层节点数为奇数时，最后一个节点直接提升到上一层，而不是与自身配对。
+/// 这样 `[a, b, c]` 和 `[a, b, c, c]` 的根不同，避免了重复末尾叶子的可塑性问题。
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    // levels[0] 为叶子哈希，最后一层只有根节点
+    levels: Vec<Vec<Hash256>>,
+}
+
+/// 默克尔包含证明
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    /// 叶子在树中的位置
+    pub leaf_index: u32,
+    /// 叶子总数，用于确定每一层是否存在兄弟节点
+    pub leaf_count: u32,
+    /// 从叶子到根路径上的兄弟节点，被直接提升的层没有兄弟节点
+    pub siblings: Vec<Hash256>,
+}
+
+impl MerkleTree {
+    /// 由交易 ID 列表构建默克尔树
+    pub fn new(leaves: &[TxId]) -> Self {
+        let mut levels = vec![leaves.iter().map(hash_leaf).collect::<Vec<_>>()];
+        while levels.last().is_some_and(|level| level.len() > 1) {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => hash_node(left, right),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        MerkleTree { levels }
+    }
+
+    /// 叶子数量
+    pub fn leaf_count(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// 默克尔根，没有叶子时为全零哈希
+    pub fn root(&self) -> Hash256 {
+        self.levels.last().and_then(|level| level.first()).copied().unwrap_or(Hash256::ZERO)
+    }
+
+    /// 生成第 `index` 个叶子的包含证明，越界时返回 None
+    pub fn proof(&self, index: usize) -> Option<MerkleProof> {
+        if index >= self.leaf_count() {
+            return None;
+        }
+        let mut siblings = Vec::new();
+        let mut position = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = position ^ 1;
+            if sibling < level.len() {
+                siblings.push(level[sibling]);
+            }
+            position /= 2;
+        }
+        Some(MerkleProof {
+            leaf_index: index as u32,
+            leaf_count: self.leaf_count() as u32,
+            siblings,
+        })
+    }
+}
+
+impl MerkleProof {
+    /// 校验证明：`leaf` 位于以 `root` 为根的树的 `leaf_index` 位置
+    pub fn verify(&self, leaf: &TxId, root: &Hash256) -> bool {
+        if self.leaf_index >= self.leaf_count {
+            return false;
+        }
+        let mut hash = hash_leaf(leaf);
+        let mut position = self.leaf_index;
+        let mut count = self.leaf_count;
+        let mut siblings = self.siblings.iter();
+        while count > 1 {
+            if position % 2 == 1 {
+                let Some(sibling) = siblings.next() else {
+                    return false;
+                };
+                hash = hash_node(sibling, &hash);
+            } else if position + 1 < count {
+                let Some(sibling) = siblings.next() else {
+                    return false;
+                };
+                hash = hash_node(&hash, sibling);
+            }
+            position /= 2;
+            count = count.div_ceil(2);
+        }
+        siblings.next().is_none() && hash == *root
+    }
+}
+
+/// 计算一组交易 ID 的默克尔根，没有叶子时返回全零哈希
+pub fn merkle_root(leaves: &[TxId]) -> Hash256 {
+    MerkleTree::new(leaves).root()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn leaves(count: usize) -> Vec<TxId> {
+        (0..count as u32).map(|i| Hash256::double_sha256(&i.to_be_bytes())).collect()
+    }
+
+    #[test]
+    fn test_merkle_root() {
+        let leaves = leaves(5);
+
+        assert_eq!(merkle_root(&[]), Hash256::ZERO);
+        assert_eq!(merkle_root(&leaves[..1]), hash_leaf(&leaves[0]));
+        assert_ne!(merkle_root(&leaves[..1]), leaves[0]);
+
+        let root = merkle_root(&leaves);
+        assert_ne!(root, merkle_root(&leaves[..4]));
+
+        // 交换叶子顺序会改变根
+        let mut swapped = leaves.clone();
+        swapped.swap(0, 1);
+        assert_ne!(merkle_root(&swapped), root);
+    }
+
+    #[test]
+    fn test_duplicate_last_leaf_changes_root() {
+        for count in [1, 3, 5, 6, 7] {
+            let leaves = leaves(count);
+            let mut duplicated = leaves.clone();
+            duplicated.push(*leaves.last().unwrap());
+            assert_ne!(merkle_root(&duplicated), merkle_root(&leaves));
+        }
+    }
+
+    #[test]
+    fn test_internal_node_is_not_a_valid_leaf() {
+        let leaves = leaves(4);
+        let tree = MerkleTree::new(&leaves);
+        // 把第一层的内部节点当作叶子，构造一个两叶子的“树”
+        let forged = MerkleProof {
+            leaf_index: 0,
+            leaf_count: 2,
+            siblings: vec![tree.levels[1][1]],
+        };
+        assert!(!forged.verify(&tree.levels[1][0], &tree.root()));
+    }
+
+    #[test]
+    fn test_proof_serde() {
+        let proof = MerkleTree::new(&leaves(3)).proof(2).unwrap();
+        let serialized = serde_json::to_string(&proof).unwrap();
+        assert_eq!(serde_json::from_str::<MerkleProof>(&serialized).unwrap(), proof);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn prop_every_leaf_has_a_valid_proof(count in 1usize..130, seed in any::<u64>()) {
+            let leaves: Vec<TxId> = (0..count as u64)
+                .map(|i| Hash256::double_sha256(&(seed ^ i).to_be_bytes()))
+                .collect();
+            let tree = MerkleTree::new(&leaves);
+            let root = tree.root();
+            prop_assert_eq!(root, merkle_root(&leaves));
+            prop_assert!(tree.proof(count).is_none());
+
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = tree.proof(index).unwrap();
+                prop_assert!(proof.siblings.len() <= 64 - (count as u64).leading_zeros() as usize);
+                prop_assert!(proof.verify(leaf, &root));
+
+                // 错误的叶子、位置、根或兄弟节点都无法通过校验
+                let other = Hash256::double_sha256(leaf.as_bytes());
+                prop_assert!(!proof.verify(&other, &root));
+                prop_assert!(!proof.verify(leaf, &other));
+                if count > 1 {
+                    let moved = MerkleProof { leaf_index: ((index + 1) % count) as u32, ..proof.clone() };
+                    prop_assert!(!moved.verify(leaf, &root));
+                    let mut tampered = proof.clone();
+                    tampered.siblings[0] = other;
+                    prop_assert!(!tampered.verify(leaf, &root));
+                    let mut truncated = proof.clone();
+                    truncated.siblings.pop();
+                    prop_assert!(!truncated.verify(leaf, &root));
+                }
+            }
+        }
+
+        #[test]
+        fn prop_root_commits_to_leaf_count(count in 1usize..100) {
+            let leaves = leaves(count);
+            let mut extended = leaves.clone();
+            extended.push(*leaves.last().unwrap());
+            prop_assert_ne!(merkle_root(&extended), merkle_root(&leaves));
+        }
+    }
+}
@@ -0,0 +1,11 @@
+pub mod block;
+pub mod merkle;
+pub mod spec;
+pub mod supply;
+#[cfg(test)]
+pub(crate) mod testing;
+
+pub use block::{Block, BlockError, BlockHash, BlockHeader, BlockLimits};
+pub use merkle::{MerkleProof, MerkleTree};
+pub use spec::{ChainSpec, ChainSpecError};
+pub use supply::SubsidySchedule;
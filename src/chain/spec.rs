@@ -0,0 +1,289 @@
+use crate::chain::block::BlockLimits;
+use crate::chain::supply::SubsidySchedule;
+use crate::consensus::{ConsensusEngine, ConsensusError, PosParams, PowParams, Retarget};
+use crate::types::{Amount, NetworkKind};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+// 以 FAIC 十进制字符串的形式序列化 Amount，方便手工编辑链规格文件
+pub mod serde_amount {
+    use crate::types::Amount;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    // 序列化 Amount 为字符串
+    pub fn serialize<S>(amount: &Amount, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&amount.to_string())
+    }
+
+    // 从字符串反序列化 Amount
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Amount, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// 链规格：描述一条链的标识和货币发行策略
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChainSpec {
+    /// 链名称
+    pub name: String,
+    /// 链 ID，写入交易签名以防止跨链重放
+    pub chain_id: u32,
+    /// 网络类型，决定地址前缀
+    pub network: NetworkKind,
+    /// 出块奖励曲线
+    pub subsidy: SubsidySchedule,
+    /// 总量硬上限
+    #[serde(with = "serde_amount")]
+    pub max_supply: Amount,
+    /// 区块大小和时间戳的限制，未配置时使用默认值
+    #[serde(default)]
+    pub block_limits: BlockLimits,
+    /// 共识引擎，未配置时使用工作量证明
+    #[serde(default)]
+    pub consensus: ConsensusEngine,
+    /// 工作量证明参数，未配置时使用主网参数
+    #[serde(default)]
+    pub pow: PowParams,
+    /// 权益证明参数，未配置时使用默认值
+    #[serde(default)]
+    pub pos: PosParams,
+}
+
+// 自定义错误类型
+#[derive(Debug)]
+pub enum ChainSpecError {
+    IoError(std::io::Error),  // 文件操作错误
+    TomlError(toml::de::Error),  // TOML解析错误
+    ZeroHalvingInterval,  // 减半间隔为零
+    SupplyOverflow,  // 累计发行量超过 Amount 的表示范围
+    ExceedsMaxSupply { total: Amount, max_supply: Amount },  // 累计发行量超过硬上限
+    Consensus(ConsensusError),  // 共识参数不合法
+}
+
+// 为 ChainSpecError 实现 Display trait，用于打印错误信息
+impl std::fmt::Display for ChainSpecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ChainSpecError::IoError(e) => write!(f, "IO error: {}", e),
+            ChainSpecError::TomlError(e) => write!(f, "TOML deserialization error: {}", e),
+            ChainSpecError::ZeroHalvingInterval => write!(f, "Halving interval must be greater than zero"),
+            ChainSpecError::SupplyOverflow => write!(f, "Issued supply overflows the maximum amount"),
+            ChainSpecError::ExceedsMaxSupply { total, max_supply } => {
+                write!(f, "Issued supply {} exceeds max supply {}", total, max_supply)
+            }
+            ChainSpecError::Consensus(e) => write!(f, "Consensus error: {}", e),
+        }
+    }
+}
+
+// 为 ChainSpecError 实现 Error trait
+impl std::error::Error for ChainSpecError {}
+
+// 实现从 std::io::Error 到 ChainSpecError 的转换
+impl From<std::io::Error> for ChainSpecError {
+    fn from(err: std::io::Error) -> Self {
+        ChainSpecError::IoError(err)
+    }
+}
+
+// 实现从 ConsensusError 到 ChainSpecError 的转换
+impl From<ConsensusError> for ChainSpecError {
+    fn from(err: ConsensusError) -> Self {
+        ChainSpecError::Consensus(err)
+    }
+}
+
+// 实现从 toml::de::Error 到 ChainSpecError 的转换
+impl From<toml::de::Error> for ChainSpecError {
+    fn from(err: toml::de::Error) -> Self {
+        ChainSpecError::TomlError(err)
+    }
+}
+
+impl ChainSpec {
+    /// 主网规格：初始奖励 100 FAIC，每 1,050,000 个区块减半，总量上限 2.1 亿 FAIC，
+    /// 目标出块间隔 2 分钟，ASERT 半衰期 2 天
+    pub fn mainnet() -> Self {
+        ChainSpec {
+            name: "mainnet".to_string(),
+            chain_id: 1,
+            network: NetworkKind::Mainnet,
+            subsidy: SubsidySchedule {
+                initial_subsidy: Amount::from_u128(100 * 100_000_000),
+                halving_interval: 1_050_000,
+                tail_emission: Amount::zero(),
+            },
+            max_supply: Amount::from_u128(210_000_000 * 100_000_000),
+            block_limits: BlockLimits::default(),
+            consensus: ConsensusEngine::ProofOfWork,
+            pow: PowParams::default(),
+            pos: PosParams::default(),
+        }
+    }
+
+    /// 测试网规格：发行策略与主网相同
+    pub fn testnet() -> Self {
+        ChainSpec {
+            name: "testnet".to_string(),
+            chain_id: 2,
+            network: NetworkKind::Testnet,
+            ..Self::mainnet()
+        }
+    }
+
+    /// 本地回归测试规格：每 150 个区块减半，难度固定为极低值，CPU 可以即时出块
+    pub fn regtest() -> Self {
+        ChainSpec {
+            name: "regtest".to_string(),
+            chain_id: 3,
+            network: NetworkKind::Regtest,
+            subsidy: SubsidySchedule {
+                halving_interval: 150,
+                ..Self::mainnet().subsidy
+            },
+            max_supply: Amount::from_u128(30_000 * 100_000_000),
+            block_limits: BlockLimits::default(),
+            pow: PowParams {
+                pow_limit: 0x207f_ffff,
+                target_spacing: 120,
+                retarget: Retarget::Fixed,
+            },
+            consensus: ConsensusEngine::ProofOfWork,
+            pos: PosParams::default(),
+        }
+    }
+
+    /// 校验发行策略和共识参数：任何高度下的累计发行量都不能超过硬上限
+    ///
+    /// 区块高度最大为 u64::MAX，因此只需检查该高度的累计发行量。
+    pub fn validate(&self) -> Result<(), ChainSpecError> {
+        self.pow.validate()?;
+        self.pos.validate()?;
+        if self.subsidy.halving_interval == 0 {
+            return Err(ChainSpecError::ZeroHalvingInterval);
+        }
+        let total = self
+            .subsidy
+            .issued_supply(u64::MAX)
+            .ok_or(ChainSpecError::SupplyOverflow)?;
+        if total > self.max_supply {
+            return Err(ChainSpecError::ExceedsMaxSupply {
+                total,
+                max_supply: self.max_supply,
+            });
+        }
+        Ok(())
+    }
+
+    /// 指定高度区块的出块奖励
+    pub fn block_subsidy(&self, height: u64) -> Amount {
+        self.subsidy.block_subsidy(height)
+    }
+
+    /// 到指定高度（包含）为止的累计发行量，结果不会超过硬上限
+    pub fn issued_supply(&self, height: u64) -> Amount {
+        self.subsidy
+            .issued_supply(height)
+            .map_or(self.max_supply, |total| total.min(self.max_supply))
+    }
+
+    /// 从文件加载链规格，并校验发行策略
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 链规格文件的路径
+    pub fn load_from_file(path: &str) -> Result<Self, ChainSpecError> {
+        let spec_str = fs::read_to_string(path)?;
+        let spec: ChainSpec = toml::from_str(&spec_str)?;
+        spec.validate()?;
+        Ok(spec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_specs_are_valid() {
+        for spec in [ChainSpec::mainnet(), ChainSpec::testnet(), ChainSpec::regtest()] {
+            spec.validate().unwrap();
+            assert!(spec.issued_supply(u64::MAX) <= spec.max_supply);
+        }
+        assert_eq!(ChainSpec::mainnet().block_subsidy(1), "100".parse().unwrap());
+        assert_eq!(ChainSpec::regtest().block_subsidy(151), "50".parse().unwrap());
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_schedules() {
+        let mut spec = ChainSpec::regtest();
+        spec.subsidy.halving_interval = 0;
+        assert!(matches!(spec.validate(), Err(ChainSpecError::ZeroHalvingInterval)));
+
+        let mut spec = ChainSpec::regtest();
+        spec.max_supply = "1000".parse().unwrap();
+        assert!(matches!(spec.validate(), Err(ChainSpecError::ExceedsMaxSupply { .. })));
+
+        // 尾部通胀会让总量在足够高的高度上超过任何较小的上限
+        let mut spec = ChainSpec::mainnet();
+        spec.subsidy.tail_emission = "1".parse().unwrap();
+        assert!(spec.validate().is_err());
+
+        let mut spec = ChainSpec::mainnet();
+        spec.subsidy.tail_emission = Amount::max_value();
+        assert!(matches!(spec.validate(), Err(ChainSpecError::SupplyOverflow)));
+
+        let mut spec = ChainSpec::regtest();
+        spec.pow.retarget = Retarget::Asert { half_life: 0 };
+        assert!(matches!(spec.validate(), Err(ChainSpecError::Consensus(_))));
+
+        let mut spec = ChainSpec::regtest();
+        spec.pos.slash_percent = 101;
+        assert!(matches!(spec.validate(), Err(ChainSpecError::Consensus(_))));
+    }
+
+    #[test]
+    fn test_load_chain_spec_from_file() {
+        let path = "test_chain_spec.toml";
+        fs::write(
+            path,
+            r#"
+name = "devnet"
+chain_id = 42
+network = "Testnet"
+max_supply = "1,000,000"
+
+[subsidy]
+initial_subsidy = "10"
+halving_interval = 1000
+tail_emission = "0"
+"#,
+        )
+        .unwrap();
+
+        let spec = ChainSpec::load_from_file(path).unwrap();
+        assert_eq!(spec.chain_id, 42);
+        assert_eq!(spec.block_limits, BlockLimits::default());
+        assert_eq!(spec.consensus, ConsensusEngine::ProofOfWork);
+        assert_eq!(spec.pow, PowParams::default());
+        assert_eq!(spec.block_subsidy(1001), "5".parse().unwrap());
+        assert_eq!(spec.subsidy.issued_supply(1000), Some("10000".parse().unwrap()));
+
+        // 序列化后可以重新加载
+        fs::write(path, toml::to_string(&spec).unwrap()).unwrap();
+        assert_eq!(ChainSpec::load_from_file(path).unwrap(), spec);
+
+        // 超过上限的规格在加载时被拒绝
+        fs::write(path, toml::to_string(&ChainSpec { max_supply: "1".parse().unwrap(), ..spec }).unwrap()).unwrap();
+        assert!(ChainSpec::load_from_file(path).is_err());
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,144 @@
+use crate::types::Amount;
+use serde::{Deserialize, Serialize};
+
+/// 出块奖励曲线
+///
+/// 第 1 个区块起，每 `halving_interval` 个区块奖励减半；减半后的奖励不会低于 `tail_emission`。
+/// `tail_emission` 为零时即为纯减半曲线。创世区块（高度 0）没有出块奖励。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SubsidySchedule {
+    /// 初始出块奖励
+    #[serde(with = "crate::chain::spec::serde_amount")]
+    pub initial_subsidy: Amount,
+    /// 减半间隔（区块数）
+    pub halving_interval: u64,
+    /// 尾部通胀：每个区块的最低奖励
+    #[serde(with = "crate::chain::spec::serde_amount")]
+    pub tail_emission: Amount,
+}
+
+impl SubsidySchedule {
+    /// 计算指定高度区块的出块奖励
+    pub fn block_subsidy(&self, height: u64) -> Amount {
+        if height == 0 || self.halving_interval == 0 {
+            return Amount::zero();
+        }
+        let era = (height - 1) / self.halving_interval;
+        self.era_subsidy(era)
+    }
+
+    /// 计算从创世区块到指定高度（包含）累计发行的总量，溢出时返回 None
+    pub fn issued_supply(&self, height: u64) -> Option<Amount> {
+        if self.halving_interval == 0 {
+            return Some(Amount::zero());
+        }
+
+        let mut total = Amount::zero();
+        let mut start = 1u64;
+        let mut era = 0u64;
+        while start <= height {
+            let subsidy = self.era_subsidy(era);
+            // 奖励已经降到尾部通胀水平，之后每个区块的奖励都相同
+            let end = if subsidy == self.tail_emission {
+                height
+            } else {
+                start.saturating_add(self.halving_interval - 1).min(height)
+            };
+            let blocks = end - start + 1;
+            total = total.checked_add(&subsidy.checked_mul_u64(blocks)?)?;
+
+            if end == height {
+                break;
+            }
+            start = end + 1;
+            era += 1;
+        }
+        Some(total)
+    }
+
+    /// 第 `era` 个减半周期内的出块奖励
+    fn era_subsidy(&self, era: u64) -> Amount {
+        let halved = match u32::try_from(era) {
+            Ok(shift) if shift < u128::BITS => self.initial_subsidy.as_u128() >> shift,
+            _ => 0,
+        };
+        Amount::from_u128(halved).max(self.tail_emission)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE_FAIC: u128 = 100_000_000;
+
+    fn halving(initial: u128, interval: u64) -> SubsidySchedule {
+        SubsidySchedule {
+            initial_subsidy: Amount::from_u128(initial),
+            halving_interval: interval,
+            tail_emission: Amount::zero(),
+        }
+    }
+
+    #[test]
+    fn test_block_subsidy_halving() {
+        let schedule = halving(50 * ONE_FAIC, 10);
+        assert_eq!(schedule.block_subsidy(0), Amount::zero());
+        assert_eq!(schedule.block_subsidy(1), Amount::from_u128(50 * ONE_FAIC));
+        assert_eq!(schedule.block_subsidy(10), Amount::from_u128(50 * ONE_FAIC));
+        assert_eq!(schedule.block_subsidy(11), Amount::from_u128(25 * ONE_FAIC));
+        assert_eq!(schedule.block_subsidy(21), Amount::from_u128(125 * ONE_FAIC / 10));
+        // 减半 128 次以后奖励为零
+        assert_eq!(schedule.block_subsidy(10 * 200), Amount::zero());
+        assert_eq!(schedule.block_subsidy(u64::MAX), Amount::zero());
+    }
+
+    #[test]
+    fn test_block_subsidy_tail_emission() {
+        let schedule = SubsidySchedule {
+            tail_emission: Amount::from_u128(10 * ONE_FAIC),
+            ..halving(50 * ONE_FAIC, 10)
+        };
+        assert_eq!(schedule.block_subsidy(21), Amount::from_u128(125 * ONE_FAIC / 10));
+        assert_eq!(schedule.block_subsidy(31), Amount::from_u128(10 * ONE_FAIC));
+        assert_eq!(schedule.block_subsidy(u64::MAX), Amount::from_u128(10 * ONE_FAIC));
+    }
+
+    #[test]
+    fn test_issued_supply_matches_sum_of_subsidies() {
+        let schedules = [
+            halving(50 * ONE_FAIC, 7),
+            halving(3, 5),
+            SubsidySchedule {
+                tail_emission: Amount::from_u128(ONE_FAIC),
+                ..halving(50 * ONE_FAIC, 4)
+            },
+        ];
+        for schedule in schedules {
+            let mut expected = Amount::zero();
+            for height in 0..200 {
+                expected = expected.checked_add(&schedule.block_subsidy(height)).unwrap();
+                assert_eq!(schedule.issued_supply(height), Some(expected), "height {}", height);
+            }
+        }
+    }
+
+    #[test]
+    fn test_issued_supply_converges() {
+        let schedule = halving(50 * ONE_FAIC, 210_000);
+        let total = schedule.issued_supply(u64::MAX).unwrap();
+        // 不超过 2 * 初始奖励 * 减半间隔
+        assert!(total <= Amount::from_u128(2 * 50 * ONE_FAIC * 210_000));
+        assert_eq!(schedule.issued_supply(u64::MAX), schedule.issued_supply(210_000 * 200));
+    }
+
+    #[test]
+    fn test_issued_supply_overflow() {
+        let schedule = SubsidySchedule {
+            tail_emission: Amount::max_value(),
+            ..halving(u128::MAX, 1)
+        };
+        assert_eq!(schedule.issued_supply(1), Some(Amount::max_value()));
+        assert_eq!(schedule.issued_supply(2), None);
+    }
+}
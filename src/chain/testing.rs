@@ -0,0 +1,56 @@
+use crate::chain::{Block, BlockHeader, ChainSpec};
+use crate::types::{Address, Hash256, KeyPair, NetworkKind};
+
+/// 测试用创世区块的时间戳
+pub(crate) const GENESIS_TIMESTAMP: u64 = 1_700_000_000;
+
+/// 测试用创世区块头：除难度目标外，哈希和随机数均为零
+pub(crate) fn genesis_header(bits: u32) -> BlockHeader {
+    BlockHeader {
+        version: 1,
+        parent_hash: Hash256::ZERO,
+        height: 0,
+        timestamp: GENESIS_TIMESTAMP,
+        merkle_root: Hash256::ZERO,
+        state_root: Hash256::ZERO,
+        bits,
+        nonce: 0,
+    }
+}
+
+/// 没有交易的测试用创世区块
+pub(crate) fn genesis_block(bits: u32) -> Block {
+    Block::new(genesis_header(bits), Vec::new())
+}
+
+/// 在 parent 之上构造区块头：高度加一，时间戳增加 spacing 秒，其余字段与 parent 相同
+pub(crate) fn child_header(parent: &BlockHeader, spacing: u64) -> BlockHeader {
+    BlockHeader {
+        parent_hash: parent.hash(),
+        height: parent.height + 1,
+        timestamp: parent.timestamp + spacing,
+        ..parent.clone()
+    }
+}
+
+/// 状态相关测试共用的链规格和账户
+pub(crate) struct Fixture {
+    pub spec: ChainSpec,
+    pub alice: KeyPair,
+    pub alice_address: Address,
+    pub bob: Address,
+    pub carol: Address,
+}
+
+/// 回归测试网络上的固定账户：alice 持有私钥，bob 和 carol 只作为收款地址
+pub(crate) fn fixture() -> Fixture {
+    let alice = KeyPair::from_secret_bytes(&[1u8; 32]);
+    let alice_address = Address::from_public_key(&alice.public_key(), NetworkKind::Regtest);
+    Fixture {
+        spec: ChainSpec::regtest(),
+        alice,
+        alice_address,
+        bob: Address::from_hash([2u8; 20], NetworkKind::Regtest),
+        carol: Address::from_hash([3u8; 20], NetworkKind::Regtest),
+    }
+}
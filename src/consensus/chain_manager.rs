@@ -0,0 +1,495 @@
+use crate::chain::{Block, BlockHash, ChainSpec};
+use crate::consensus::error::ConsensusError;
+use crate::consensus::fork_choice::ForkChoice;
+use crate::consensus::pos::{ValidatorSet, Vote};
+use crate::consensus::ConsensusEngine;
+use crate::state::{StateDb, StateError};
+use crate::storage::{BlockStore, KvStore, StorageError};
+
+/// 处理区块的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockOutcome {
+    /// 区块已经处理过
+    AlreadyKnown,
+    /// 区块已保存，但不在最优链上
+    SideChain,
+    /// 最优链直接延长，没有回滚区块
+    Extended,
+    /// 最优链切换到另一个分叉
+    Reorganized {
+        /// 被回滚的区块，从原末端开始
+        disconnected: Vec<BlockHash>,
+        /// 新应用的区块，按高度升序
+        connected: Vec<BlockHash>,
+    },
+}
+
+/// 区块链管理：把区块存储、状态数据库和分叉选择组合在一起
+///
+/// 每个新区块先做结构和共识校验，保存后加入区块树；最优链末端改变时，状态数据库沿分叉点
+/// 回滚旧分叉的区块并应用新分叉的区块。执行失败的区块被标记为无效，状态退回到剩余的最优链。
+/// 状态数据库先于区块存储更新，两者不一致时以状态数据库为准。
+/// 权益证明链上的区块通过 `process_sealed_block` 处理，出块签名随区块一起传入，必须来自当前验证者集合
+/// 选出的出块人；签名只用于校验，不保存在区块中。
+#[derive(Debug)]
+pub struct ChainManager<S, K> {
+    spec: ChainSpec,
+    store: S,
+    state: StateDb<K>,
+    fork_choice: ForkChoice,
+    validators: ValidatorSet,
+}
+
+impl<S: BlockStore, K: KvStore> ChainManager<S, K> {
+    /// 打开区块链
+    ///
+    /// 状态数据库必须已经应用了创世区块，区块存储中必须有状态末端及其所有祖先。
+    /// 区块树由区块存储的主链重建，重启前看到的侧链不会恢复；主链上的区块都视为已执行。
+    pub fn new(spec: ChainSpec, mut store: S, state: StateDb<K>) -> Result<Self, ConsensusError> {
+        let (tip, _) = state.tip()?.ok_or(StateError::NotInitialized)?;
+        if store.best_tip() != Some(tip) {
+            store.set_best_tip(&tip)?;
+        }
+
+        let mut headers = store.headers();
+        let genesis = headers
+            .next()
+            .transpose()?
+            .ok_or(ConsensusError::MissingAncestor { height: 0 })?;
+        let mut fork_choice = ForkChoice::new(spec.consensus, genesis)?;
+        for header in headers {
+            let header = header?;
+            let hash = header.hash();
+            fork_choice.insert(header)?;
+            fork_choice.mark_connected(&hash);
+        }
+        Ok(ChainManager {
+            spec,
+            store,
+            state,
+            fork_choice,
+            validators: ValidatorSet::new(),
+        })
+    }
+
+    /// 链规格
+    pub fn spec(&self) -> &ChainSpec {
+        &self.spec
+    }
+
+    /// 区块存储
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// 状态数据库
+    pub fn state(&self) -> &StateDb<K> {
+        &self.state
+    }
+
+    /// 校验出块签名所用的验证者集合
+    pub fn validators(&self) -> &ValidatorSet {
+        &self.validators
+    }
+
+    /// 更新验证者集合（例如在纪元切换时），影响之后收到的区块；初始为空集合，此时权益证明链不接受任何新区块
+    pub fn set_validators(&mut self, validators: ValidatorSet) {
+        self.validators = validators;
+    }
+
+    /// 区块树
+    pub fn fork_choice(&self) -> &ForkChoice {
+        &self.fork_choice
+    }
+
+    /// 最优链末端
+    pub fn best_tip(&self) -> BlockHash {
+        self.fork_choice.best_tip()
+    }
+
+    /// 处理收到的区块（工作量证明）
+    ///
+    /// 区块需要通过结构校验（`now` 为本地时间）以及难度和工作量校验；权益证明链上返回 `MissingSeal`。
+    /// 区块在最优链上且执行失败时返回 `InvalidBlock`，此时状态已经退回到剩余的最优链。
+    pub fn process_block(&mut self, block: &Block, now: u64) -> Result<BlockOutcome, ConsensusError> {
+        self.process(block, None, now)
+    }
+
+    /// 处理收到的区块及其出块签名（权益证明）
+    ///
+    /// `seal` 必须是该高度出块人对区块头的签名；工作量证明链上返回 `UnexpectedSeal`。
+    /// 其余校验和返回值与 `process_block` 相同。
+    pub fn process_sealed_block(&mut self, block: &Block, seal: &Vote, now: u64) -> Result<BlockOutcome, ConsensusError> {
+        self.process(block, Some(seal), now)
+    }
+
+    fn process(&mut self, block: &Block, seal: Option<&Vote>, now: u64) -> Result<BlockOutcome, ConsensusError> {
+        let hash = block.hash();
+        if self.fork_choice.contains(&hash) {
+            return Ok(BlockOutcome::AlreadyKnown);
+        }
+        let parent_hash = block.header.parent_hash;
+        let parent = self
+            .fork_choice
+            .header(&parent_hash)
+            .ok_or(ConsensusError::UnknownParent(parent_hash))?;
+        if self.fork_choice.is_invalid(&parent_hash) {
+            return Err(ConsensusError::InvalidAncestor(parent_hash));
+        }
+        block.validate(parent, &self.spec.block_limits, now)?;
+        match self.spec.consensus {
+            ConsensusEngine::ProofOfWork => {
+                if seal.is_some() {
+                    return Err(ConsensusError::UnexpectedSeal(hash));
+                }
+                self.spec.pow.check_header(&self.store, &block.header)?;
+            }
+            ConsensusEngine::ProofOfStake => {
+                let seal = seal.ok_or(ConsensusError::MissingSeal(hash))?;
+                self.validators.verify_proposal(self.spec.chain_id, &block.header, seal)?;
+            }
+        }
+
+        self.store.put_block(block)?;
+        self.fork_choice.insert(block.header.clone())?;
+        self.reorganize()
+    }
+
+    /// 最终确认区块（权益证明），最优链不包含该区块时会切换分叉
+    ///
+    /// 区块必须已经被成功执行过，否则返回 `UnconnectedBlock`：确认一个执行失败的区块会让最优链无处可去。
+    pub fn finalize(&mut self, hash: &BlockHash) -> Result<BlockOutcome, ConsensusError> {
+        if self.fork_choice.contains(hash) && !self.fork_choice.is_connected(hash) {
+            return Err(ConsensusError::UnconnectedBlock(*hash));
+        }
+        self.fork_choice.finalize(hash)?;
+        self.reorganize()
+    }
+
+    fn load_block(&self, hash: &BlockHash) -> Result<Block, ConsensusError> {
+        Ok(self
+            .store
+            .get_block(hash)?
+            .ok_or(StorageError::UnknownBlock(*hash))?)
+    }
+
+    // 让状态数据库和区块存储跟随分叉选择的最优链末端
+    fn reorganize(&mut self) -> Result<BlockOutcome, ConsensusError> {
+        let mut disconnected: Vec<BlockHash> = Vec::new();
+        let mut connected: Vec<BlockHash> = Vec::new();
+        let mut failure = None;
+        let mut previous_target = None;
+
+        loop {
+            let (current, _) = self.state.tip()?.ok_or(StateError::NotInitialized)?;
+            let target = self.fork_choice.best_tip();
+            if current == target {
+                break;
+            }
+            // 执行失败后最优末端仍然无效或没有改变时停在当前状态，避免反复执行同一个区块
+            if failure.is_some() && (self.fork_choice.is_invalid(&target) || previous_target == Some(target)) {
+                break;
+            }
+            previous_target = Some(target);
+            let (down, up) = self.fork_choice.path(&current, &target)?;
+            for hash in down {
+                self.state.revert_block(&self.load_block(&hash)?)?;
+                match connected.iter().position(|h| *h == hash) {
+                    Some(index) => {
+                        connected.remove(index);
+                    }
+                    None => disconnected.push(hash),
+                }
+            }
+            for hash in up {
+                match self.state.apply_block(&self.load_block(&hash)?) {
+                    Ok(()) => {
+                        self.fork_choice.mark_connected(&hash);
+                        match disconnected.iter().position(|h| *h == hash) {
+                            Some(index) => {
+                                disconnected.remove(index);
+                            }
+                            None => connected.push(hash),
+                        }
+                    }
+                    Err(StateError::Storage(e)) => return Err(e.into()),
+                    // 区块执行失败：标记为无效后重新选择最优链，下一轮从当前状态末端切换过去
+                    Err(error) => {
+                        self.fork_choice.mark_invalid(&hash);
+                        failure.get_or_insert((hash, error));
+                        break;
+                    }
+                }
+            }
+        }
+
+        // 以状态数据库的末端为准，正常情况下它就是最优链末端
+        let (target, _) = self.state.tip()?.ok_or(StateError::NotInitialized)?;
+        if self.store.best_tip() != Some(target) {
+            self.store.set_best_tip(&target)?;
+        }
+        if let Some((hash, error)) = failure {
+            return Err(ConsensusError::InvalidBlock {
+                hash,
+                error: Box::new(error),
+            });
+        }
+        Ok(match (disconnected.is_empty(), connected.is_empty()) {
+            (true, true) => BlockOutcome::SideChain,
+            (true, false) => BlockOutcome::Extended,
+            (false, _) => BlockOutcome::Reorganized { disconnected, connected },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::testing::{child_header, fixture, genesis_header, Fixture};
+    use crate::chain::BlockHeader;
+    use crate::storage::{MemoryBlockStore, MemoryKvStore};
+    use crate::types::{Address, Amount, Hash256, KeyPair, Transaction};
+
+    const NOW: u64 = 1_800_000_000;
+
+    fn amount(s: &str) -> Amount {
+        s.parse().unwrap()
+    }
+
+    // 在 parent 对应的状态之上出块：填写状态根并挖出工作量证明，返回区块和出块后的状态
+    fn mine(
+        f: &Fixture,
+        state: &StateDb<MemoryKvStore>,
+        parent: &Block,
+        transactions: Vec<Transaction>,
+    ) -> (Block, StateDb<MemoryKvStore>) {
+        let header = BlockHeader {
+            state_root: state.state_root_after(&transactions).unwrap_or(Hash256::ZERO),
+            bits: f.spec.pow.pow_limit,
+            ..child_header(&parent.header, 60)
+        };
+        let mut block = Block::new(header, transactions);
+        while f.spec.pow.check_proof_of_work(&block.header).is_err() {
+            block.header.nonce += 1;
+        }
+        let mut next = state.clone();
+        let _ = next.apply_block(&block);
+        (block, next)
+    }
+
+    fn transfer(f: &Fixture, nonce: u64, to: Address, value: &str) -> Vec<Transaction> {
+        vec![Transaction::new_signed(&f.alice, f.spec.chain_id, nonce, to, amount(value), Amount::zero())]
+    }
+
+    fn setup(f: &Fixture) -> (ChainManager<MemoryBlockStore, MemoryKvStore>, Block, StateDb<MemoryKvStore>) {
+        let allocations = [(f.alice_address, amount("10"))];
+        let header = BlockHeader {
+            state_root: StateDb::<MemoryKvStore>::genesis_state_root(&allocations).unwrap(),
+            ..genesis_header(f.spec.pow.pow_limit)
+        };
+        let mut genesis = Block::new(header, Vec::new());
+        while f.spec.pow.check_proof_of_work(&genesis.header).is_err() {
+            genesis.header.nonce += 1;
+        }
+        let mut state = StateDb::new(MemoryKvStore::new(), &f.spec);
+        state.apply_genesis(&genesis, &allocations).unwrap();
+        let mut store = MemoryBlockStore::new();
+        store.put_block(&genesis).unwrap();
+        store.set_best_tip(&genesis.hash()).unwrap();
+
+        let chain = ChainManager::new(f.spec.clone(), store, state.clone()).unwrap();
+        (chain, genesis, state)
+    }
+
+    #[test]
+    fn test_competing_forks() {
+        let f = fixture();
+        let (mut chain, genesis, genesis_state) = setup(&f);
+
+        // 链 A：alice 转给 bob 3，然后转给 bob 1
+        let (a1, a1_state) = mine(&f, &genesis_state, &genesis, transfer(&f, 0, f.bob, "3"));
+        let (a2, a2_state) = mine(&f, &a1_state, &a1, transfer(&f, 1, f.bob, "1"));
+        assert_eq!(chain.process_block(&a1, NOW).unwrap(), BlockOutcome::Extended);
+        assert_eq!(chain.process_block(&a2, NOW).unwrap(), BlockOutcome::Extended);
+        assert_eq!(chain.process_block(&a2, NOW).unwrap(), BlockOutcome::AlreadyKnown);
+        assert_eq!(chain.state().balance(&f.bob).unwrap(), amount("4"));
+
+        // 链 B：从创世区块分叉，alice 转给 carol 5；等长时保持链 A
+        let (b1, b1_state) = mine(&f, &genesis_state, &genesis, transfer(&f, 0, f.carol, "5"));
+        let (b2, b2_state) = mine(&f, &b1_state, &b1, Vec::new());
+        let (b3, _) = mine(&f, &b2_state, &b2, Vec::new());
+        assert_eq!(chain.process_block(&b1, NOW).unwrap(), BlockOutcome::SideChain);
+        assert_eq!(chain.process_block(&b2, NOW).unwrap(), BlockOutcome::SideChain);
+        assert_eq!(chain.best_tip(), a2.hash());
+
+        // 链 B 更长后重组：链 A 的交易被回滚
+        assert_eq!(
+            chain.process_block(&b3, NOW).unwrap(),
+            BlockOutcome::Reorganized {
+                disconnected: vec![a2.hash(), a1.hash()],
+                connected: vec![b1.hash(), b2.hash(), b3.hash()],
+            }
+        );
+        assert_eq!(chain.state().balance(&f.bob).unwrap(), Amount::zero());
+        assert_eq!(chain.state().balance(&f.carol).unwrap(), amount("5"));
+        assert_eq!(chain.state().nonce(&f.alice_address).unwrap(), 1);
+        assert_eq!(chain.state().state_root().unwrap(), b3.header.state_root);
+        assert_eq!(chain.store().best_tip(), Some(b3.hash()));
+        assert_eq!(chain.store().hash_at_height(1), Some(b1.hash()));
+
+        // 链 A 再次反超
+        let (a3, a3_state) = mine(&f, &a2_state, &a2, Vec::new());
+        let (a4, _) = mine(&f, &a3_state, &a3, Vec::new());
+        assert_eq!(chain.process_block(&a3, NOW).unwrap(), BlockOutcome::SideChain);
+        assert!(matches!(
+            chain.process_block(&a4, NOW).unwrap(),
+            BlockOutcome::Reorganized { .. }
+        ));
+        assert_eq!(chain.state().balance(&f.bob).unwrap(), amount("4"));
+        assert_eq!(chain.state().balance(&f.carol).unwrap(), Amount::zero());
+        assert_eq!(chain.store().best_height(), Some(4));
+    }
+
+    #[test]
+    fn test_invalid_fork_is_abandoned() {
+        let f = fixture();
+        let (mut chain, genesis, genesis_state) = setup(&f);
+
+        let (a1, _) = mine(&f, &genesis_state, &genesis, transfer(&f, 0, f.bob, "3"));
+        chain.process_block(&a1, NOW).unwrap();
+
+        // b2 使用了错误的序号，结构和工作量都合法，但执行失败
+        let (b1, b1_state) = mine(&f, &genesis_state, &genesis, Vec::new());
+        let (b2, b2_state) = mine(&f, &b1_state, &b1, transfer(&f, 7, f.carol, "1"));
+        let (b3, _) = mine(&f, &b2_state, &b2, Vec::new());
+        assert_eq!(chain.process_block(&b1, NOW).unwrap(), BlockOutcome::SideChain);
+        let error = chain.process_block(&b2, NOW).unwrap_err();
+        assert!(matches!(&error, ConsensusError::InvalidBlock { hash, .. } if *hash == b2.hash()));
+
+        // 状态回到链 A，无效分叉上的后续区块直接被拒绝
+        assert_eq!(chain.best_tip(), a1.hash());
+        assert_eq!(chain.state().tip().unwrap(), Some((a1.hash(), 1)));
+        assert_eq!(chain.state().balance(&f.bob).unwrap(), amount("3"));
+        assert_eq!(chain.store().best_tip(), Some(a1.hash()));
+        assert!(matches!(chain.process_block(&b3, NOW), Err(ConsensusError::InvalidAncestor(_))));
+    }
+
+    #[test]
+    fn test_finalize_requires_executed_block() {
+        let f = fixture();
+        let (mut chain, genesis, genesis_state) = setup(&f);
+
+        let (a1, a1_state) = mine(&f, &genesis_state, &genesis, Vec::new());
+        let (a2, _) = mine(&f, &a1_state, &a1, Vec::new());
+        chain.process_block(&a1, NOW).unwrap();
+        chain.process_block(&a2, NOW).unwrap();
+
+        // b1 使用了错误的序号，作为侧链保存但从未执行
+        let (b1, _) = mine(&f, &genesis_state, &genesis, transfer(&f, 7, f.carol, "1"));
+        assert_eq!(chain.process_block(&b1, NOW).unwrap(), BlockOutcome::SideChain);
+        assert!(matches!(chain.finalize(&b1.hash()), Err(ConsensusError::UnconnectedBlock(_))));
+        assert_eq!(chain.fork_choice().finalized(), genesis.hash());
+        assert_eq!(chain.best_tip(), a2.hash());
+
+        // 已执行的区块可以确认
+        assert_eq!(chain.finalize(&a1.hash()).unwrap(), BlockOutcome::SideChain);
+        assert_eq!(chain.fork_choice().finalized(), a1.hash());
+        assert_eq!(chain.state().tip().unwrap(), Some((a2.hash(), 2)));
+    }
+
+    #[test]
+    fn test_rejects_bad_blocks() {
+        let f = fixture();
+        let (mut chain, genesis, genesis_state) = setup(&f);
+
+        let (orphan_parent, orphan_state) = mine(&f, &genesis_state, &genesis, Vec::new());
+        let (orphan, _) = mine(&f, &orphan_state, &orphan_parent, Vec::new());
+        assert!(matches!(chain.process_block(&orphan, NOW), Err(ConsensusError::UnknownParent(_))));
+
+        // 难度与重定向结果不一致
+        let (mut block, _) = mine(&f, &genesis_state, &genesis, Vec::new());
+        block.header.bits = 0x2000_ffff;
+        assert!(matches!(
+            chain.process_block(&block, NOW),
+            Err(ConsensusError::BitsMismatch { .. })
+        ));
+
+        // 时间戳超前本地时间太多
+        let (block, _) = mine(&f, &genesis_state, &genesis, Vec::new());
+        assert!(matches!(
+            chain.process_block(&block, genesis.header.timestamp - 3 * 60 * 60),
+            Err(ConsensusError::Block(_))
+        ));
+        assert_eq!(chain.best_tip(), genesis.hash());
+    }
+
+    #[test]
+    fn test_reopen_chain() {
+        let f = fixture();
+        let (mut chain, genesis, genesis_state) = setup(&f);
+        let (a1, a1_state) = mine(&f, &genesis_state, &genesis, transfer(&f, 0, f.bob, "3"));
+        let (a2, _) = mine(&f, &a1_state, &a1, Vec::new());
+        chain.process_block(&a1, NOW).unwrap();
+        chain.process_block(&a2, NOW).unwrap();
+
+        let ChainManager { spec, store, state, .. } = chain;
+        let chain = ChainManager::new(spec, store, state).unwrap();
+        assert_eq!(chain.best_tip(), a2.hash());
+        assert!(chain.fork_choice().contains(&a1.hash()));
+        assert_eq!(chain.state().balance(&f.bob).unwrap(), amount("3"));
+    }
+
+    #[test]
+    fn test_proof_of_stake_requires_proposer_seal() {
+        let mut f = fixture();
+        f.spec.consensus = ConsensusEngine::ProofOfStake;
+        let (mut chain, genesis, genesis_state) = setup(&f);
+        let keys: Vec<KeyPair> = (10..12).map(|i| KeyPair::from_secret_bytes(&[i; 32])).collect();
+        let mut validators = ValidatorSet::new();
+        for key in &keys {
+            validators
+                .bond(&f.spec.pos, key.public_key(), f.spec.pos.min_stake)
+                .unwrap();
+        }
+
+        let (block, _) = mine(&f, &genesis_state, &genesis, transfer(&f, 0, f.bob, "1"));
+        let proposer = validators.proposer(&genesis.hash(), 1).unwrap();
+        let proposer_key = keys.iter().find(|k| k.public_key() == proposer).unwrap();
+        let other_key = keys.iter().find(|k| k.public_key() != proposer).unwrap();
+
+        // 没有验证者时任何区块都无法通过校验
+        let seal = Vote::seal(proposer_key, f.spec.chain_id, &block.header);
+        assert!(matches!(chain.process_sealed_block(&block, &seal, NOW), Err(ConsensusError::NoValidators)));
+        chain.set_validators(validators);
+
+        // 没有签名、由其他验证者签名或签名用于其他链
+        assert!(matches!(chain.process_block(&block, NOW), Err(ConsensusError::MissingSeal(_))));
+        let other = Vote::seal(other_key, f.spec.chain_id, &block.header);
+        assert!(matches!(
+            chain.process_sealed_block(&block, &other, NOW),
+            Err(ConsensusError::WrongProposer { .. })
+        ));
+        let other_chain = Vote::seal(proposer_key, f.spec.chain_id + 1, &block.header);
+        assert!(matches!(
+            chain.process_sealed_block(&block, &other_chain, NOW),
+            Err(ConsensusError::InvalidVote(_))
+        ));
+        assert_eq!(chain.best_tip(), genesis.hash());
+        assert!(!chain.store().contains_block(&block.hash()).unwrap());
+
+        assert_eq!(chain.process_sealed_block(&block, &seal, NOW).unwrap(), BlockOutcome::Extended);
+        assert_eq!(chain.store().get_block(&block.hash()).unwrap(), Some(block));
+    }
+
+    #[test]
+    fn test_proof_of_work_rejects_seal() {
+        let f = fixture();
+        let (mut chain, genesis, genesis_state) = setup(&f);
+        let (block, _) = mine(&f, &genesis_state, &genesis, Vec::new());
+        let seal = Vote::seal(&f.alice, f.spec.chain_id, &block.header);
+        assert!(matches!(
+            chain.process_sealed_block(&block, &seal, NOW),
+            Err(ConsensusError::UnexpectedSeal(_))
+        ));
+    }
+}
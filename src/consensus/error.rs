@@ -0,0 +1,119 @@
+use crate::chain::{BlockError, BlockHash};
+use crate::consensus::pos::DoubleSignEvidence;
+use crate::state::StateError;
+use crate::storage::StorageError;
+use crate::types::{Amount, PublicKey};
+use std::fmt;
+
+// 自定义错误类型
+#[derive(Debug)]
+pub enum ConsensusError {
+    InvalidParams(&'static str),  // 共识参数不合法
+    InvalidBits(u32),  // 紧凑格式的难度目标无法解码（为负、为零或溢出）
+    TargetAboveLimit(u32),  // 难度目标比最低难度还容易
+    BitsMismatch { expected: u32, actual: u32 },  // 区块头的难度与重定向算法的结果不一致
+    InsufficientWork(BlockHash),  // 区块哈希没有达到难度目标
+    MissingAncestor { height: u64 },  // 计算难度所需的祖先区块不存在
+    Storage(StorageError),  // 读取区块失败
+    UnknownValidator(PublicKey),  // 不是验证者或没有投票权
+    ValidatorJailed(PublicKey),  // 验证者已因双签被监禁
+    InsufficientStake { stake: Amount, required: Amount },  // 质押不足
+    StakeOverflow,  // 质押总额超过 Amount 的表示范围
+    NoValidators,  // 没有可以出块的验证者
+    WrongProposer { expected: PublicKey, actual: PublicKey },  // 区块不是由选中的出块人签名
+    MissingSeal(BlockHash),  // 权益证明链上的区块没有出块签名
+    UnexpectedSeal(BlockHash),  // 工作量证明链上的区块带有出块签名
+    InvalidVote(&'static str),  // 投票与用途不符
+    InvalidSignature,  // 投票签名无效
+    InvalidEvidence(&'static str),  // 双签证据不成立
+    Equivocation(Box<DoubleSignEvidence>),  // 收到冲突的投票，附带双签证据
+    UnknownParent(BlockHash),  // 父区块未知（孤块）
+    UnknownBlock(BlockHash),  // 区块不在区块树中
+    InvalidAncestor(BlockHash),  // 祖先区块已被判定为无效
+    FinalityConflict(BlockHash),  // 要确认的区块与已确认的区块冲突
+    UnconnectedBlock(BlockHash),  // 要确认的区块还没有被执行过
+    InvalidBlock { hash: BlockHash, error: Box<StateError> },  // 区块执行失败，已被标记为无效
+    Block(BlockError),  // 区块结构校验失败
+    State(StateError),  // 状态数据库错误
+}
+
+// 为 ConsensusError 实现 Display trait，用于打印错误信息
+impl fmt::Display for ConsensusError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConsensusError::InvalidParams(e) => write!(f, "Invalid consensus parameters: {}", e),
+            ConsensusError::InvalidBits(bits) => write!(f, "Invalid compact target {:#010x}", bits),
+            ConsensusError::TargetAboveLimit(bits) => {
+                write!(f, "Compact target {:#010x} is easier than the proof-of-work limit", bits)
+            }
+            ConsensusError::BitsMismatch { expected, actual } => {
+                write!(f, "Wrong difficulty: expected {:#010x}, got {:#010x}", expected, actual)
+            }
+            ConsensusError::InsufficientWork(hash) => {
+                write!(f, "Block {} does not meet its difficulty target", hash)
+            }
+            ConsensusError::MissingAncestor { height } => {
+                write!(f, "Missing ancestor block at height {}", height)
+            }
+            ConsensusError::Storage(e) => write!(f, "Storage error: {}", e),
+            ConsensusError::UnknownValidator(key) => write!(f, "Unknown validator {}", key),
+            ConsensusError::ValidatorJailed(key) => write!(f, "Validator {} is jailed", key),
+            ConsensusError::InsufficientStake { stake, required } => {
+                write!(f, "Insufficient stake: have {}, need {}", stake, required)
+            }
+            ConsensusError::StakeOverflow => write!(f, "Stake overflows the maximum amount"),
+            ConsensusError::NoValidators => write!(f, "No active validators"),
+            ConsensusError::WrongProposer { expected, actual } => {
+                write!(f, "Block signed by {}, expected proposer {}", actual, expected)
+            }
+            ConsensusError::MissingSeal(hash) => write!(f, "Block {} has no proposer seal", hash),
+            ConsensusError::UnexpectedSeal(hash) => {
+                write!(f, "Block {} carries a proposer seal on a proof-of-work chain", hash)
+            }
+            ConsensusError::InvalidVote(e) => write!(f, "Invalid vote: {}", e),
+            ConsensusError::InvalidSignature => write!(f, "Invalid vote signature"),
+            ConsensusError::InvalidEvidence(e) => write!(f, "Invalid double-sign evidence: {}", e),
+            ConsensusError::Equivocation(evidence) => write!(
+                f,
+                "Validator {} signed conflicting blocks at height {}",
+                evidence.first.validator, evidence.first.height
+            ),
+            ConsensusError::UnknownParent(hash) => write!(f, "Unknown parent block {}", hash),
+            ConsensusError::UnknownBlock(hash) => write!(f, "Unknown block {}", hash),
+            ConsensusError::InvalidAncestor(hash) => write!(f, "Ancestor block {} is invalid", hash),
+            ConsensusError::FinalityConflict(hash) => {
+                write!(f, "Block {} conflicts with the finalized chain", hash)
+            }
+            ConsensusError::UnconnectedBlock(hash) => {
+                write!(f, "Block {} has not been executed and cannot be finalized", hash)
+            }
+            ConsensusError::InvalidBlock { hash, error } => write!(f, "Invalid block {}: {}", hash, error),
+            ConsensusError::Block(e) => write!(f, "Block error: {}", e),
+            ConsensusError::State(e) => write!(f, "State error: {}", e),
+        }
+    }
+}
+
+// 为 ConsensusError 实现 Error trait
+impl std::error::Error for ConsensusError {}
+
+// 实现从 StorageError 到 ConsensusError 的转换
+impl From<StorageError> for ConsensusError {
+    fn from(err: StorageError) -> Self {
+        ConsensusError::Storage(err)
+    }
+}
+
+// 实现从 BlockError 到 ConsensusError 的转换
+impl From<BlockError> for ConsensusError {
+    fn from(err: BlockError) -> Self {
+        ConsensusError::Block(err)
+    }
+}
+
+// 实现从 StateError 到 ConsensusError 的转换
+impl From<StateError> for ConsensusError {
+    fn from(err: StateError) -> Self {
+        ConsensusError::State(err)
+    }
+}
@@ -0,0 +1,352 @@
+use crate::chain::{BlockHash, BlockHeader};
+use crate::consensus::error::ConsensusError;
+use crate::consensus::pow::block_work;
+use crate::consensus::ConsensusEngine;
+use num_bigint::BigUint;
+use std::collections::HashMap;
+
+// 区块树中的节点
+#[derive(Debug, Clone)]
+struct TreeNode {
+    header: BlockHeader,
+    // 从创世区块到本区块的累计权重
+    weight: BigUint,
+    // 插入顺序，权重相同时先插入的优先
+    sequence: u64,
+    invalid: bool,
+    // 是否被成功执行过；执行过的区块的祖先也都执行过
+    connected: bool,
+}
+
+/// 分叉选择：维护所有已知区块组成的树，并选出最优链的末端
+///
+/// 工作量证明下最优链是累计工作量最大的链；权益证明下每个区块的权重为 1，最优链必须包含最近
+/// 被最终确认的区块，在此前提下选最长的链。权重相同时保留先看到的末端，避免在等长分叉之间
+/// 来回切换。被判定为无效的区块及其所有后代都不会被选中。
+#[derive(Debug, Clone)]
+pub struct ForkChoice {
+    engine: ConsensusEngine,
+    nodes: HashMap<BlockHash, TreeNode>,
+    children: HashMap<BlockHash, Vec<BlockHash>>,
+    best: BlockHash,
+    finalized: BlockHash,
+    next_sequence: u64,
+}
+
+impl ForkChoice {
+    /// 以创世区块为根创建区块树
+    pub fn new(engine: ConsensusEngine, genesis: BlockHeader) -> Result<Self, ConsensusError> {
+        let hash = genesis.hash();
+        let mut fork_choice = ForkChoice {
+            engine,
+            nodes: HashMap::new(),
+            children: HashMap::new(),
+            best: hash,
+            finalized: hash,
+            next_sequence: 0,
+        };
+        let weight = fork_choice.block_weight(&genesis)?;
+        fork_choice.add_node(genesis, weight, false);
+        fork_choice.mark_connected(&hash);
+        Ok(fork_choice)
+    }
+
+    // 单个区块的权重
+    fn block_weight(&self, header: &BlockHeader) -> Result<BigUint, ConsensusError> {
+        match self.engine {
+            ConsensusEngine::ProofOfWork => block_work(header.bits),
+            ConsensusEngine::ProofOfStake => Ok(BigUint::from(1u8)),
+        }
+    }
+
+    fn add_node(&mut self, header: BlockHeader, weight: BigUint, invalid: bool) -> BlockHash {
+        let hash = header.hash();
+        self.children.entry(header.parent_hash).or_default().push(hash);
+        self.nodes.insert(
+            hash,
+            TreeNode {
+                header,
+                weight,
+                sequence: self.next_sequence,
+                invalid,
+                connected: false,
+            },
+        );
+        self.next_sequence += 1;
+        hash
+    }
+
+    /// 是否已知该区块
+    pub fn contains(&self, hash: &BlockHash) -> bool {
+        self.nodes.contains_key(hash)
+    }
+
+    /// 已知区块的区块头
+    pub fn header(&self, hash: &BlockHash) -> Option<&BlockHeader> {
+        self.nodes.get(hash).map(|node| &node.header)
+    }
+
+    /// 从创世区块到该区块的累计权重（工作量证明下即累计工作量）
+    pub fn weight(&self, hash: &BlockHash) -> Option<&BigUint> {
+        self.nodes.get(hash).map(|node| &node.weight)
+    }
+
+    /// 区块是否已被判定为无效
+    pub fn is_invalid(&self, hash: &BlockHash) -> bool {
+        self.nodes.get(hash).is_some_and(|node| node.invalid)
+    }
+
+    /// 区块是否被成功执行过
+    pub fn is_connected(&self, hash: &BlockHash) -> bool {
+        self.nodes.get(hash).is_some_and(|node| node.connected)
+    }
+
+    /// 记录区块已被成功执行
+    pub fn mark_connected(&mut self, hash: &BlockHash) {
+        if let Some(node) = self.nodes.get_mut(hash) {
+            node.connected = true;
+        }
+    }
+
+    /// 最优链的末端
+    pub fn best_tip(&self) -> BlockHash {
+        self.best
+    }
+
+    /// 最近被最终确认的区块，初始为创世区块
+    pub fn finalized(&self) -> BlockHash {
+        self.finalized
+    }
+
+    /// 加入区块头，父区块必须已知；返回该区块是否成为新的最优链末端
+    ///
+    /// 父区块无效时区块同样被记为无效，并返回 `InvalidAncestor`。
+    pub fn insert(&mut self, header: BlockHeader) -> Result<bool, ConsensusError> {
+        let hash = header.hash();
+        if self.nodes.contains_key(&hash) {
+            return Ok(false);
+        }
+        let parent = self
+            .nodes
+            .get(&header.parent_hash)
+            .ok_or(ConsensusError::UnknownParent(header.parent_hash))?;
+        let (parent_weight, parent_invalid) = (parent.weight.clone(), parent.invalid);
+        let weight = parent_weight + self.block_weight(&header)?;
+        let parent_hash = header.parent_hash;
+        self.add_node(header, weight, parent_invalid);
+        if parent_invalid {
+            return Err(ConsensusError::InvalidAncestor(parent_hash));
+        }
+
+        if self.is_better(&hash, &self.best) && self.is_ancestor(&self.finalized, &hash) {
+            self.best = hash;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    // a 是否优于 b：权重更大，权重相同时先插入的优先
+    fn is_better(&self, a: &BlockHash, b: &BlockHash) -> bool {
+        let (a, b) = (&self.nodes[a], &self.nodes[b]);
+        (&a.weight, std::cmp::Reverse(a.sequence)) > (&b.weight, std::cmp::Reverse(b.sequence))
+    }
+
+    /// ancestor 是否是 descendant 本身或其祖先，两者都必须已知
+    pub fn is_ancestor(&self, ancestor: &BlockHash, descendant: &BlockHash) -> bool {
+        let Some(target) = self.nodes.get(ancestor) else {
+            return false;
+        };
+        let mut current = descendant;
+        while let Some(node) = self.nodes.get(current) {
+            if node.header.height <= target.header.height {
+                return current == ancestor;
+            }
+            current = &node.header.parent_hash;
+        }
+        false
+    }
+
+    /// 把区块及其所有后代标记为无效，必要时重新选择最优链
+    pub fn mark_invalid(&mut self, hash: &BlockHash) {
+        let mut pending = vec![*hash];
+        while let Some(hash) = pending.pop() {
+            if let Some(node) = self.nodes.get_mut(&hash) {
+                node.invalid = true;
+                pending.extend(self.children.get(&hash).into_iter().flatten().copied());
+            }
+        }
+        if self.is_invalid(&self.best) {
+            self.recompute_best();
+        }
+    }
+
+    /// 最终确认区块：该区块必须有效，且是当前已确认区块的后代
+    ///
+    /// 最优链不包含该区块时改选包含它的最优链。
+    pub fn finalize(&mut self, hash: &BlockHash) -> Result<(), ConsensusError> {
+        if !self.nodes.contains_key(hash) {
+            return Err(ConsensusError::UnknownBlock(*hash));
+        }
+        if self.is_invalid(hash) || !self.is_ancestor(&self.finalized, hash) {
+            return Err(ConsensusError::FinalityConflict(*hash));
+        }
+        self.finalized = *hash;
+        if !self.is_ancestor(hash, &self.best) {
+            self.recompute_best();
+        }
+        Ok(())
+    }
+
+    // 在所有有效且包含已确认区块的区块中重新选出最优末端
+    //
+    // 已确认的区块本身无效时（确认了从未执行过的区块），没有任何有效的链包含它，
+    // 最优链停在它最近的有效祖先上。
+    fn recompute_best(&mut self) {
+        if self.is_invalid(&self.finalized) {
+            let mut best = self.finalized;
+            while self.is_invalid(&best) && self.nodes[&best].header.height > 0 {
+                best = self.nodes[&best].header.parent_hash;
+            }
+            self.best = best;
+            return;
+        }
+        let mut best = self.finalized;
+        for (hash, node) in &self.nodes {
+            if !node.invalid && self.is_better(hash, &best) && self.is_ancestor(&self.finalized, hash) {
+                best = *hash;
+            }
+        }
+        self.best = best;
+    }
+
+    /// 从 from 切换到 to 需要回滚和应用的区块
+    ///
+    /// 返回的第一个列表从 from 开始向下到分叉点（不含），第二个列表从分叉点之后向上到 to，
+    /// 两个区块都必须已知。
+    pub fn path(&self, from: &BlockHash, to: &BlockHash) -> Result<(Vec<BlockHash>, Vec<BlockHash>), ConsensusError> {
+        let node = |hash: &BlockHash| self.nodes.get(hash).ok_or(ConsensusError::UnknownBlock(*hash));
+        let (mut down, mut up) = (Vec::new(), Vec::new());
+        let (mut a, mut b) = (*from, *to);
+        while a != b {
+            let (node_a, node_b) = (node(&a)?, node(&b)?);
+            if node_a.header.height >= node_b.header.height {
+                down.push(a);
+                a = node_a.header.parent_hash;
+            } else {
+                up.push(b);
+                b = node_b.header.parent_hash;
+            }
+        }
+        up.reverse();
+        Ok((down, up))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::testing::{child_header, genesis_header};
+    use crate::types::Hash256;
+
+    // 在 parent 之上构造区块头，nonce 用来区分不同分叉上的同高度区块
+    fn child(parent: &BlockHeader, bits: u32, nonce: u64) -> BlockHeader {
+        BlockHeader {
+            bits,
+            nonce,
+            ..child_header(parent, 0)
+        }
+    }
+
+    // 从 parent 开始连续构造 len 个区块并插入
+    fn extend(tree: &mut ForkChoice, parent: &BlockHeader, len: usize, bits: u32, nonce: u64) -> Vec<BlockHeader> {
+        let mut headers: Vec<BlockHeader> = Vec::new();
+        for _ in 0..len {
+            let header = child(headers.last().unwrap_or(parent), bits, nonce);
+            tree.insert(header.clone()).unwrap();
+            headers.push(header);
+        }
+        headers
+    }
+
+    #[test]
+    fn test_most_work_wins() {
+        let genesis = genesis_header(0x207f_ffff);
+        let mut tree = ForkChoice::new(ConsensusEngine::ProofOfWork, genesis.clone()).unwrap();
+        let a = extend(&mut tree, &genesis, 3, 0x207f_ffff, 1);
+        assert_eq!(tree.best_tip(), a[2].hash());
+
+        // 等长分叉不会取代先看到的链
+        let b = extend(&mut tree, &genesis, 3, 0x207f_ffff, 2);
+        assert_eq!(tree.best_tip(), a[2].hash());
+
+        // 难度更高的较短分叉工作量更大
+        let c = extend(&mut tree, &genesis, 2, 0x2000_ffff, 3);
+        assert_eq!(tree.best_tip(), c[1].hash());
+        assert!(tree.weight(&c[1].hash()).unwrap() > tree.weight(&a[2].hash()).unwrap());
+
+        let (down, up) = tree.path(&a[2].hash(), &b[1].hash()).unwrap();
+        assert_eq!(down, vec![a[2].hash(), a[1].hash(), a[0].hash()]);
+        assert_eq!(up, vec![b[0].hash(), b[1].hash()]);
+        let (down, up) = tree.path(&a[0].hash(), &a[2].hash()).unwrap();
+        assert!(down.is_empty());
+        assert_eq!(up, vec![a[1].hash(), a[2].hash()]);
+
+        assert!(matches!(
+            tree.insert(child(&child(&genesis, 0x207f_ffff, 9), 0x207f_ffff, 9)),
+            Err(ConsensusError::UnknownParent(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_blocks_are_excluded() {
+        let genesis = genesis_header(0x207f_ffff);
+        let mut tree = ForkChoice::new(ConsensusEngine::ProofOfWork, genesis.clone()).unwrap();
+        let a = extend(&mut tree, &genesis, 2, 0x207f_ffff, 1);
+        let b = extend(&mut tree, &genesis, 3, 0x207f_ffff, 2);
+        assert_eq!(tree.best_tip(), b[2].hash());
+
+        // b[1] 无效时其后代一并无效，最优链回到 a
+        tree.mark_invalid(&b[1].hash());
+        assert!(tree.is_invalid(&b[2].hash()));
+        assert!(!tree.is_invalid(&b[0].hash()));
+        assert_eq!(tree.best_tip(), a[1].hash());
+
+        assert!(matches!(
+            tree.insert(child(&b[2], 0x207f_ffff, 2)),
+            Err(ConsensusError::InvalidAncestor(_))
+        ));
+        assert_eq!(tree.best_tip(), a[1].hash());
+    }
+
+    #[test]
+    fn test_finalized_block_must_stay_in_best_chain() {
+        let genesis = genesis_header(0x207f_ffff);
+        let mut tree = ForkChoice::new(ConsensusEngine::ProofOfStake, genesis.clone()).unwrap();
+        let a = extend(&mut tree, &genesis, 3, 0, 1);
+        let b = extend(&mut tree, &genesis, 2, 0, 2);
+
+        // 确认较短分叉上的区块后，最优链切换到包含它的分叉
+        tree.finalize(&b[1].hash()).unwrap();
+        assert_eq!(tree.best_tip(), b[1].hash());
+
+        // 不包含已确认区块的分叉再长也不会被选中
+        let longer = extend(&mut tree, &a[2], 5, 0, 1);
+        assert_eq!(tree.best_tip(), b[1].hash());
+        assert!(matches!(
+            tree.finalize(&longer[4].hash()),
+            Err(ConsensusError::FinalityConflict(_))
+        ));
+
+        let b_next = extend(&mut tree, &b[1], 1, 0, 2);
+        assert_eq!(tree.best_tip(), b_next[0].hash());
+        assert!(matches!(
+            tree.finalize(&Hash256::ZERO),
+            Err(ConsensusError::UnknownBlock(_))
+        ));
+
+        // 已确认的区块被判定为无效时，最优链停在它最近的有效祖先上，不会再选中它
+        tree.mark_invalid(&b[1].hash());
+        assert_eq!(tree.best_tip(), b[0].hash());
+        assert!(!tree.is_invalid(&tree.best_tip()));
+    }
+}
@@ -0,0 +1,117 @@
+use crate::chain::{Block, BlockHeader};
+use crate::consensus::error::ConsensusError;
+use crate::consensus::pow::{hash_meets_target, target_from_bits};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+/// CPU 挖矿器：用多个线程穷举区块头的 nonce，适用于回归测试网络
+#[derive(Debug, Clone, Copy)]
+pub struct CpuMiner {
+    threads: usize,
+}
+
+impl CpuMiner {
+    /// 创建挖矿器，线程数至少为 1
+    pub fn new(threads: usize) -> Self {
+        CpuMiner {
+            threads: threads.max(1),
+        }
+    }
+
+    /// 线程数
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// 为区块头寻找满足其难度目标的 nonce
+    ///
+    /// 第 i 个线程依次尝试 i, i + threads, ...。`stop` 被置位时（例如收到了同高度的新区块）
+    /// 所有线程尽快退出并返回 None；整个 nonce 空间都不满足时同样返回 None，调用方可以更新
+    /// 时间戳后重试。
+    pub fn mine_header(&self, header: &BlockHeader, stop: &AtomicBool) -> Result<Option<BlockHeader>, ConsensusError> {
+        let target = target_from_bits(header.bits)?;
+        let found = AtomicBool::new(false);
+        let result = thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.threads)
+                .map(|start| {
+                    let (target, found) = (&target, &found);
+                    let mut candidate = header.clone();
+                    scope.spawn(move || {
+                        for nonce in (start as u64..=u64::MAX).step_by(self.threads) {
+                            if stop.load(Ordering::Relaxed) || found.load(Ordering::Relaxed) {
+                                return None;
+                            }
+                            candidate.nonce = nonce;
+                            if hash_meets_target(&candidate.hash(), target) {
+                                found.store(true, Ordering::Relaxed);
+                                return Some(candidate);
+                            }
+                        }
+                        None
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .filter_map(|worker| worker.join().expect("miner thread panicked"))
+                .next()
+        });
+        Ok(result)
+    }
+
+    /// 挖出区块：区块头以外的内容不变
+    pub fn mine_block(&self, block: Block, stop: &AtomicBool) -> Result<Option<Block>, ConsensusError> {
+        Ok(self.mine_header(&block.header, stop)?.map(|header| Block { header, ..block }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::testing::{child_header, genesis_block, genesis_header};
+    use crate::consensus::pow::{PowParams, Retarget};
+    use crate::storage::{BlockStore, MemoryBlockStore};
+
+    fn regtest_params() -> PowParams {
+        PowParams {
+            pow_limit: 0x2000_ffff,
+            target_spacing: 1,
+            retarget: Retarget::WindowedAverage { window: 5 },
+        }
+    }
+
+    #[test]
+    fn test_mine_chain() {
+        let params = regtest_params();
+        let miner = CpuMiner::new(4);
+        let stop = AtomicBool::new(false);
+        let mut store = MemoryBlockStore::new();
+
+        let genesis = miner.mine_block(genesis_block(params.pow_limit), &stop).unwrap().unwrap();
+        params.check_proof_of_work(&genesis.header).unwrap();
+        store.put_block(&genesis).unwrap();
+        store.set_best_tip(&genesis.hash()).unwrap();
+
+        let mut parent = genesis.header;
+        for _ in 0..5 {
+            let header = BlockHeader {
+                bits: params.next_bits(&store, &parent).unwrap(),
+                ..child_header(&parent, 1)
+            };
+            let block = miner.mine_block(Block::new(header, Vec::new()), &stop).unwrap().unwrap();
+            params.check_header(&store, &block.header).unwrap();
+            store.put_block(&block).unwrap();
+            store.set_best_tip(&block.hash()).unwrap();
+            parent = block.header;
+        }
+        assert_eq!(store.best_height(), Some(5));
+    }
+
+    #[test]
+    fn test_stop_mining() {
+        // 几乎不可能找到满足该目标的哈希
+        let header = genesis_header(0x0300_0001);
+        let stop = AtomicBool::new(true);
+        assert_eq!(CpuMiner::new(2).mine_header(&header, &stop).unwrap(), None);
+    }
+}
@@ -0,0 +1,25 @@
+pub mod chain_manager;
+pub mod error;
+pub mod fork_choice;
+pub mod miner;
+pub mod pos;
+pub mod pow;
+
+pub use chain_manager::{BlockOutcome, ChainManager};
+pub use error::ConsensusError;
+pub use fork_choice::ForkChoice;
+pub use miner::CpuMiner;
+pub use pos::{DoubleSignEvidence, PosParams, Validator, ValidatorSet, Vote, VoteKind, VoteTracker};
+pub use pow::{PowParams, Retarget};
+
+use serde::{Deserialize, Serialize};
+
+/// 共识引擎
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ConsensusEngine {
+    /// 工作量证明
+    #[default]
+    ProofOfWork,
+    /// 权益证明
+    ProofOfStake,
+}
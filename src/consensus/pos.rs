@@ -0,0 +1,625 @@
+use crate::chain::spec::serde_amount;
+use crate::chain::{BlockHash, BlockHeader};
+use crate::consensus::error::ConsensusError;
+use crate::types::{Amount, Hash256, KeyPair, PublicKey, Signature};
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// 权益证明共识参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PosParams {
+    /// 成为验证者所需的最低质押
+    #[serde(with = "serde_amount")]
+    pub min_stake: Amount,
+    /// 双签时罚没的质押比例（百分比）
+    pub slash_percent: u8,
+}
+
+impl Default for PosParams {
+    fn default() -> Self {
+        PosParams {
+            min_stake: Amount::from_u128(1_000 * 100_000_000),
+            slash_percent: 5,
+        }
+    }
+}
+
+impl PosParams {
+    /// 校验参数
+    pub fn validate(&self) -> Result<(), ConsensusError> {
+        if self.min_stake.is_zero() {
+            return Err(ConsensusError::InvalidParams("minimum stake must be greater than zero"));
+        }
+        if self.slash_percent > 100 {
+            return Err(ConsensusError::InvalidParams("slash percentage must not exceed 100"));
+        }
+        Ok(())
+    }
+}
+
+/// 验证者
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Validator {
+    /// 质押数量
+    pub stake: Amount,
+    /// 因双签被监禁：不再参与出块和投票，剩余质押只能解除
+    pub jailed: bool,
+}
+
+/// 验证者集合：按公钥登记质押
+///
+/// 出块权和投票权都与质押成正比，被监禁的验证者不计入。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidatorSet {
+    validators: BTreeMap<PublicKey, Validator>,
+}
+
+impl ValidatorSet {
+    // 出块人抽签的域分隔前缀
+    const PROPOSER_DOMAIN: &'static [u8] = b"FAIC-PROPOSER-V1";
+
+    /// 创建空集合
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 查询验证者
+    pub fn get(&self, key: &PublicKey) -> Option<&Validator> {
+        self.validators.get(key)
+    }
+
+    /// 按公钥顺序遍历所有验证者（包括被监禁的）
+    pub fn iter(&self) -> impl Iterator<Item = (&PublicKey, &Validator)> {
+        self.validators.iter()
+    }
+
+    /// 质押：新验证者或追加质押，质押总额不能低于最低质押
+    pub fn bond(&mut self, params: &PosParams, key: PublicKey, amount: Amount) -> Result<(), ConsensusError> {
+        let current = match self.validators.get(&key) {
+            Some(validator) if validator.jailed => return Err(ConsensusError::ValidatorJailed(key)),
+            Some(validator) => validator.stake,
+            None => Amount::zero(),
+        };
+        let stake = current.checked_add(&amount).ok_or(ConsensusError::StakeOverflow)?;
+        if stake < params.min_stake {
+            return Err(ConsensusError::InsufficientStake {
+                stake,
+                required: params.min_stake,
+            });
+        }
+        self.validators.insert(key, Validator { stake, jailed: false });
+        Ok(())
+    }
+
+    /// 解除质押：剩余质押为零时移除验证者，否则剩余部分不能低于最低质押（被监禁的验证者除外）
+    pub fn unbond(&mut self, params: &PosParams, key: &PublicKey, amount: Amount) -> Result<(), ConsensusError> {
+        let validator = self
+            .validators
+            .get_mut(key)
+            .ok_or(ConsensusError::UnknownValidator(*key))?;
+        let remaining = validator
+            .stake
+            .checked_sub(&amount)
+            .ok_or(ConsensusError::InsufficientStake {
+                stake: validator.stake,
+                required: amount,
+            })?;
+        if remaining.is_zero() {
+            self.validators.remove(key);
+            return Ok(());
+        }
+        if remaining < params.min_stake && !validator.jailed {
+            return Err(ConsensusError::InsufficientStake {
+                stake: remaining,
+                required: params.min_stake,
+            });
+        }
+        validator.stake = remaining;
+        Ok(())
+    }
+
+    /// 验证者的投票权：未监禁时等于质押，否则为零
+    pub fn voting_power(&self, key: &PublicKey) -> Amount {
+        match self.validators.get(key) {
+            Some(validator) if !validator.jailed => validator.stake,
+            _ => Amount::zero(),
+        }
+    }
+
+    // 未被监禁的验证者
+    fn active(&self) -> impl Iterator<Item = (&PublicKey, &Validator)> {
+        self.validators.iter().filter(|(_, validator)| !validator.jailed)
+    }
+
+    /// 所有未被监禁的验证者的质押总额
+    pub fn total_stake(&self) -> Amount {
+        self.active()
+            .fold(Amount::zero(), |total, (_, validator)| total.saturating_add(&validator.stake))
+    }
+
+    /// 选出 parent_hash 之上高度为 height 的区块的出块人
+    ///
+    /// 以父区块哈希和高度的哈希作为随机数，按公钥顺序累计质押，落在哪个区间就由哪个验证者出块，
+    /// 因此选中概率与质押成正比，且所有节点的结果相同。没有可用验证者时返回 None。
+    pub fn proposer(&self, parent_hash: &BlockHash, height: u64) -> Option<PublicKey> {
+        let total = self.total_stake().as_u128();
+        if total == 0 {
+            return None;
+        }
+        let mut seed = Vec::with_capacity(Self::PROPOSER_DOMAIN.len() + Hash256::LEN + 8);
+        seed.extend_from_slice(Self::PROPOSER_DOMAIN);
+        seed.extend_from_slice(parent_hash.as_bytes());
+        seed.extend_from_slice(&height.to_be_bytes());
+        let random = Hash256::double_sha256(&seed);
+        let mut point = u128::from_be_bytes(random.as_bytes()[..16].try_into().expect("16 bytes")) % total;
+        for (key, validator) in self.active() {
+            let stake = validator.stake.as_u128();
+            if point < stake {
+                return Some(*key);
+            }
+            point -= stake;
+        }
+        None
+    }
+
+    /// 校验区块的出块签名：签名者是该高度选出的出块人，且签名覆盖了这个区块头
+    pub fn verify_proposal(&self, chain_id: u32, header: &BlockHeader, seal: &Vote) -> Result<(), ConsensusError> {
+        if seal.kind != VoteKind::Proposal {
+            return Err(ConsensusError::InvalidVote("block seal must be a proposal"));
+        }
+        if seal.chain_id != chain_id || seal.height != header.height || seal.block_hash != header.hash() {
+            return Err(ConsensusError::InvalidVote("block seal does not match the header"));
+        }
+        let expected = self
+            .proposer(&header.parent_hash, header.height)
+            .ok_or(ConsensusError::NoValidators)?;
+        if seal.validator != expected {
+            return Err(ConsensusError::WrongProposer {
+                expected,
+                actual: seal.validator,
+            });
+        }
+        if !seal.verify() {
+            return Err(ConsensusError::InvalidSignature);
+        }
+        Ok(())
+    }
+
+    /// 根据双签证据罚没质押并监禁验证者，返回被罚没（销毁）的数量
+    pub fn slash(&mut self, params: &PosParams, chain_id: u32, evidence: &DoubleSignEvidence) -> Result<Amount, ConsensusError> {
+        evidence.verify(chain_id)?;
+        let key = evidence.first.validator;
+        let validator = self
+            .validators
+            .get_mut(&key)
+            .ok_or(ConsensusError::UnknownValidator(key))?;
+        if validator.jailed {
+            return Err(ConsensusError::ValidatorJailed(key));
+        }
+        // 罚没数量不超过质押，一定能用 Amount 表示
+        let penalty = Amount::from_biguint(BigUint::from(validator.stake.as_u128()) * params.slash_percent / 100u32)
+            .unwrap_or(validator.stake);
+        validator.stake = validator.stake.saturating_sub(&penalty);
+        validator.jailed = true;
+        Ok(penalty)
+    }
+}
+
+/// 签名消息的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VoteKind {
+    /// 出块人对区块的签名
+    Proposal,
+    /// 验证者对区块的最终确认投票
+    Commit,
+}
+
+/// 验证者签名的投票：对某条链某个高度的某个区块表态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Vote {
+    /// 投票类型
+    pub kind: VoteKind,
+    /// 链 ID，防止跨链重放
+    pub chain_id: u32,
+    /// 区块高度
+    pub height: u64,
+    /// 区块哈希
+    pub block_hash: BlockHash,
+    /// 验证者公钥
+    pub validator: PublicKey,
+    /// 对签名原像的签名
+    pub signature: Signature,
+}
+
+impl Vote {
+    // 投票签名的域分隔前缀，防止与交易签名混用
+    const SIGNING_DOMAIN: &'static [u8] = b"FAIC-VOTE-V1";
+
+    // 签名原像：域前缀 || 类型 || 链 ID || 高度 || 区块哈希
+    fn signing_preimage(kind: VoteKind, chain_id: u32, height: u64, block_hash: &BlockHash) -> Vec<u8> {
+        let mut preimage = Vec::with_capacity(Self::SIGNING_DOMAIN.len() + 1 + 4 + 8 + Hash256::LEN);
+        preimage.extend_from_slice(Self::SIGNING_DOMAIN);
+        preimage.push(match kind {
+            VoteKind::Proposal => 0,
+            VoteKind::Commit => 1,
+        });
+        preimage.extend_from_slice(&chain_id.to_be_bytes());
+        preimage.extend_from_slice(&height.to_be_bytes());
+        preimage.extend_from_slice(block_hash.as_bytes());
+        preimage
+    }
+
+    /// 创建并签名投票
+    pub fn new_signed(keypair: &KeyPair, kind: VoteKind, chain_id: u32, height: u64, block_hash: BlockHash) -> Self {
+        let signature = keypair.sign(&Self::signing_preimage(kind, chain_id, height, &block_hash));
+        Vote {
+            kind,
+            chain_id,
+            height,
+            block_hash,
+            validator: keypair.public_key(),
+            signature,
+        }
+    }
+
+    /// 出块人对区块头签名
+    pub fn seal(keypair: &KeyPair, chain_id: u32, header: &BlockHeader) -> Self {
+        Self::new_signed(keypair, VoteKind::Proposal, chain_id, header.height, header.hash())
+    }
+
+    /// 校验签名
+    pub fn verify(&self) -> bool {
+        let preimage = Self::signing_preimage(self.kind, self.chain_id, self.height, &self.block_hash);
+        self.validator.verify(&preimage, &self.signature)
+    }
+}
+
+/// 双签证据：同一验证者在同一高度对两个不同区块签了同类型的消息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DoubleSignEvidence {
+    /// 第一条消息
+    pub first: Vote,
+    /// 冲突的第二条消息
+    pub second: Vote,
+}
+
+impl DoubleSignEvidence {
+    /// 校验证据是否成立
+    pub fn verify(&self, chain_id: u32) -> Result<(), ConsensusError> {
+        let (first, second) = (&self.first, &self.second);
+        if first.chain_id != chain_id || second.chain_id != chain_id {
+            return Err(ConsensusError::InvalidEvidence("votes are for another chain"));
+        }
+        if first.kind != second.kind || first.validator != second.validator || first.height != second.height {
+            return Err(ConsensusError::InvalidEvidence("votes are not from the same validator and height"));
+        }
+        if first.block_hash == second.block_hash {
+            return Err(ConsensusError::InvalidEvidence("votes are for the same block"));
+        }
+        if !first.verify() || !second.verify() {
+            return Err(ConsensusError::InvalidSignature);
+        }
+        Ok(())
+    }
+}
+
+/// 最终确认投票的收集器
+///
+/// 某个高度上一个区块获得的投票权超过总质押的 2/3 时，该区块被最终确认。只要作恶的质押不超过
+/// 1/3，同一高度就不会有两个区块被确认。同一验证者在同一高度投出冲突的票时返回双签证据。
+#[derive(Debug, Clone)]
+pub struct VoteTracker {
+    chain_id: u32,
+    validators: ValidatorSet,
+    votes: BTreeMap<u64, HashMap<PublicKey, Vote>>,
+    finalized: BTreeMap<u64, BlockHash>,
+}
+
+impl VoteTracker {
+    /// 以给定的验证者集合统计投票
+    pub fn new(chain_id: u32, validators: ValidatorSet) -> Self {
+        VoteTracker {
+            chain_id,
+            validators,
+            votes: BTreeMap::new(),
+            finalized: BTreeMap::new(),
+        }
+    }
+
+    /// 统计投票使用的验证者集合
+    pub fn validators(&self) -> &ValidatorSet {
+        &self.validators
+    }
+
+    /// 更新验证者集合（例如罚没之后），已确认的区块不受影响
+    pub fn set_validators(&mut self, validators: ValidatorSet) {
+        self.validators = validators;
+    }
+
+    /// 已被最终确认的区块
+    pub fn finalized(&self, height: u64) -> Option<BlockHash> {
+        self.finalized.get(&height).copied()
+    }
+
+    /// 最高的已确认高度及区块
+    pub fn last_finalized(&self) -> Option<(u64, BlockHash)> {
+        self.finalized.iter().next_back().map(|(height, hash)| (*height, *hash))
+    }
+
+    /// 加入一张确认投票
+    ///
+    /// 该投票使区块刚好达到确认门槛时返回区块哈希；重复的投票被忽略。
+    pub fn add_vote(&mut self, vote: Vote) -> Result<Option<BlockHash>, ConsensusError> {
+        if vote.kind != VoteKind::Commit {
+            return Err(ConsensusError::InvalidVote("only commit votes count towards finality"));
+        }
+        if vote.chain_id != self.chain_id {
+            return Err(ConsensusError::InvalidVote("vote is for another chain"));
+        }
+        if !vote.verify() {
+            return Err(ConsensusError::InvalidSignature);
+        }
+        if self.validators.voting_power(&vote.validator).is_zero() {
+            return Err(ConsensusError::UnknownValidator(vote.validator));
+        }
+
+        let (height, block_hash) = (vote.height, vote.block_hash);
+        let votes = self.votes.entry(height).or_default();
+        match votes.get(&vote.validator) {
+            Some(previous) if previous.block_hash == block_hash => return Ok(None),
+            Some(previous) => {
+                return Err(ConsensusError::Equivocation(Box::new(DoubleSignEvidence {
+                    first: previous.clone(),
+                    second: vote,
+                })))
+            }
+            None => {}
+        }
+        votes.insert(vote.validator, vote);
+        if self.finalized.contains_key(&height) {
+            return Ok(None);
+        }
+
+        let voted = votes
+            .values()
+            .filter(|vote| vote.block_hash == block_hash)
+            .fold(BigUint::ZERO, |sum, vote| sum + self.validators.voting_power(&vote.validator).as_u128());
+        if voted * 3u32 > BigUint::from(self.validators.total_stake().as_u128()) * 2u32 {
+            self.finalized.insert(height, block_hash);
+            return Ok(Some(block_hash));
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::testing::{child_header, genesis_block};
+    use crate::chain::Block;
+    use crate::storage::{BlockStore, MemoryBlockStore};
+
+    const CHAIN_ID: u32 = 3;
+
+    fn stake(faic: u128) -> Amount {
+        Amount::from_u128(faic * 100_000_000)
+    }
+
+    fn keypair(i: u8) -> KeyPair {
+        KeyPair::from_secret_bytes(&[i; 32])
+    }
+
+    // 四个验证者，质押 4000 / 3000 / 2000 / 1000 FAIC
+    fn validator_set(params: &PosParams) -> (Vec<KeyPair>, ValidatorSet) {
+        let keys: Vec<KeyPair> = (1..=4).map(keypair).collect();
+        let mut set = ValidatorSet::new();
+        for (key, amount) in keys.iter().zip([4000, 3000, 2000, 1000]) {
+            set.bond(params, key.public_key(), stake(amount)).unwrap();
+        }
+        (keys, set)
+    }
+
+    #[test]
+    fn test_bond_and_unbond() {
+        let params = PosParams::default();
+        let key = keypair(1).public_key();
+        let mut set = ValidatorSet::new();
+
+        assert!(matches!(
+            set.bond(&params, key, stake(999)),
+            Err(ConsensusError::InsufficientStake { .. })
+        ));
+        set.bond(&params, key, stake(1000)).unwrap();
+        set.bond(&params, key, stake(500)).unwrap();
+        assert_eq!(set.voting_power(&key), stake(1500));
+
+        // 剩余质押不能低于下限，全部解除时移除验证者
+        assert!(set.unbond(&params, &key, stake(600)).is_err());
+        assert!(set.unbond(&params, &key, stake(2000)).is_err());
+        set.unbond(&params, &key, stake(500)).unwrap();
+        set.unbond(&params, &key, stake(1000)).unwrap();
+        assert!(set.get(&key).is_none());
+        assert_eq!(set.proposer(&Hash256::ZERO, 1), None);
+        assert!(matches!(
+            set.unbond(&params, &key, stake(1)),
+            Err(ConsensusError::UnknownValidator(_))
+        ));
+    }
+
+    #[test]
+    fn test_proposer_selection_is_stake_weighted() {
+        let params = PosParams::default();
+        let (keys, set) = validator_set(&params);
+        let mut counts: HashMap<PublicKey, u32> = HashMap::new();
+        let rounds = 4000u32;
+        for height in 1..=rounds as u64 {
+            let parent = Hash256::double_sha256(&height.to_be_bytes());
+            let proposer = set.proposer(&parent, height).unwrap();
+            // 相同输入总是选出相同的出块人
+            assert_eq!(set.clone().proposer(&parent, height), Some(proposer));
+            *counts.entry(proposer).or_default() += 1;
+        }
+        for (key, share) in keys.iter().zip([40, 30, 20, 10]) {
+            let percent = counts[&key.public_key()] * 100 / rounds;
+            assert!(percent.abs_diff(share) <= 3, "expected ~{}%, got {}%", share, percent);
+        }
+    }
+
+    #[test]
+    fn test_verify_proposal() {
+        let params = PosParams::default();
+        let (keys, set) = validator_set(&params);
+        let genesis = genesis_block(0);
+        let header = child_header(&genesis.header, 0);
+        let proposer = set.proposer(&header.parent_hash, 1).unwrap();
+        let proposer_key = keys.iter().find(|k| k.public_key() == proposer).unwrap();
+        let other_key = keys.iter().find(|k| k.public_key() != proposer).unwrap();
+
+        set.verify_proposal(CHAIN_ID, &header, &Vote::seal(proposer_key, CHAIN_ID, &header))
+            .unwrap();
+        assert!(matches!(
+            set.verify_proposal(CHAIN_ID, &header, &Vote::seal(other_key, CHAIN_ID, &header)),
+            Err(ConsensusError::WrongProposer { .. })
+        ));
+        assert!(set
+            .verify_proposal(CHAIN_ID + 1, &header, &Vote::seal(proposer_key, CHAIN_ID, &header))
+            .is_err());
+
+        let mut forged = Vote::seal(proposer_key, CHAIN_ID, &header);
+        forged.signature = Vote::seal(other_key, CHAIN_ID, &header).signature;
+        assert!(matches!(
+            set.verify_proposal(CHAIN_ID, &header, &forged),
+            Err(ConsensusError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_finality_threshold() {
+        let params = PosParams::default();
+        let (keys, set) = validator_set(&params);
+        let mut tracker = VoteTracker::new(CHAIN_ID, set);
+        let hash = Hash256::double_sha256(b"block");
+        let vote = |i: usize| Vote::new_signed(&keys[i], VoteKind::Commit, CHAIN_ID, 1, hash);
+
+        // 40% + 20% 不足 2/3，再加 10% 后确认；重复投票和确认后的投票不影响结果
+        assert_eq!(tracker.add_vote(vote(0)).unwrap(), None);
+        assert_eq!(tracker.add_vote(vote(0)).unwrap(), None);
+        assert_eq!(tracker.add_vote(vote(2)).unwrap(), None);
+        assert_eq!(tracker.finalized(1), None);
+        assert_eq!(tracker.add_vote(vote(3)).unwrap(), Some(hash));
+        assert_eq!(tracker.add_vote(vote(1)).unwrap(), None);
+        assert_eq!(tracker.last_finalized(), Some((1, hash)));
+
+        // 非验证者和提案签名不计入
+        let outsider = Vote::new_signed(&keypair(9), VoteKind::Commit, CHAIN_ID, 2, hash);
+        assert!(matches!(tracker.add_vote(outsider), Err(ConsensusError::UnknownValidator(_))));
+        let proposal = Vote::new_signed(&keys[0], VoteKind::Proposal, CHAIN_ID, 2, hash);
+        assert!(matches!(tracker.add_vote(proposal), Err(ConsensusError::InvalidVote(_))));
+    }
+
+    #[test]
+    fn test_evidence_validation() {
+        let key = keypair(1);
+        let a = Hash256::double_sha256(b"a");
+        let b = Hash256::double_sha256(b"b");
+        let vote = |kind, height, hash| Vote::new_signed(&key, kind, CHAIN_ID, height, hash);
+
+        let evidence = DoubleSignEvidence {
+            first: vote(VoteKind::Commit, 5, a),
+            second: vote(VoteKind::Commit, 5, b),
+        };
+        evidence.verify(CHAIN_ID).unwrap();
+        assert!(evidence.verify(CHAIN_ID + 1).is_err());
+
+        for (first, second) in [
+            (vote(VoteKind::Commit, 5, a), vote(VoteKind::Commit, 5, a)),
+            (vote(VoteKind::Commit, 5, a), vote(VoteKind::Commit, 6, b)),
+            (vote(VoteKind::Commit, 5, a), vote(VoteKind::Proposal, 5, b)),
+            (vote(VoteKind::Commit, 5, a), Vote::new_signed(&keypair(2), VoteKind::Commit, CHAIN_ID, 5, b)),
+        ] {
+            assert!(DoubleSignEvidence { first, second }.verify(CHAIN_ID).is_err());
+        }
+    }
+
+    // 进程内的验证者节点
+    struct Node {
+        keypair: KeyPair,
+        store: MemoryBlockStore,
+        tracker: VoteTracker,
+    }
+
+    #[test]
+    fn test_multi_validator_network() {
+        let params = PosParams::default();
+        let (keys, mut set) = validator_set(&params);
+        let genesis = genesis_block(0);
+        let mut nodes: Vec<Node> = keys
+            .into_iter()
+            .map(|keypair| {
+                let mut store = MemoryBlockStore::new();
+                store.put_block(&genesis).unwrap();
+                store.set_best_tip(&genesis.hash()).unwrap();
+                Node {
+                    keypair,
+                    store,
+                    tracker: VoteTracker::new(CHAIN_ID, set.clone()),
+                }
+            })
+            .collect();
+
+        for height in 1..=20u64 {
+            // 出块人在自己的主链末端出块并签名
+            let parent_hash = nodes[0].store.best_tip().unwrap();
+            let proposer = set.proposer(&parent_hash, height).unwrap();
+            let proposer_node = nodes.iter().find(|n| n.keypair.public_key() == proposer).unwrap();
+            let parent = proposer_node.store.get_header(&parent_hash).unwrap().unwrap();
+            let block = Block::new(child_header(&parent, 5), Vec::new());
+            let seal = Vote::seal(&proposer_node.keypair, CHAIN_ID, &block.header);
+
+            // 每个节点校验出块签名后接受区块并投票
+            let mut votes = Vec::new();
+            for node in nodes.iter_mut() {
+                set.verify_proposal(CHAIN_ID, &block.header, &seal).unwrap();
+                node.store.put_block(&block).unwrap();
+                node.store.set_best_tip(&block.hash()).unwrap();
+                if !set.voting_power(&node.keypair.public_key()).is_zero() {
+                    votes.push(Vote::new_signed(&node.keypair, VoteKind::Commit, CHAIN_ID, height, block.hash()));
+                }
+            }
+            for node in nodes.iter_mut() {
+                for vote in &votes {
+                    node.tracker.add_vote(vote.clone()).unwrap();
+                }
+                assert_eq!(node.tracker.finalized(height), Some(block.hash()));
+            }
+
+            // 第 10 个区块时第三个验证者对另一个区块投票，被发现后罚没并监禁
+            if height == 10 {
+                let cheater = nodes[2].keypair.clone();
+                let conflicting =
+                    Vote::new_signed(&cheater, VoteKind::Commit, CHAIN_ID, height, Hash256::double_sha256(b"fork"));
+                let evidence = match nodes[0].tracker.add_vote(conflicting) {
+                    Err(ConsensusError::Equivocation(evidence)) => evidence,
+                    other => panic!("expected equivocation, got {:?}", other),
+                };
+                let penalty = set.slash(&params, CHAIN_ID, &evidence).unwrap();
+                assert_eq!(penalty, stake(100));
+                assert_eq!(set.get(&cheater.public_key()).unwrap().stake, stake(1900));
+                assert_eq!(set.total_stake(), stake(8000));
+                // 同一证据不能重复罚没
+                assert!(set.slash(&params, CHAIN_ID, &evidence).is_err());
+                for node in nodes.iter_mut() {
+                    node.tracker.set_validators(set.clone());
+                }
+            }
+            if height > 10 {
+                assert_ne!(proposer, nodes[2].keypair.public_key());
+            }
+        }
+        for node in &nodes {
+            assert_eq!(node.store.best_height(), Some(20));
+            assert_eq!(node.tracker.last_finalized().map(|(h, _)| h), Some(20));
+        }
+    }
+}
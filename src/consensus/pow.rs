@@ -0,0 +1,424 @@
+use crate::chain::BlockHeader;
+use crate::consensus::error::ConsensusError;
+use crate::storage::BlockStore;
+use crate::types::Hash256;
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+
+/// 从紧凑格式解码难度目标
+///
+/// 紧凑格式与比特币相同：最高字节为目标的字节长度，低三字节为最高有效的三个字节，
+/// 第 23 位是符号位。负数、零和超过 256 位的目标都不合法。
+pub fn target_from_bits(bits: u32) -> Result<BigUint, ConsensusError> {
+    let size = bits >> 24;
+    let mantissa = bits & 0x007f_ffff;
+    if bits & 0x0080_0000 != 0 {
+        return Err(ConsensusError::InvalidBits(bits));
+    }
+    let target = if size <= 3 {
+        BigUint::from(mantissa >> (8 * (3 - size)))
+    } else {
+        BigUint::from(mantissa) << (8 * (size - 3))
+    };
+    if target == BigUint::ZERO || target.bits() > 256 {
+        return Err(ConsensusError::InvalidBits(bits));
+    }
+    Ok(target)
+}
+
+/// 把难度目标编码为紧凑格式，只保留最高的三个有效字节
+pub fn bits_from_target(target: &BigUint) -> u32 {
+    let low_u32 = |value: &BigUint| value.iter_u32_digits().next().unwrap_or(0);
+    let mut size = target.bits().div_ceil(8) as u32;
+    let mut compact = if size <= 3 {
+        low_u32(target) << (8 * (3 - size))
+    } else {
+        low_u32(&(target >> (8 * (size - 3))))
+    };
+    // 最高位会被当作符号位，需要多用一个字节
+    if compact & 0x0080_0000 != 0 {
+        compact >>= 8;
+        size += 1;
+    }
+    compact | (size << 24)
+}
+
+/// 哈希（按大端序解释为整数）是否不大于难度目标
+pub fn hash_meets_target(hash: &Hash256, target: &BigUint) -> bool {
+    BigUint::from_bytes_be(hash.as_bytes()) <= *target
+}
+
+/// 区块的工作量：找到满足目标的哈希所需的期望尝试次数，即 2^256 / (target + 1)
+pub fn block_work(bits: u32) -> Result<BigUint, ConsensusError> {
+    let target = target_from_bits(bits)?;
+    Ok((BigUint::from(1u8) << 256u32) / (target + 1u8))
+}
+
+/// ASERT 难度目标：`anchor_target * 2^((time_delta - spacing * height_delta) / half_life)`
+///
+/// `time_delta` 和 `height_delta` 是父区块相对锚点区块的时间差和高度差。指数以 16 位定点数
+/// 计算，小数部分的 2^x 使用与 BCH aserti3-2d 相同的三次多项式近似，结果只依赖整数运算。
+/// 返回值未做上下限截断。
+pub fn asert_target(
+    anchor_target: &BigUint,
+    time_delta: i128,
+    height_delta: u64,
+    spacing: u64,
+    half_life: u64,
+) -> BigUint {
+    let exponent = ((time_delta - spacing as i128 * height_delta as i128) * 65536).div_euclid(half_life as i128);
+    let shifts = (exponent >> 16) - 16;
+    let frac = (exponent & 0xffff) as u128;
+    let factor = 65536
+        + ((195_766_423_245_049 * frac + 971_821_376 * frac * frac + 5_127 * frac * frac * frac + (1 << 47))
+            >> 48);
+
+    // 偏离计划过远时结果必然落在上下限之外，不再做无意义的大数移位
+    if shifts > 256 {
+        return BigUint::from(1u8) << 512u32;
+    }
+    if shifts < -(256 + 64) {
+        return BigUint::ZERO;
+    }
+    let target = anchor_target * BigUint::from(factor);
+    if shifts >= 0 {
+        target << shifts as u32
+    } else {
+        target >> (-shifts) as u32
+    }
+}
+
+/// 窗口平均难度目标：窗口内目标的平均值乘以实际用时与计划用时之比
+///
+/// `headers` 按高度升序排列，至少包含两个区块。为防止时间戳操纵，实际用时被限制在计划用时的
+/// 1/4 到 4 倍之间。返回值未做上下限截断。
+pub fn windowed_target(headers: &[BlockHeader], spacing: u64) -> Result<BigUint, ConsensusError> {
+    let (first, last) = match headers {
+        [first, .., last] => (first, last),
+        _ => return Err(ConsensusError::InvalidParams("retarget window needs at least two blocks")),
+    };
+    let mut sum = BigUint::ZERO;
+    for header in headers {
+        sum += target_from_bits(header.bits)?;
+    }
+    let average = sum / headers.len();
+
+    let expected = spacing * (headers.len() as u64 - 1);
+    let actual = last
+        .timestamp
+        .saturating_sub(first.timestamp)
+        .clamp(expected / 4, expected * 4);
+    Ok(average * actual / expected)
+}
+
+/// 难度重定向算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "algorithm", rename_all = "snake_case")]
+pub enum Retarget {
+    /// 不调整难度，始终使用最低难度，用于回归测试
+    Fixed,
+    /// 逐块 ASERT：以创世区块为锚点，出块进度每超前 `half_life` 秒难度加倍，每落后 `half_life` 秒难度减半
+    Asert { half_life: u64 },
+    /// 逐块窗口平均：取最近 `window` 个区块（含父区块）计算
+    WindowedAverage { window: u64 },
+}
+
+/// 工作量证明共识参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PowParams {
+    /// 最低难度（紧凑格式），创世区块使用该难度
+    pub pow_limit: u32,
+    /// 目标出块间隔（秒）
+    pub target_spacing: u64,
+    /// 难度重定向算法
+    pub retarget: Retarget,
+}
+
+impl Default for PowParams {
+    fn default() -> Self {
+        PowParams {
+            pow_limit: 0x1e0f_ffff,
+            target_spacing: 120,
+            retarget: Retarget::Asert { half_life: 2 * 24 * 60 * 60 },
+        }
+    }
+}
+
+impl PowParams {
+    /// 校验参数
+    pub fn validate(&self) -> Result<(), ConsensusError> {
+        self.limit()?;
+        if self.target_spacing == 0 {
+            return Err(ConsensusError::InvalidParams("target spacing must be greater than zero"));
+        }
+        match self.retarget {
+            Retarget::Asert { half_life: 0 } => {
+                Err(ConsensusError::InvalidParams("ASERT half-life must be greater than zero"))
+            }
+            Retarget::WindowedAverage { window } if window < 2 => {
+                Err(ConsensusError::InvalidParams("retarget window must contain at least two blocks"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 最低难度对应的目标
+    pub fn limit(&self) -> Result<BigUint, ConsensusError> {
+        target_from_bits(self.pow_limit)
+    }
+
+    // 把目标截断到 [1, 最低难度目标]
+    fn clamp(&self, target: BigUint) -> Result<BigUint, ConsensusError> {
+        Ok(target.clamp(BigUint::from(1u8), self.limit()?))
+    }
+
+    /// 计算 parent 的子区块应使用的难度（紧凑格式）
+    ///
+    /// 窗口平均沿父区块哈希回溯，因此对分叉上的区块同样适用；ASERT 的锚点是主链的创世区块。
+    pub fn next_bits<S: BlockStore>(&self, store: &S, parent: &BlockHeader) -> Result<u32, ConsensusError> {
+        let target = match self.retarget {
+            Retarget::Fixed => return Ok(self.pow_limit),
+            Retarget::Asert { half_life } => {
+                let anchor = store
+                    .hash_at_height(0)
+                    .map(|hash| store.get_header(&hash))
+                    .transpose()?
+                    .flatten()
+                    .ok_or(ConsensusError::MissingAncestor { height: 0 })?;
+                asert_target(
+                    &target_from_bits(anchor.bits)?,
+                    parent.timestamp as i128 - anchor.timestamp as i128,
+                    parent.height - anchor.height,
+                    self.target_spacing,
+                    half_life,
+                )
+            }
+            Retarget::WindowedAverage { window } => {
+                let mut headers = vec![parent.clone()];
+                while (headers.len() as u64) < window {
+                    let oldest = &headers[headers.len() - 1];
+                    if oldest.height == 0 {
+                        break;
+                    }
+                    let ancestor = store
+                        .get_header(&oldest.parent_hash)?
+                        .ok_or(ConsensusError::MissingAncestor { height: oldest.height - 1 })?;
+                    headers.push(ancestor);
+                }
+                // 链还不够长时沿用父区块的难度
+                if headers.len() < 2 {
+                    return Ok(parent.bits);
+                }
+                headers.reverse();
+                windowed_target(&headers, self.target_spacing)?
+            }
+        };
+        Ok(bits_from_target(&self.clamp(target)?))
+    }
+
+    /// 校验区块头的工作量证明：难度不低于最低难度，且区块哈希满足难度目标
+    ///
+    /// 只检查区块头本身，创世区块也适用。
+    pub fn check_proof_of_work(&self, header: &BlockHeader) -> Result<(), ConsensusError> {
+        let target = target_from_bits(header.bits)?;
+        if target > self.limit()? {
+            return Err(ConsensusError::TargetAboveLimit(header.bits));
+        }
+        let hash = header.hash();
+        if !hash_meets_target(&hash, &target) {
+            return Err(ConsensusError::InsufficientWork(hash));
+        }
+        Ok(())
+    }
+
+    /// 完整校验非创世区块头的共识字段：难度等于重定向结果，且工作量证明有效
+    ///
+    /// 父区块必须已经保存在 store 中。
+    pub fn check_header<S: BlockStore>(&self, store: &S, header: &BlockHeader) -> Result<(), ConsensusError> {
+        let parent = store
+            .get_header(&header.parent_hash)?
+            .ok_or(ConsensusError::MissingAncestor { height: header.height.saturating_sub(1) })?;
+        let expected = self.next_bits(store, &parent)?;
+        if header.bits != expected {
+            return Err(ConsensusError::BitsMismatch { expected, actual: header.bits });
+        }
+        self.check_proof_of_work(header)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::testing::{child_header, genesis_block};
+    use crate::chain::Block;
+    use crate::storage::MemoryBlockStore;
+
+    const SPACING: u64 = 120;
+    const REGTEST_LIMIT: u32 = 0x207f_ffff;
+
+    fn params(retarget: Retarget) -> PowParams {
+        PowParams {
+            pow_limit: REGTEST_LIMIT,
+            target_spacing: SPACING,
+            retarget,
+        }
+    }
+
+    fn to_u64(value: &BigUint) -> u64 {
+        assert!(value.bits() <= 64);
+        value.iter_u64_digits().next().unwrap_or(0)
+    }
+
+    // 模拟出块：算力恒定时出块用时与目标成反比，`equilibrium` 是用时恰好等于目标间隔的目标。
+    // 返回每个区块的出块用时
+    fn simulate(
+        params: &PowParams,
+        store: &mut MemoryBlockStore,
+        equilibrium: &BigUint,
+        blocks: usize,
+    ) -> Vec<u64> {
+        let mut times = Vec::with_capacity(blocks);
+        let mut parent = store.get_header(&store.best_tip().unwrap()).unwrap().unwrap();
+        for _ in 0..blocks {
+            let bits = params.next_bits(store, &parent).unwrap();
+            let elapsed = to_u64(&(BigUint::from(SPACING) * equilibrium / target_from_bits(bits).unwrap()));
+            let header = BlockHeader {
+                bits,
+                ..child_header(&parent, elapsed)
+            };
+            let block = Block::new(header, Vec::new());
+            store.put_block(&block).unwrap();
+            store.set_best_tip(&block.hash()).unwrap();
+            parent = block.header;
+            times.push(elapsed);
+        }
+        times
+    }
+
+    fn average(times: &[u64]) -> u64 {
+        times.iter().sum::<u64>() / times.len() as u64
+    }
+
+    // 算力先上升 4096 倍，再下降到 1/8，两次都应收敛到目标出块间隔附近
+    fn assert_converges(params: PowParams) {
+        let mut store = MemoryBlockStore::new();
+        let genesis = genesis_block(params.pow_limit);
+        store.put_block(&genesis).unwrap();
+        store.set_best_tip(&genesis.hash()).unwrap();
+
+        let equilibrium = params.limit().unwrap() >> 12u32;
+        let times = simulate(&params, &mut store, &equilibrium, 1000);
+        let recent = average(&times[800..]);
+        assert!(recent.abs_diff(SPACING) <= SPACING / 20, "{:?}: average {}", params.retarget, recent);
+
+        let equilibrium = equilibrium << 3u32;
+        let times = simulate(&params, &mut store, &equilibrium, 1000);
+        assert!(times[0] > 4 * SPACING);
+        let recent = average(&times[800..]);
+        assert!(recent.abs_diff(SPACING) <= SPACING / 20, "{:?}: average {}", params.retarget, recent);
+    }
+
+    #[test]
+    fn test_compact_target_encoding() {
+        assert_eq!(target_from_bits(0x1d00_ffff).unwrap(), BigUint::from(0xffffu32) << 208u32);
+        assert_eq!(target_from_bits(0x0312_3456).unwrap(), BigUint::from(0x12_3456u32));
+        assert_eq!(target_from_bits(0x0212_3400).unwrap(), BigUint::from(0x1234u32));
+
+        for bits in [0x1d00_ffff, REGTEST_LIMIT, 0x1e0f_ffff, 0x0212_3400, 0x0312_3456, 0x0200_8000] {
+            assert_eq!(bits_from_target(&target_from_bits(bits).unwrap()), bits);
+        }
+        // 最高字节大于 0x7f 时多用一个字节
+        assert_eq!(bits_from_target(&BigUint::from(0x80u32)), 0x0200_8000);
+
+        // 负数、零、右移后为零以及超过 256 位的目标都不合法
+        for bits in [0x1d80_ffff, 0x1d00_0000, 0x0100_3456, 0x2300_ffff] {
+            assert!(matches!(target_from_bits(bits), Err(ConsensusError::InvalidBits(_))));
+        }
+    }
+
+    #[test]
+    fn test_block_work() {
+        assert_eq!(block_work(REGTEST_LIMIT).unwrap(), BigUint::from(2u8));
+        assert!(block_work(0x1d00_ffff).unwrap() > block_work(0x1e0f_ffff).unwrap());
+    }
+
+    #[test]
+    fn test_check_proof_of_work() {
+        let params = params(Retarget::Fixed);
+        let mut header = genesis_block(REGTEST_LIMIT).header;
+        while params.check_proof_of_work(&header).is_err() {
+            header.nonce += 1;
+        }
+        assert!(hash_meets_target(&header.hash(), &params.limit().unwrap()));
+
+        let mut easier = header.clone();
+        easier.bits = 0x2100_ffff;
+        assert!(matches!(params.check_proof_of_work(&easier), Err(ConsensusError::TargetAboveLimit(_))));
+
+        let mut harder = header;
+        harder.bits = 0x0300_0001;
+        assert!(matches!(params.check_proof_of_work(&harder), Err(ConsensusError::InsufficientWork(_))));
+    }
+
+    #[test]
+    fn test_asert_schedule() {
+        let anchor = BigUint::from(1u8) << 200u32;
+        // 按计划出块时目标不变，超前或落后一个半衰期时目标精确减半或加倍
+        assert_eq!(asert_target(&anchor, 1200, 10, SPACING, 3600), anchor);
+        assert_eq!(asert_target(&anchor, 1200 + 3600, 10, SPACING, 3600), &anchor << 1u32);
+        assert_eq!(asert_target(&anchor, 1200 - 3600, 10, SPACING, 3600), &anchor >> 1u32);
+        // 半个半衰期约为 sqrt(2) 倍
+        let half = asert_target(&anchor, 1200 + 1800, 10, SPACING, 3600);
+        assert_eq!((half * 1000u32 / &anchor), BigUint::from(1414u32));
+        // 极端偏离不会做巨大的移位
+        assert!(asert_target(&anchor, i64::MAX as i128, 0, SPACING, 1).bits() > 256);
+        assert_eq!(asert_target(&anchor, 0, u64::MAX, SPACING, 1), BigUint::ZERO);
+    }
+
+    #[test]
+    fn test_asert_converges() {
+        assert_converges(params(Retarget::Asert { half_life: 3600 }));
+    }
+
+    #[test]
+    fn test_windowed_average_converges() {
+        assert_converges(params(Retarget::WindowedAverage { window: 30 }));
+    }
+
+    #[test]
+    fn test_check_header() {
+        let params = params(Retarget::WindowedAverage { window: 10 });
+        let mut store = MemoryBlockStore::new();
+        let genesis = genesis_block(REGTEST_LIMIT);
+        store.put_block(&genesis).unwrap();
+
+        let mut header = child_header(&genesis.header, 10);
+        // 只有创世区块时沿用其难度
+        assert_eq!(params.next_bits(&store, &genesis.header).unwrap(), REGTEST_LIMIT);
+        while params.check_proof_of_work(&header).is_err() {
+            header.nonce += 1;
+        }
+        params.check_header(&store, &header).unwrap();
+
+        header.bits = 0x2000_ffff;
+        assert!(matches!(
+            params.check_header(&store, &header),
+            Err(ConsensusError::BitsMismatch { expected: REGTEST_LIMIT, .. })
+        ));
+
+        header.parent_hash = Hash256::ZERO;
+        assert!(matches!(
+            params.check_header(&store, &header),
+            Err(ConsensusError::MissingAncestor { height: 0 })
+        ));
+    }
+
+    #[test]
+    fn test_validate_params() {
+        PowParams::default().validate().unwrap();
+        assert!(params(Retarget::Asert { half_life: 0 }).validate().is_err());
+        assert!(params(Retarget::WindowedAverage { window: 1 }).validate().is_err());
+        assert!(PowParams { target_spacing: 0, ..PowParams::default() }.validate().is_err());
+        assert!(PowParams { pow_limit: 0, ..PowParams::default() }.validate().is_err());
+    }
+}
@@ -1,2 +1,6 @@
 pub mod types;
-pub mod network;
\ No newline at end of file
+pub mod network;
+pub mod chain;
+pub mod consensus;
+pub mod storage;
+pub mod state;
\ No newline at end of file
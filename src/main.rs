@@ -1,71 +1,191 @@
-use faic_core::network::config::{NetworkConfig, NetworkConfigError};
-use libp2p::PeerId;
+use clap::Parser;
+use faic_core::network::config::{ConfigIssue, NetworkConfig, NetworkConfigError};
+use faic_core::network::loader::{self, config_path, ConfigLoader, EffectiveConfig, CONFIG_PATH_ENV};
+use libp2p::multiaddr::Protocol;
+use libp2p::{Multiaddr, PeerId};
+use std::path::{Path, PathBuf};
 
+/// 身份密钥文件口令的环境变量，未设置时密钥文件不加密
+const PASSPHRASE_ENV: &str = "FAIC_IDENTITY_PASSPHRASE";
 
+/// FAIC 节点
+///
+/// 配置按以下顺序逐层覆盖：内置默认值、配置文件、FAIC_NET__* 环境变量、命令行参数。
+#[derive(Debug, Parser)]
+#[command(name = "faic_core", version)]
+struct Cli {
+    /// 配置文件路径，也可以用 FAIC_CONFIG 环境变量指定
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// 监听地址，可以重复指定，覆盖 listen_addresses
+    #[arg(long = "listen", value_name = "MULTIADDR")]
+    listen_addresses: Vec<Multiaddr>,
+    /// 引导节点地址，必须以 /p2p/<PeerId> 结尾，可以重复指定，覆盖 bootstrap_nodes
+    #[arg(long = "bootstrap", value_name = "MULTIADDR")]
+    bootstrap_nodes: Vec<Multiaddr>,
+    /// 最大连接数
+    #[arg(long, value_name = "N")]
+    max_connections: Option<u32>,
+    /// 连接超时时间（秒）
+    #[arg(long, value_name = "SECS")]
+    connection_timeout: Option<u64>,
+    /// 心跳间隔时间（秒）
+    #[arg(long, value_name = "SECS")]
+    heartbeat_interval: Option<u64>,
+    /// 身份密钥文件路径
+    #[arg(long, value_name = "PATH")]
+    identity_file: Option<PathBuf>,
+    /// 打印最终生效的配置及每个字段的来源，然后退出；不创建或修改任何文件
+    #[arg(long)]
+    print_effective_config: bool,
+}
 
 fn main() -> Result<(), NetworkConfigError> {
-    let config_path = "config.toml"; // 默认路径
-    load_or_create_config(config_path, None) // 传递 None 作为 peer_id
+    let cli = Cli::parse();
+    let passphrase = std::env::var(PASSPHRASE_ENV).ok();
+    // 第一次运行时在默认路径创建配置文件，显式指定的路径必须已经存在
+    let (path, required) = config_path(cli.config.clone(), std::env::var(CONFIG_PATH_ENV).ok());
+    if !required && !cli.print_effective_config {
+        load_or_create_config(&path.to_string_lossy(), None)?;
+    }
+    let effective = load_config(&cli, path, required, std::env::vars(), passphrase.as_deref())?;
+    for warning in &effective.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+    if cli.print_effective_config {
+        print!("{}", effective.render()?);
+        return Ok(());
+    }
+    println!("Loaded network config: {:?}", effective.config);
+    println!("Local peer id: {}", effective.keypair.public().to_peer_id());
+    Ok(())
 }
 
+/// 配置文件不存在时创建默认配置
+///
+/// `peer_id` 为 None 时不写入 local_peer_id，加载时由身份密钥文件派生。
 fn load_or_create_config(config_path: &str, peer_id: Option<PeerId>) -> Result<(), NetworkConfigError> {
-    match NetworkConfig::load_from_file(config_path) {
-        Ok(config) => {
-            println!("Loaded network config: {:?}", config);
-            Ok(())
-        }
-        Err(err) => match err {
-            NetworkConfigError::IoError(ref io_err) if io_err.kind() == std::io::ErrorKind::NotFound => {
-                println!("Config file not found, creating default config.");
-                let default_config = match peer_id {
-                    Some(id) => NetworkConfig::new(id),
-                    None => NetworkConfig::default(),
-                };
-                default_config.save_to_file(config_path)?;
-                Ok(())
-            }
-            _ => {
-                println!("Error loading config: {}", err);
-                Err(err)
-            }
-        },
+    if Path::new(config_path).exists() {
+        return Ok(());
+    }
+    println!("Config file not found, creating default config.");
+    match peer_id {
+        Some(id) => NetworkConfig::new(id).save_to_file(config_path),
+        None => loader::save_defaults(Path::new(config_path)),
     }
 }
 
+/// 按 默认值 → 配置文件 → 环境变量 → 命令行参数 的顺序加载配置
+///
+/// `path` 和 `required` 由 `config_path` 解析得到。打印生效配置时只读加载，
+/// 不升级配置文件，也不生成身份密钥文件。
+fn load_config<I>(
+    cli: &Cli,
+    path: PathBuf,
+    required: bool,
+    env: I,
+    passphrase: Option<&str>,
+) -> Result<EffectiveConfig, NetworkConfigError>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    let mut loader = ConfigLoader::new().file(path, required).env(env);
+    if cli.print_effective_config {
+        loader = loader.read_only();
+    }
+
+    if !cli.listen_addresses.is_empty() {
+        let addresses: Vec<String> = cli.listen_addresses.iter().map(|a| a.to_string()).collect();
+        loader = loader.set("listen_addresses", addresses, "--listen");
+    }
+    if !cli.bootstrap_nodes.is_empty() {
+        let mut nodes = Vec::new();
+        for (i, address) in cli.bootstrap_nodes.iter().enumerate() {
+            // 从地址末尾的 /p2p/ 中取出 PeerId
+            let Some(Protocol::P2p(peer_id)) = address.iter().last() else {
+                return Err(NetworkConfigError::Invalid(vec![ConfigIssue::new(
+                    format!("--bootstrap[{}]", i),
+                    "must end with /p2p/<peer id>",
+                )]));
+            };
+            nodes.push(vec![peer_id.to_string(), address.to_string()]);
+        }
+        loader = loader.set("bootstrap_nodes", nodes, "--bootstrap");
+    }
+    if let Some(max_connections) = cli.max_connections {
+        loader = loader.set("max_connections", max_connections, "--max-connections");
+    }
+    if let Some(secs) = cli.connection_timeout {
+        loader = loader.set("connection_timeout", seconds(secs, "--connection-timeout")?, "--connection-timeout");
+    }
+    if let Some(secs) = cli.heartbeat_interval {
+        loader = loader.set("heartbeat_interval", seconds(secs, "--heartbeat-interval")?, "--heartbeat-interval");
+    }
+    if let Some(path) = &cli.identity_file {
+        loader = loader.set("identity_file", path.display().to_string(), "--identity-file");
+    }
+    loader.load(passphrase)
+}
 
+// TOML 整数是 i64，超出范围的秒数报告为该参数的问题
+fn seconds(secs: u64, flag: &str) -> Result<i64, NetworkConfigError> {
+    i64::try_from(secs).map_err(|_| {
+        NetworkConfigError::Invalid(vec![ConfigIssue::new(flag, format!("must be at most {} seconds", i64::MAX))])
+    })
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use faic_core::network::loader::ConfigSource;
     use std::fs;
     use std::io::Write;
     use std::time::Duration;
+
+    fn cli(args: &[&str]) -> Cli {
+        Cli::try_parse_from(std::iter::once("faic_core").chain(args.iter().copied())).unwrap()
+    }
+
+    // 与 main 相同：先由 --config 解析配置文件路径，再加载
+    fn load(args: &Cli, env: Vec<(String, String)>) -> Result<EffectiveConfig, NetworkConfigError> {
+        let (path, required) = config_path(args.config.clone(), None);
+        load_config(args, path, required, env, None)
+    }
+
     #[test]
     fn test_load_valid_config() {
         // 创建一个有效的配置文件
         let config_path = "test_valid_config.toml";
+        let identity_file = "test_valid_identity.key";
         let mut file = fs::File::create(config_path).unwrap();
         writeln!(
             file,
             r#"
-    local_peer_id = "12D3KooWLyEavPji9n9qaGcoe5j4qoJYkDwHLN5MU7o26d54fVMD"
+    config_version = 1
     listen_addresses = ["/ip4/127.0.0.1/tcp/8080"]
     bootstrap_nodes = []
     max_connections = 50
     connection_timeout = 20
     heartbeat_interval = 30
-    "#
+    identity_file = "{}"
+    "#,
+            identity_file
         )
         .unwrap();
-    
-        // 运行主程序逻辑，传递 None 作为 peer_id
-        let result = load_or_create_config(config_path, None);
-    
+
+        // 通过 --config 指定配置文件
+        let loaded = load(&cli(&["--config", config_path]), vec![]).unwrap();
+
         // 验证结果
-        assert!(result.is_ok());
-    
+        assert_eq!(loaded.config.listen_addresses, vec!["/ip4/127.0.0.1/tcp/8080".parse().unwrap()]);
+        assert_eq!(loaded.config.max_connections, 50);
+        assert_eq!(loaded.config.connection_timeout, Duration::from_secs(20));
+        assert_eq!(loaded.config.heartbeat_interval, Duration::from_secs(30));
+        assert_eq!(loaded.config.local_peer_id, loaded.keypair.public().to_peer_id());
+
         // 删除临时文件
         fs::remove_file(config_path).unwrap();
+        fs::remove_file(identity_file).unwrap();
     }
 
     #[test]
@@ -105,22 +225,110 @@ mod tests {
         fs::remove_file(config_path).unwrap();
     }
 
+    #[test]
+    fn test_explicit_config_not_found() {
+        // 使用测试专用的文件路径
+        let config_path = "test_explicit_not_found_config.toml";
+
+        // 确保文件不存在
+        fs::remove_file(config_path).unwrap_or(());
+
+        // 通过 FAIC_CONFIG 显式指定的文件必须存在
+        let (path, required) = loader::config_path(None, Some(config_path.to_string()));
+        assert!(required);
+        let result = load_config(&cli(&[]), path, required, vec![], None);
+        assert!(matches!(result, Err(NetworkConfigError::IoError(ref e)) if e.kind() == std::io::ErrorKind::NotFound));
+
+        // 不会创建配置文件
+        assert!(fs::metadata(config_path).is_err());
+    }
+
+    #[test]
+    fn test_print_effective_config_is_read_only() {
+        let config_path = "test_print_config.toml";
+        let identity_file = "test_print_identity.key";
+        // 没有版本号的旧配置文件
+        let legacy = format!("max_connections = 50\nidentity_file = \"{}\"\n", identity_file);
+        fs::write(config_path, &legacy).unwrap();
+        fs::remove_file(identity_file).unwrap_or(());
+
+        // 不生成身份密钥文件，也不升级配置文件
+        let result = load(&cli(&["--config", config_path, "--print-effective-config"]), vec![]);
+        assert!(matches!(result, Err(NetworkConfigError::IdentityMissing(_))));
+        assert!(fs::metadata(identity_file).is_err());
+        assert_eq!(fs::read_to_string(config_path).unwrap(), legacy);
+
+        fs::remove_file(config_path).unwrap();
+    }
+
     #[test]
     fn test_load_invalid_config() {
         // 使用测试专用的文件路径
         let config_path = "test_invalid_config.toml";
-        
+
         // 创建无效的 TOML 文件
         let mut file = fs::File::create(config_path).unwrap();
         writeln!(file, "invalid toml content").unwrap();
-    
-        // 运行主逻辑，传入测试专用的文件路径和 None 作为 peer_id
-        let result = load_or_create_config(config_path, None);
-    
+
+        // 运行主逻辑，通过 --config 指定无效文件
+        let result = load(&cli(&["--config", config_path]), vec![]);
+
         // 检查是否返回错误
-        assert!(result.is_err());
-    
+        assert!(matches!(result, Err(NetworkConfigError::TomlError(_))));
+
         // 清理文件
         fs::remove_file(config_path).unwrap();
     }
+
+    #[test]
+    fn test_cli_overrides_env() {
+        let config_path = "test_cli_config.toml";
+        let identity_file = "test_cli_identity.key";
+        let bootstrap_peer = libp2p::PeerId::random();
+        let bootstrap = format!("/ip4/10.0.0.1/tcp/4001/p2p/{}", bootstrap_peer);
+        let args = cli(&[
+            "--config",
+            config_path,
+            "--listen",
+            "/ip4/127.0.0.1/tcp/4001",
+            "--listen",
+            "/ip4/127.0.0.1/udp/4001/quic-v1",
+            "--bootstrap",
+            &bootstrap,
+            "--max-connections",
+            "7",
+            "--identity-file",
+            identity_file,
+        ]);
+        let env = vec![
+            ("FAIC_NET__MAX_CONNECTIONS".to_string(), "3".to_string()),
+            ("FAIC_NET__CONNECTION_TIMEOUT".to_string(), "4".to_string()),
+        ];
+        // 空配置文件：所有字段都来自其他层
+        fs::write(config_path, "config_version = 1\n").unwrap();
+
+        let loaded = load(&args, env).unwrap();
+        assert_eq!(loaded.config.listen_addresses.len(), 2);
+        assert_eq!(loaded.config.bootstrap_nodes, vec![(bootstrap_peer, bootstrap.parse().unwrap())]);
+        assert_eq!(loaded.config.max_connections, 7);
+        assert_eq!(loaded.config.connection_timeout, Duration::from_secs(4));
+        assert_eq!(loaded.source("max_connections"), Some(&ConfigSource::Cli("--max-connections".to_string())));
+        assert_eq!(
+            loaded.source("connection_timeout"),
+            Some(&ConfigSource::Env("FAIC_NET__CONNECTION_TIMEOUT".to_string()))
+        );
+
+        // 引导节点地址必须带 /p2p/
+        let args = cli(&["--bootstrap", "/ip4/10.0.0.1/tcp/4001", "--identity-file", identity_file]);
+        let result = load(&args, vec![]);
+        assert!(matches!(result, Err(NetworkConfigError::Invalid(ref issues)) if issues[0].field == "--bootstrap[0]"));
+
+        // 超出 TOML 整数范围的秒数
+        let args = cli(&["--connection-timeout", &u64::MAX.to_string(), "--identity-file", identity_file]);
+        let result = load(&args, vec![]);
+        assert!(matches!(result, Err(NetworkConfigError::Invalid(ref issues)) if issues[0].field == "--connection-timeout"));
+
+        fs::remove_file(config_path).unwrap();
+        fs::remove_file(identity_file).unwrap();
+    }
 }
@@ -1,6 +1,8 @@
-use libp2p::{Multiaddr, PeerId};  //用于P2P网络通信
+use libp2p::{multiaddr::Protocol, Multiaddr, PeerId};  //用于P2P网络通信
 use serde::{Deserialize, Serialize};  //用于序列化和反序列化
-use std::fs;
+use libp2p::identity::Keypair;  //节点身份密钥对
+use crate::network::{keyfile, migrate};
+use std::path::{Path, PathBuf};
 use std::time::Duration;  //用于时间操作
 use toml;  //用于TOML格式解析
 
@@ -32,7 +34,7 @@ mod serde_multiaddr {
     use serde::{Deserialize, Serializer, Serialize};
 
     // 序列化 Multiaddr 为字符串
-    pub fn serialize<S>(multiaddrs: &Vec<Multiaddr>, serializer: S) -> Result<S::Ok, S::Error>
+    pub fn serialize<S>(multiaddrs: &[Multiaddr], serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
@@ -131,7 +133,27 @@ pub struct NetworkConfig {
     // 心跳间隔时间，使用自定义的序列化和反序列化方法
     #[serde(with = "serde_duration_secs")]
     pub heartbeat_interval: Duration,
+    // 身份密钥文件路径，local_peer_id 必须由其中的密钥对派生
+    #[serde(default = "default_identity_file")]
+    pub identity_file: PathBuf,
+}
 
+/// `NetworkConfig` 的所有字段，按结构体中的顺序排列
+pub const FIELDS: &[&str] = &[
+    "local_peer_id",
+    "listen_addresses",
+    "bootstrap_nodes",
+    "max_connections",
+    "connection_timeout",
+    "heartbeat_interval",
+    "identity_file",
+];
+
+/// 身份密钥文件的默认路径
+pub const DEFAULT_IDENTITY_FILE: &str = "identity.key";
+
+fn default_identity_file() -> PathBuf {
+    PathBuf::from(DEFAULT_IDENTITY_FILE)
 }
 
 impl NetworkConfig {
@@ -144,25 +166,47 @@ impl NetworkConfig {
             max_connections: 100,
             connection_timeout: Duration::from_secs(10),
             heartbeat_interval: Duration::from_secs(60),
+            identity_file: default_identity_file(),
         }
     }
+
+    /// 创建默认配置，local_peer_id 由身份密钥文件派生；密钥文件不存在时生成并保存新的密钥对
+    ///
+    /// # Arguments
+    ///
+    /// * `identity_file` - 身份密钥文件路径
+    /// * `passphrase` - 密钥文件的口令，为 None 时密钥文件不加密
+    pub fn with_identity(
+        identity_file: impl Into<PathBuf>,
+        passphrase: Option<&str>,
+    ) -> Result<(Self, Keypair), NetworkConfigError> {
+        let identity_file = identity_file.into();
+        let keypair = keyfile::load_or_generate(&identity_file, passphrase)?;
+        let config = NetworkConfig { identity_file, ..NetworkConfig::new(keypair.public().to_peer_id()) };
+        Ok((config, keypair))
+    }
 }
 
+/// 配置校验发现的一个问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// 出问题的字段路径，例如 `bootstrap_nodes[1].address`
+    pub field: String,
+    /// 问题描述
+    pub message: String,
+}
 
-// 默认实现 NetworkConfig 的默认值
-impl Default for NetworkConfig {
-    fn default() -> Self {
-        let config = NetworkConfig {
-            local_peer_id: PeerId::random(), // 随机生成 PeerId
-            listen_addresses: vec!["/ip4/0.0.0.0/tcp/0".parse().unwrap()], // 监听所有IPv4地址的0号端口
-            bootstrap_nodes: vec![], // 初始为空
-            max_connections: 100, // 最大连接数
-            connection_timeout: Duration::from_secs(10), // 连接超时时间
-            heartbeat_interval: Duration::from_secs(60), // 心跳间隔时间
+impl ConfigIssue {
+    /// 创建一个问题
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        ConfigIssue { field: field.into(), message: message.into() }
+    }
+}
 
-        };
-        println!("网络配置默认值: {:?}", config);
-        config
+// 为 ConfigIssue 实现 Display trait，格式为 "字段: 描述"
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
     }
 }
 
@@ -172,6 +216,14 @@ pub enum NetworkConfigError {
     IoError(std::io::Error),  // 文件操作错误
     TomlError(toml::de::Error),  // TOML解析错误
     TomlSerializeError(toml::ser::Error),  // TOML序列化错误
+    IdentityDecode(String),  // 身份密钥文件无法解码
+    IdentityMissing(PathBuf),  // 身份密钥文件不存在
+    IdentityPassphrase,  // 身份密钥文件已加密，但缺少口令或口令错误
+    IdentityPermissions(u32),  // 身份密钥文件的权限宽于 0600
+    IdentityMismatch { expected: Box<PeerId>, actual: Box<PeerId> },  // 密钥对派生的 PeerId 与配置中的 local_peer_id 不一致
+    Invalid(Vec<ConfigIssue>),  // 配置语义校验失败，包含发现的所有问题
+    UnsupportedVersion { found: u32, supported: u32 },  // 配置文件的版本比当前程序支持的更新
+    BackupExists(PathBuf),  // 迁移前的备份文件已经存在，拒绝覆盖
 }
 
 // 为 NetworkConfigError 实现 Display trait，用于打印错误信息
@@ -181,6 +233,39 @@ impl std::fmt::Display for NetworkConfigError {
             NetworkConfigError::IoError(e) => write!(f, "IO error: {}", e),
             NetworkConfigError::TomlError(e) => write!(f, "TOML deserialization error: {}", e),
             NetworkConfigError::TomlSerializeError(e) => write!(f, "TOML serialization error: {}", e),
+            NetworkConfigError::IdentityDecode(e) => write!(f, "Invalid identity key file: {}", e),
+            NetworkConfigError::IdentityMissing(path) => {
+                write!(f, "Identity key file {} does not exist", path.display())
+            }
+            NetworkConfigError::IdentityPassphrase => {
+                write!(f, "Identity key file is encrypted and the passphrase is missing or wrong")
+            }
+            NetworkConfigError::IdentityPermissions(mode) => {
+                write!(f, "Identity key file permissions {:o} are too open, expected 600", mode)
+            }
+            NetworkConfigError::IdentityMismatch { expected, actual } => write!(
+                f,
+                "Identity key belongs to {}, but local_peer_id is {}",
+                actual, expected
+            ),
+            NetworkConfigError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "Config version {} is newer than the supported version {}",
+                found, supported
+            ),
+            NetworkConfigError::BackupExists(path) => {
+                write!(f, "Config backup {} already exists, move it away before migrating", path.display())
+            }
+            NetworkConfigError::Invalid(issues) => {
+                write!(f, "Invalid network config: ")?;
+                for (i, issue) in issues.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{}", issue)?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -218,15 +303,31 @@ impl NetworkConfig {
     ///
     /// # Returns
     ///
-    /// `Result<NetworkConfig, NetworkConfigError>` - 返回加载的配置或错误    
-
+    /// `Result<NetworkConfig, NetworkConfigError>` - 返回加载的配置或错误。
+    /// 加载时的警告被丢弃，需要报告警告时使用 `load_from_file_with_warnings`。
     pub fn load_from_file(path: &str) -> Result<Self, NetworkConfigError> {
-        // 读取配置文件内容到字符串
-        let config_str = fs::read_to_string(path)?;
-        // 将字符串反序列化为 NetworkConfig 结构体
-        let config: NetworkConfig = toml::from_str(&config_str)?;
-        // 返回加载的配置
-        Ok(config)
+        Ok(Self::load_from_file_with_warnings(path)?.0)
+    }
+
+    /// 从文件加载网络配置，并返回加载时的警告
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - 配置文件的路径
+    ///
+    /// # Returns
+    ///
+    /// `Result<(NetworkConfig, Vec<ConfigIssue>), NetworkConfigError>` - 返回加载的配置和警告（被忽略的未知键、
+    /// 执行过的迁移），或错误
+    pub fn load_from_file_with_warnings(path: &str) -> Result<(Self, Vec<ConfigIssue>), NetworkConfigError> {
+        // 读取配置文件，旧版本的文件会被就地升级
+        let loaded = migrate::load_file(Path::new(path))?;
+        // 将配置表反序列化为 NetworkConfig 结构体
+        let config: NetworkConfig = toml::Value::Table(loaded.table).try_into()?;
+        // 校验配置的语义
+        config.validate()?;
+        // 返回加载的配置和警告
+        Ok((config, loaded.warnings))
     }
 
     /// 保存网络配置到文件
@@ -239,13 +340,95 @@ impl NetworkConfig {
     ///
     /// `Result<(), NetworkConfigError>` - 返回成功或错误
     pub fn save_to_file(&self, path: &str) -> Result<(), NetworkConfigError> {
-        // 将 NetworkConfig 结构体序列化为字符串
-        let config_str = toml::to_string(self)?;
+        // 将 NetworkConfig 结构体序列化为带版本号的字符串
+        let config_str = migrate::to_versioned_string(self)?;
         // 将字符串写入文件
         std::fs::write(path, config_str)?;
         // 返回成功
         Ok(())
     }
+
+    /// 校验配置的语义，一次返回发现的所有问题
+    ///
+    /// # Returns
+    ///
+    /// `Result<(), NetworkConfigError>` - 配置有问题时返回 `NetworkConfigError::Invalid`
+    pub fn validate(&self) -> Result<(), NetworkConfigError> {
+        let mut issues = Vec::new();
+
+        if self.max_connections == 0 {
+            issues.push(ConfigIssue::new("max_connections", "must be greater than 0"));
+        }
+        if self.connection_timeout.is_zero() {
+            issues.push(ConfigIssue::new("connection_timeout", "must be greater than 0"));
+        }
+        if self.heartbeat_interval.is_zero() {
+            issues.push(ConfigIssue::new("heartbeat_interval", "must be greater than 0"));
+        }
+
+        // 重复的监听地址
+        for (i, address) in self.listen_addresses.iter().enumerate() {
+            if let Some(first) = self.listen_addresses[..i].iter().position(|a| a == address) {
+                issues.push(ConfigIssue::new(
+                    format!("listen_addresses[{}]", i),
+                    format!("duplicate of listen_addresses[{}]", first),
+                ));
+            }
+        }
+
+        for (i, (peer_id, address)) in self.bootstrap_nodes.iter().enumerate() {
+            if *peer_id == self.local_peer_id {
+                issues.push(ConfigIssue::new(
+                    format!("bootstrap_nodes[{}].peer_id", i),
+                    "is the local peer id",
+                ));
+            }
+            // 地址中的 /p2p/ 必须与配对的 PeerId 一致
+            let embedded = address.iter().find_map(|protocol| match protocol {
+                Protocol::P2p(id) => Some(id),
+                _ => None,
+            });
+            if let Some(embedded) = embedded {
+                if embedded != *peer_id {
+                    issues.push(ConfigIssue::new(
+                        format!("bootstrap_nodes[{}].address", i),
+                        format!("contains /p2p/{}, but the peer id is {}", embedded, peer_id),
+                    ));
+                }
+            }
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(NetworkConfigError::Invalid(issues))
+        }
+    }
+
+    /// 检查密钥对是否与 local_peer_id 一致
+    pub fn check_identity(&self, keypair: &Keypair) -> Result<(), NetworkConfigError> {
+        let actual = keypair.public().to_peer_id();
+        if actual != self.local_peer_id {
+            return Err(NetworkConfigError::IdentityMismatch {
+                expected: Box::new(self.local_peer_id),
+                actual: Box::new(actual),
+            });
+        }
+        Ok(())
+    }
+
+    /// 读取身份密钥文件，并检查它与 local_peer_id 是否一致
+    ///
+    /// 密钥文件不存在时返回 `IdentityMissing`，不会生成新的密钥对：新密钥必然与 local_peer_id 不一致。
+    ///
+    /// # Arguments
+    ///
+    /// * `passphrase` - 密钥文件的口令，为 None 时密钥文件不加密
+    pub fn load_identity(&self, passphrase: Option<&str>) -> Result<Keypair, NetworkConfigError> {
+        let keypair = keyfile::load_keypair(&self.identity_file, passphrase)?;
+        self.check_identity(&keypair)?;
+        Ok(keypair)
+    }
 }
 
 #[cfg(test)]
@@ -259,7 +442,7 @@ mod tests {
         let temp_file = "temp_config.toml";
 
         // 创建一个默认配置
-        let config = NetworkConfig::default();
+        let config = NetworkConfig::new(PeerId::random());
 
         // 保存配置到临时文件
         config.save_to_file(temp_file).unwrap();
@@ -274,11 +457,27 @@ mod tests {
         assert_eq!(config.max_connections, loaded_config.max_connections);
         assert_eq!(config.connection_timeout, loaded_config.connection_timeout);
         assert_eq!(config.heartbeat_interval, loaded_config.heartbeat_interval);
+        assert_eq!(config.identity_file, loaded_config.identity_file);
 
         // 删除临时文件
         fs::remove_file(temp_file).unwrap();
     }
 
+    #[test]
+    fn test_load_returns_warnings() {
+        let path = "test_config_warnings.toml";
+        let config = NetworkConfig::new(PeerId::random());
+        let text = format!("{}gossip = true\n", migrate::to_versioned_string(&config).unwrap());
+        fs::write(path, text).unwrap();
+
+        // 未知键作为警告返回给调用方
+        let (loaded, warnings) = NetworkConfig::load_from_file_with_warnings(path).unwrap();
+        assert_eq!(loaded.local_peer_id, config.local_peer_id);
+        assert_eq!(warnings, vec![ConfigIssue::new("gossip", "unknown key, ignored")]);
+
+        fs::remove_file(path).unwrap();
+    }
+
     #[test]
     fn test_load_invalid_config() {
         // 创建一个无效的配置文件
@@ -297,7 +496,13 @@ mod tests {
 
     #[test]
     fn test_default_config() {
-        let config = NetworkConfig::default();
+        let identity_file = PathBuf::from("test_default_identity.key");
+        let (config, keypair) = NetworkConfig::with_identity(&identity_file, None).unwrap();
+
+        // 身份来自密钥文件
+        assert_eq!(config.local_peer_id, keypair.public().to_peer_id());
+        assert_eq!(config.identity_file, identity_file);
+        assert_eq!(NetworkConfig::with_identity(&identity_file, None).unwrap().0.local_peer_id, config.local_peer_id);
     
         // 检查 listen_addresses
         assert_eq!(
@@ -310,5 +515,83 @@ mod tests {
         assert_eq!(config.max_connections, 100);
         assert_eq!(config.connection_timeout, Duration::from_secs(10));
         assert_eq!(config.heartbeat_interval, Duration::from_secs(60));
+
+        fs::remove_file(&identity_file).unwrap();
+    }
+
+    #[test]
+    fn test_load_identity() {
+        let identity_file = PathBuf::from("test_config_identity.key");
+        fs::remove_file(&identity_file).unwrap_or(());
+
+        // 配置已经给出 local_peer_id，缺少密钥文件时直接报错，不会生成新的密钥文件
+        let config = NetworkConfig { identity_file: identity_file.clone(), ..NetworkConfig::new(PeerId::random()) };
+        let result = config.load_identity(None);
+        assert!(matches!(result, Err(NetworkConfigError::IdentityMissing(ref path)) if *path == identity_file));
+        assert!(!identity_file.exists());
+
+        // 从密钥文件派生 local_peer_id 后，加载成功且身份保持不变
+        let (mut config, keypair) = NetworkConfig::with_identity(&identity_file, None).unwrap();
+        assert_eq!(config.load_identity(None).unwrap().public(), keypair.public());
+
+        // 与密钥文件不一致的 local_peer_id
+        config.local_peer_id = PeerId::random();
+        assert!(matches!(config.load_identity(None), Err(NetworkConfigError::IdentityMismatch { .. })));
+
+        fs::remove_file(&identity_file).unwrap();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_validate_reports_all_issues() {
+        let local = PeerId::random();
+        let other = PeerId::random();
+        let address: Multiaddr = "/ip4/127.0.0.1/tcp/4001".parse().unwrap();
+        let config = NetworkConfig {
+            listen_addresses: vec![address.clone(), "/ip4/0.0.0.0/tcp/0".parse().unwrap(), address.clone()],
+            bootstrap_nodes: vec![
+                (local, address.clone()),
+                (other, address.clone().with(Protocol::P2p(other))),
+                (other, address.clone().with(Protocol::P2p(local))),
+            ],
+            max_connections: 0,
+            connection_timeout: Duration::ZERO,
+            ..NetworkConfig::new(local)
+        };
+
+        let issues = match config.validate() {
+            Err(NetworkConfigError::Invalid(issues)) => issues,
+            other => panic!("expected Invalid, got {:?}", other),
+        };
+        let fields: Vec<&str> = issues.iter().map(|issue| issue.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "max_connections",
+                "connection_timeout",
+                "listen_addresses[2]",
+                "bootstrap_nodes[0].peer_id",
+                "bootstrap_nodes[2].address",
+            ]
+        );
+        assert_eq!(issues[2].message, "duplicate of listen_addresses[0]");
+
+        assert!(NetworkConfig::new(local).validate().is_ok());
+    }
+
+    #[test]
+    fn test_load_validates_config() {
+        let path = "invalid_semantics_config.toml";
+        let config = NetworkConfig { max_connections: 0, ..NetworkConfig::new(PeerId::random()) };
+        config.save_to_file(path).unwrap();
+
+        // 加载时自动校验
+        let result = NetworkConfig::load_from_file(path);
+        assert!(matches!(result, Err(NetworkConfigError::Invalid(ref issues)) if issues[0].field == "max_connections"));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid network config: max_connections: must be greater than 0"
+        );
+
+        fs::remove_file(path).unwrap();
+    }
+}
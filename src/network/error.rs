@@ -1,4 +1,6 @@
 use crate::network::config::NetworkConfigError;
+use crate::state::StateError;
+use crate::storage::StorageError;
 use std::fmt;
 
 /// 定义整个 faic_core 项目的通用错误类型
@@ -10,6 +12,10 @@ pub enum Error {
     Network(String),
     /// IO 错误
     Io(std::io::Error),
+    /// 存储错误
+    Storage(StorageError),
+    /// 账户状态错误
+    State(StateError),
     /// 其他错误
     Other(String),
 }
@@ -21,6 +27,8 @@ impl fmt::Display for Error {
             Error::NetworkConfig(e) => write!(f, "Network config error: {}", e),
             Error::Network(e) => write!(f, "Network error: {}", e),
             Error::Io(e) => write!(f, "IO error: {}", e),
+            Error::Storage(e) => write!(f, "Storage error: {}", e),
+            Error::State(e) => write!(f, "State error: {}", e),
             Error::Other(e) => write!(f, "Other error: {}", e),
         }
     }
@@ -43,6 +51,20 @@ impl From<std::io::Error> for Error {
     }
 }
 
+// 实现从 StorageError 到 Error 的转换
+impl From<StorageError> for Error {
+    fn from(err: StorageError) -> Self {
+        Error::Storage(err)
+    }
+}
+
+// 实现从 StateError 到 Error 的转换
+impl From<StateError> for Error {
+    fn from(err: StateError) -> Self {
+        Error::State(err)
+    }
+}
+
 // 可以在这里添加其他错误类型的转换，例如：
 // impl From<WalletError> for Error {
 //     fn from(err: WalletError) -> Self {
@@ -0,0 +1,221 @@
+use crate::network::config::NetworkConfigError;
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use libp2p::identity::Keypair;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// 加密密钥文件的魔数，明文密钥文件直接是 protobuf 编码，不会以它开头
+const MAGIC: &[u8; 8] = b"FAICKEY\x01";
+/// Argon2 盐的长度
+const SALT_LEN: usize = 16;
+/// ChaCha20-Poly1305 随机数的长度
+const NONCE_LEN: usize = 12;
+/// 加密文件头的长度：魔数、三个 Argon2 参数、盐和随机数
+const HEADER_LEN: usize = MAGIC.len() + 12 + SALT_LEN + NONCE_LEN;
+
+/// 读取身份密钥文件
+///
+/// 明文文件是 libp2p 密钥对的 protobuf 编码；加密文件需要提供口令。
+/// 在 Unix 上，文件权限不能宽于 0600。文件不存在时返回 `IdentityMissing`。
+pub fn load_keypair(path: &Path, passphrase: Option<&str>) -> Result<Keypair, NetworkConfigError> {
+    let data = match fs::read(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err(NetworkConfigError::IdentityMissing(path.to_path_buf()));
+        }
+        result => result?,
+    };
+    check_permissions(path)?;
+    let encoded = if data.starts_with(MAGIC) {
+        let passphrase = passphrase.ok_or(NetworkConfigError::IdentityPassphrase)?;
+        decrypt(&data, passphrase)?
+    } else {
+        data
+    };
+    Keypair::from_protobuf_encoding(&encoded).map_err(|e| NetworkConfigError::IdentityDecode(e.to_string()))
+}
+
+/// 保存身份密钥文件，权限为 0600
+///
+/// 提供口令时用 Argon2id 派生密钥，再用 ChaCha20-Poly1305 加密。
+/// 先写入临时文件再重命名，避免中途失败留下损坏的密钥文件。
+pub fn save_keypair(path: &Path, keypair: &Keypair, passphrase: Option<&str>) -> Result<(), NetworkConfigError> {
+    let encoded = keypair
+        .to_protobuf_encoding()
+        .map_err(|e| NetworkConfigError::IdentityDecode(e.to_string()))?;
+    let data = match passphrase {
+        Some(passphrase) => encrypt(&encoded, passphrase)?,
+        None => encoded,
+    };
+
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = Path::new(&tmp);
+    let mut file = create_private(tmp)?;
+    file.write_all(&data)?;
+    file.sync_all()?;
+    fs::rename(tmp, path)?;
+    Ok(())
+}
+
+/// 读取身份密钥文件，文件不存在时生成新的 Ed25519 密钥对并保存
+pub fn load_or_generate(path: &Path, passphrase: Option<&str>) -> Result<Keypair, NetworkConfigError> {
+    match load_keypair(path, passphrase) {
+        Err(NetworkConfigError::IdentityMissing(_)) => {
+            let keypair = Keypair::generate_ed25519();
+            save_keypair(path, &keypair, passphrase)?;
+            Ok(keypair)
+        }
+        result => result,
+    }
+}
+
+// 派生加密密钥
+fn derive_key(passphrase: &str, salt: &[u8], params: Params) -> Result<Key, NetworkConfigError> {
+    let mut key = Key::default();
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| NetworkConfigError::IdentityDecode(e.to_string()))?;
+    Ok(key)
+}
+
+// 加密格式：魔数 | m_cost | t_cost | p_cost | 盐 | 随机数 | 密文
+fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>, NetworkConfigError> {
+    let params = Params::DEFAULT;
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let key = derive_key(passphrase, &salt, params.clone())?;
+    let ciphertext = ChaCha20Poly1305::new(&key)
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|_| NetworkConfigError::IdentityDecode("Encryption failed".to_string()))?;
+
+    let mut data = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&params.m_cost().to_be_bytes());
+    data.extend_from_slice(&params.t_cost().to_be_bytes());
+    data.extend_from_slice(&params.p_cost().to_be_bytes());
+    data.extend_from_slice(&salt);
+    data.extend_from_slice(&nonce);
+    data.extend_from_slice(&ciphertext);
+    Ok(data)
+}
+
+fn decrypt(data: &[u8], passphrase: &str) -> Result<Vec<u8>, NetworkConfigError> {
+    if data.len() < HEADER_LEN {
+        return Err(NetworkConfigError::IdentityDecode("Encrypted key file is truncated".to_string()));
+    }
+    let (header, ciphertext) = data.split_at(HEADER_LEN);
+    let field = |i: usize| {
+        let start = MAGIC.len() + 4 * i;
+        u32::from_be_bytes(header[start..start + 4].try_into().unwrap())
+    };
+    let params = Params::new(field(0), field(1), field(2), None)
+        .map_err(|e| NetworkConfigError::IdentityDecode(e.to_string()))?;
+    let salt = &header[MAGIC.len() + 12..MAGIC.len() + 12 + SALT_LEN];
+    let nonce = &header[HEADER_LEN - NONCE_LEN..];
+
+    let key = derive_key(passphrase, salt, params)?;
+    // 认证失败说明口令错误或文件被篡改
+    ChaCha20Poly1305::new(&key)
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| NetworkConfigError::IdentityPassphrase)
+}
+
+// 以 0600 权限创建（或截断）文件
+fn create_private(path: &Path) -> io::Result<fs::File> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        let file = options.open(path)?;
+        // 文件已存在时 mode 不生效，需要显式设置
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        Ok(file)
+    }
+    #[cfg(not(unix))]
+    {
+        options.open(path)
+    }
+}
+
+// 拒绝组或其他用户可以访问的密钥文件
+#[cfg(unix)]
+fn check_permissions(path: &Path) -> Result<(), NetworkConfigError> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = fs::metadata(path)?.permissions().mode() & 0o777;
+    if mode & 0o077 != 0 {
+        return Err(NetworkConfigError::IdentityPermissions(mode));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_permissions(_path: &Path) -> Result<(), NetworkConfigError> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_or_generate_is_stable() {
+        let path = Path::new("test_identity_plain.key");
+        fs::remove_file(path).unwrap_or(());
+
+        let generated = load_or_generate(path, None).unwrap();
+        let loaded = load_or_generate(path, None).unwrap();
+        assert_eq!(generated.public(), loaded.public());
+
+        // 明文文件就是 protobuf 编码
+        assert_eq!(fs::read(path).unwrap(), generated.to_protobuf_encoding().unwrap());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_encrypted_keyfile() {
+        let path = Path::new("test_identity_encrypted.key");
+        let keypair = Keypair::generate_ed25519();
+        save_keypair(path, &keypair, Some("correct horse")).unwrap();
+
+        let data = fs::read(path).unwrap();
+        assert!(data.starts_with(MAGIC));
+        assert_eq!(load_keypair(path, Some("correct horse")).unwrap().public(), keypair.public());
+        assert!(matches!(load_keypair(path, Some("wrong")), Err(NetworkConfigError::IdentityPassphrase)));
+        assert!(matches!(load_keypair(path, None), Err(NetworkConfigError::IdentityPassphrase)));
+
+        // 截断的文件无法解密
+        fs::write(path, &data[..HEADER_LEN - 1]).unwrap();
+        assert!(matches!(load_keypair(path, Some("correct horse")), Err(NetworkConfigError::IdentityDecode(_))));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rejects_loose_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let path = Path::new("test_identity_loose.key");
+        save_keypair(path, &Keypair::generate_ed25519(), None).unwrap();
+        fs::set_permissions(path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        assert!(matches!(load_keypair(path, None), Err(NetworkConfigError::IdentityPermissions(0o644))));
+
+        fs::remove_file(path).unwrap();
+    }
+}
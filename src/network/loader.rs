@@ -0,0 +1,434 @@
+use crate::network::config::{ConfigIssue, NetworkConfig, NetworkConfigError, DEFAULT_IDENTITY_FILE, FIELDS};
+use crate::network::keyfile;
+use crate::network::migrate::{self, CONFIG_VERSION, VERSION_KEY};
+use libp2p::identity::Keypair;
+use libp2p::PeerId;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// 环境变量覆盖项的前缀，例如 `FAIC_NET__MAX_CONNECTIONS`
+pub const ENV_PREFIX: &str = "FAIC_NET__";
+/// 指定配置文件路径的环境变量
+pub const CONFIG_PATH_ENV: &str = "FAIC_CONFIG";
+/// 默认的配置文件路径
+pub const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+/// 配置值的来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// 内置默认值
+    Default,
+    /// 配置文件
+    File(PathBuf),
+    /// 环境变量，记录变量名
+    Env(String),
+    /// 命令行参数，记录参数名
+    Cli(String),
+    /// 由身份密钥文件派生（只用于 local_peer_id）
+    Identity(PathBuf),
+}
+
+// 为 ConfigSource 实现 Display trait，用于打印生效配置
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "default"),
+            ConfigSource::File(path) => write!(f, "file {}", path.display()),
+            ConfigSource::Env(name) => write!(f, "env {}", name),
+            ConfigSource::Cli(flag) => write!(f, "cli {}", flag),
+            ConfigSource::Identity(path) => write!(f, "identity file {}", path.display()),
+        }
+    }
+}
+
+/// 确定配置文件路径：`--config` 优先，其次是 `FAIC_CONFIG`，最后是默认路径
+///
+/// 返回的布尔值表示文件是否必须存在：显式指定的文件不存在时报错，默认文件不存在时跳过。
+pub fn config_path(cli: Option<PathBuf>, env: Option<String>) -> (PathBuf, bool) {
+    match cli.or_else(|| env.map(PathBuf::from)) {
+        Some(path) => (path, true),
+        None => (PathBuf::from(DEFAULT_CONFIG_PATH), false),
+    }
+}
+
+/// 分层加载网络配置：内置默认值 → 配置文件 → 环境变量 → 命令行参数
+///
+/// 后面的层按字段覆盖前面的层。没有任何一层给出 `local_peer_id` 时，
+/// 它由身份密钥文件派生。
+#[derive(Debug, Clone, Default)]
+pub struct ConfigLoader {
+    // 配置文件路径，以及文件是否必须存在
+    file: Option<(PathBuf, bool)>,
+    // FAIC_NET__ 开头的环境变量
+    env: BTreeMap<String, String>,
+    // 命令行参数覆盖项：(字段, 值, 参数名)
+    cli: Vec<(String, toml::Value, String)>,
+    // 为 true 时不升级配置文件，也不生成身份密钥文件
+    read_only: bool,
+}
+
+impl ConfigLoader {
+    /// 创建只包含默认值的加载器
+    pub fn new() -> Self {
+        ConfigLoader::default()
+    }
+
+    /// 设置配置文件，`required` 为 false 时文件不存在就跳过
+    pub fn file(mut self, path: impl Into<PathBuf>, required: bool) -> Self {
+        self.file = Some((path.into(), required));
+        self
+    }
+
+    /// 添加环境变量，只使用以 `FAIC_NET__` 开头的变量
+    pub fn env<I>(mut self, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        self.env.extend(vars.into_iter().filter(|(name, _)| name.starts_with(ENV_PREFIX)));
+        self
+    }
+
+    /// 只读加载：旧版本的配置文件只在内存中升级，身份密钥文件不存在时报错而不是生成
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// 用命令行参数 `flag` 覆盖字段 `field`
+    pub fn set(mut self, field: &str, value: impl Into<toml::Value>, flag: &str) -> Self {
+        self.cli.push((field.to_string(), value.into(), flag.to_string()));
+        self
+    }
+
+    /// 合并所有层，读取身份密钥文件，然后校验最终配置
+    ///
+    /// # Arguments
+    ///
+    /// * `passphrase` - 身份密钥文件的口令
+    pub fn load(&self, passphrase: Option<&str>) -> Result<EffectiveConfig, NetworkConfigError> {
+        let mut table = defaults()?;
+        let mut sources: BTreeMap<String, ConfigSource> =
+            table.keys().map(|field| (field.clone(), ConfigSource::Default)).collect();
+        let mut issues = Vec::new();
+        let mut warnings = Vec::new();
+
+        if let Some((path, required)) = &self.file {
+            // 旧版本的文件会被就地升级（只读时只在内存中升级），未知键只产生警告
+            let loaded = if self.read_only { migrate::read_file(path) } else { migrate::load_file(path) };
+            match loaded {
+                Ok(file) => {
+                    for (field, value) in file.table {
+                        sources.insert(field.clone(), ConfigSource::File(path.clone()));
+                        table.insert(field, value);
+                    }
+                    warnings.extend(file.warnings);
+                }
+                Err(NetworkConfigError::IoError(e)) if e.kind() == io::ErrorKind::NotFound && !required => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        for (name, raw) in &self.env {
+            let field = name[ENV_PREFIX.len()..].to_ascii_lowercase();
+            if !FIELDS.contains(&field.as_str()) {
+                issues.push(ConfigIssue::new(name.clone(), "unknown network config field"));
+                continue;
+            }
+            table.insert(field.clone(), parse_env_value(raw));
+            sources.insert(field, ConfigSource::Env(name.clone()));
+        }
+
+        for (field, value, flag) in &self.cli {
+            if !FIELDS.contains(&field.as_str()) {
+                issues.push(ConfigIssue::new(flag.clone(), "unknown network config field"));
+                continue;
+            }
+            table.insert(field.clone(), value.clone());
+            sources.insert(field.clone(), ConfigSource::Cli(flag.clone()));
+        }
+
+        // 先确定身份密钥文件，才能派生 local_peer_id
+        let identity_file = match table.get("identity_file") {
+            Some(toml::Value::String(path)) => PathBuf::from(path),
+            _ => {
+                issues.push(ConfigIssue::new("identity_file", "must be a path string"));
+                PathBuf::from(DEFAULT_IDENTITY_FILE)
+            }
+        };
+        if !issues.is_empty() {
+            return Err(NetworkConfigError::Invalid(issues));
+        }
+
+        // 显式给出 local_peer_id 时密钥文件必须已经存在，新生成的密钥必然与它不一致
+        let keypair = if self.read_only || table.contains_key("local_peer_id") {
+            keyfile::load_keypair(&identity_file, passphrase)?
+        } else {
+            keyfile::load_or_generate(&identity_file, passphrase)?
+        };
+        if !table.contains_key("local_peer_id") {
+            table.insert("local_peer_id".to_string(), keypair.public().to_peer_id().to_string().into());
+            sources.insert("local_peer_id".to_string(), ConfigSource::Identity(identity_file));
+        }
+
+        let config: NetworkConfig = toml::Value::Table(table).try_into()?;
+        config.validate()?;
+        config.check_identity(&keypair)?;
+        Ok(EffectiveConfig { config, keypair, sources, warnings })
+    }
+}
+
+/// 分层加载得到的最终配置
+#[derive(Debug)]
+pub struct EffectiveConfig {
+    /// 合并并校验后的网络配置
+    pub config: NetworkConfig,
+    /// 节点的身份密钥对
+    pub keypair: Keypair,
+    /// 每个字段的来源
+    pub sources: BTreeMap<String, ConfigSource>,
+    /// 加载配置文件时的警告
+    pub warnings: Vec<ConfigIssue>,
+}
+
+impl EffectiveConfig {
+    /// 字段的来源
+    pub fn source(&self, field: &str) -> Option<&ConfigSource> {
+        self.sources.get(field)
+    }
+
+    /// 以 TOML 格式输出最终配置，每个字段后面用注释标出来源
+    pub fn render(&self) -> Result<String, NetworkConfigError> {
+        let value = toml::Value::try_from(&self.config)?;
+        let table = value.as_table().expect("NetworkConfig serializes to a table");
+        let mut out = format!("{} = {}\n", VERSION_KEY, CONFIG_VERSION);
+        for field in FIELDS {
+            let Some(value) = table.get(*field) else { continue };
+            let mut single = toml::Table::new();
+            single.insert(field.to_string(), value.clone());
+            let line = toml::to_string(&single)?;
+            let source = self.sources.get(*field).unwrap_or(&ConfigSource::Default);
+            out.push_str(&format!("{}  # {}\n", line.trim_end(), source));
+        }
+        Ok(out)
+    }
+}
+
+/// 把内置默认值写入配置文件，不写 local_peer_id：加载时由身份密钥文件派生
+pub fn save_defaults(path: &Path) -> Result<(), NetworkConfigError> {
+    fs::write(path, migrate::to_versioned_string(&defaults()?)?)?;
+    Ok(())
+}
+
+// 内置默认值，不包括 local_peer_id
+fn defaults() -> Result<toml::Table, NetworkConfigError> {
+    let config = NetworkConfig::new(PeerId::random());
+    let mut table = match toml::Value::try_from(&config)? {
+        toml::Value::Table(table) => table,
+        _ => unreachable!("NetworkConfig serializes to a table"),
+    };
+    table.remove("local_peer_id");
+    Ok(table)
+}
+
+// 环境变量的值按 TOML 值解析（数字、数组等），解析失败时当作字符串
+fn parse_env_value(raw: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("value = {}", raw))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::Multiaddr;
+    use std::time::Duration;
+
+    fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_fields_match_config() {
+        let mut fields: Vec<String> = defaults().unwrap().keys().cloned().collect();
+        fields.push("local_peer_id".to_string());
+        fields.sort();
+        let mut expected: Vec<&str> = FIELDS.to_vec();
+        expected.sort();
+        assert_eq!(fields, expected);
+    }
+
+    #[test]
+    fn test_layer_precedence() {
+        let config_file = "test_layered_config.toml";
+        let identity_file = "test_layered_identity.key";
+        fs::write(
+            config_file,
+            "config_version = 1\nmax_connections = 50\nconnection_timeout = 20\nlisten_addresses = [\"/ip4/127.0.0.1/tcp/4001\"]\n",
+        )
+        .unwrap();
+
+        let loaded = ConfigLoader::new()
+            .file(config_file, true)
+            .env(env(&[
+                ("FAIC_NET__MAX_CONNECTIONS", "70"),
+                ("FAIC_NET__HEARTBEAT_INTERVAL", "5"),
+                ("PATH", "/usr/bin"),
+            ]))
+            .set("max_connections", 90, "--max-connections")
+            .set("identity_file", identity_file, "--identity-file")
+            .load(None)
+            .unwrap();
+
+        let config = &loaded.config;
+        assert_eq!(config.max_connections, 90);
+        assert_eq!(config.connection_timeout, Duration::from_secs(20));
+        assert_eq!(config.heartbeat_interval, Duration::from_secs(5));
+        assert_eq!(config.listen_addresses, vec!["/ip4/127.0.0.1/tcp/4001".parse::<Multiaddr>().unwrap()]);
+        assert_eq!(config.local_peer_id, loaded.keypair.public().to_peer_id());
+
+        let file = ConfigSource::File(PathBuf::from(config_file));
+        assert_eq!(loaded.source("max_connections"), Some(&ConfigSource::Cli("--max-connections".to_string())));
+        assert_eq!(loaded.source("connection_timeout"), Some(&file));
+        assert_eq!(loaded.source("listen_addresses"), Some(&file));
+        assert_eq!(
+            loaded.source("heartbeat_interval"),
+            Some(&ConfigSource::Env("FAIC_NET__HEARTBEAT_INTERVAL".to_string()))
+        );
+        assert_eq!(loaded.source("bootstrap_nodes"), Some(&ConfigSource::Default));
+        assert_eq!(loaded.source("local_peer_id"), Some(&ConfigSource::Identity(PathBuf::from(identity_file))));
+
+        // 输出的配置可以重新加载，并标出每个字段的来源
+        let rendered = loaded.render().unwrap();
+        assert!(loaded.warnings.is_empty());
+        assert!(rendered.starts_with("config_version = 1\n"));
+        assert!(rendered.contains("max_connections = 90  # cli --max-connections\n"));
+        assert!(rendered.contains("heartbeat_interval = 5  # env FAIC_NET__HEARTBEAT_INTERVAL\n"));
+        assert!(rendered.contains("bootstrap_nodes = []  # default\n"));
+        let reparsed: NetworkConfig = toml::from_str(&rendered).unwrap();
+        assert_eq!(reparsed.max_connections, 90);
+        assert_eq!(reparsed.local_peer_id, config.local_peer_id);
+
+        fs::remove_file(config_file).unwrap();
+        fs::remove_file(identity_file).unwrap();
+    }
+
+    #[test]
+    fn test_env_values() {
+        assert_eq!(parse_env_value("42"), toml::Value::Integer(42));
+        assert_eq!(parse_env_value("/ip4/1.2.3.4/tcp/1"), toml::Value::String("/ip4/1.2.3.4/tcp/1".to_string()));
+        assert_eq!(
+            parse_env_value("[\"/ip4/1.2.3.4/tcp/1\"]"),
+            toml::Value::Array(vec![toml::Value::String("/ip4/1.2.3.4/tcp/1".to_string())])
+        );
+
+        // 未知字段和类型错误都会报告
+        let result = ConfigLoader::new().env(env(&[("FAIC_NET__MAX_CONN", "1")])).load(None);
+        assert!(matches!(result, Err(NetworkConfigError::Invalid(ref issues)) if issues[0].field == "FAIC_NET__MAX_CONN"));
+        let result = ConfigLoader::new().env(env(&[("FAIC_NET__IDENTITY_FILE", "1")])).load(None);
+        assert!(matches!(result, Err(NetworkConfigError::Invalid(ref issues)) if issues[0].field == "identity_file"));
+    }
+
+    #[test]
+    fn test_config_file_resolution() {
+        assert_eq!(config_path(None, None), (PathBuf::from(DEFAULT_CONFIG_PATH), false));
+        assert_eq!(config_path(None, Some("env.toml".to_string())), (PathBuf::from("env.toml"), true));
+        assert_eq!(
+            config_path(Some(PathBuf::from("cli.toml")), Some("env.toml".to_string())),
+            (PathBuf::from("cli.toml"), true)
+        );
+
+        // 显式指定的文件必须存在，默认文件不存在时跳过
+        let missing = Path::new("test_layered_missing.toml");
+        let identity_file = "test_layered_missing_identity.key";
+        let result = ConfigLoader::new().file(missing, true).load(None);
+        assert!(matches!(result, Err(NetworkConfigError::IoError(ref e)) if e.kind() == io::ErrorKind::NotFound));
+        let loaded = ConfigLoader::new()
+            .file(missing, false)
+            .set("identity_file", identity_file, "--identity-file")
+            .load(None)
+            .unwrap();
+        assert_eq!(loaded.config.max_connections, 100);
+
+        fs::remove_file(identity_file).unwrap();
+    }
+
+    #[test]
+    fn test_read_only_load() {
+        let config_file = "test_layered_read_only.toml";
+        let identity_file = "test_layered_read_only_identity.key";
+        let legacy = format!("max_connections = 50\nidentity_file = \"{}\"\n", identity_file);
+        fs::write(config_file, &legacy).unwrap();
+        fs::remove_file(identity_file).unwrap_or(());
+
+        // 不生成密钥文件，也不升级配置文件
+        let result = ConfigLoader::new().file(config_file, true).read_only().load(None);
+        assert!(matches!(result, Err(NetworkConfigError::IdentityMissing(_))));
+        assert!(!Path::new(identity_file).exists());
+
+        keyfile::save_keypair(Path::new(identity_file), &Keypair::generate_ed25519(), None).unwrap();
+        let loaded = ConfigLoader::new().file(config_file, true).read_only().load(None).unwrap();
+        assert_eq!(loaded.config.max_connections, 50);
+        assert_eq!(loaded.warnings[0].field, VERSION_KEY);
+        assert_eq!(fs::read_to_string(config_file).unwrap(), legacy);
+        assert!(!migrate::backup_path(Path::new(config_file), 0).exists());
+
+        fs::remove_file(config_file).unwrap();
+        fs::remove_file(identity_file).unwrap();
+    }
+
+    #[test]
+    fn test_saved_defaults() {
+        let config_file = "test_layered_defaults.toml";
+        let identity_file = "test_layered_defaults_identity.key";
+        save_defaults(Path::new(config_file)).unwrap();
+
+        // 默认配置文件不含 local_peer_id，加载时由密钥文件派生
+        let text = fs::read_to_string(config_file).unwrap();
+        assert!(text.starts_with("config_version = 1\n"));
+        assert!(!text.contains("local_peer_id"));
+        let loaded = ConfigLoader::new()
+            .file(config_file, true)
+            .set("identity_file", identity_file, "--identity-file")
+            .load(None)
+            .unwrap();
+        assert_eq!(loaded.config.max_connections, 100);
+        assert_eq!(loaded.source("local_peer_id"), Some(&ConfigSource::Identity(PathBuf::from(identity_file))));
+        assert!(loaded.warnings.is_empty());
+
+        fs::remove_file(config_file).unwrap();
+        fs::remove_file(identity_file).unwrap();
+    }
+
+    #[test]
+    fn test_final_config_is_validated() {
+        let identity_file = "test_layered_invalid_identity.key";
+        fs::remove_file(identity_file).unwrap_or(());
+
+        // 显式给出 local_peer_id 时不会生成密钥文件
+        let result = ConfigLoader::new()
+            .env(env(&[("FAIC_NET__LOCAL_PEER_ID", &PeerId::random().to_string())]))
+            .set("identity_file", identity_file, "--identity-file")
+            .load(None);
+        assert!(matches!(result, Err(NetworkConfigError::IdentityMissing(_))));
+        assert!(!Path::new(identity_file).exists());
+
+        let result = ConfigLoader::new()
+            .set("identity_file", identity_file, "--identity-file")
+            .set("max_connections", 0, "--max-connections")
+            .load(None);
+        assert!(matches!(result, Err(NetworkConfigError::Invalid(ref issues)) if issues[0].field == "max_connections"));
+
+        // 显式给出的 local_peer_id 必须与身份密钥一致
+        let result = ConfigLoader::new()
+            .env(env(&[("FAIC_NET__LOCAL_PEER_ID", &PeerId::random().to_string())]))
+            .set("identity_file", identity_file, "--identity-file")
+            .load(None);
+        assert!(matches!(result, Err(NetworkConfigError::IdentityMismatch { .. })));
+
+        fs::remove_file(identity_file).unwrap();
+    }
+}
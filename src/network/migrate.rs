@@ -0,0 +1,259 @@
+use crate::network::config::{ConfigIssue, NetworkConfigError, DEFAULT_IDENTITY_FILE, FIELDS};
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// 当前的配置文件版本
+pub const CONFIG_VERSION: u32 = 1;
+/// 配置文件中记录版本的键，没有该键的文件视为版本 0
+pub const VERSION_KEY: &str = "config_version";
+
+// 把配置从版本 i 升级到 i + 1 的迁移函数，下标即起始版本
+type Migration = fn(&mut toml::Table);
+const MIGRATIONS: [Migration; CONFIG_VERSION as usize] = [v0_to_v1];
+
+// 版本 1 引入身份密钥文件，旧文件显式写入默认路径
+fn v0_to_v1(table: &mut toml::Table) {
+    table
+        .entry("identity_file")
+        .or_insert_with(|| toml::Value::String(DEFAULT_IDENTITY_FILE.to_string()));
+}
+
+/// 读取并迁移后的配置文件
+#[derive(Debug, Clone)]
+pub struct LoadedFile {
+    /// 已升级到当前版本、去掉版本键和未知键的配置
+    pub table: toml::Table,
+    /// 加载过程中的警告：被忽略的未知键和执行过的迁移
+    pub warnings: Vec<ConfigIssue>,
+    /// 文件原来的版本，没有迁移时为 None
+    pub migrated_from: Option<u32>,
+}
+
+/// 读取配置表中的版本号
+pub fn file_version(table: &toml::Table) -> Result<u32, NetworkConfigError> {
+    match table.get(VERSION_KEY) {
+        None => Ok(0),
+        Some(toml::Value::Integer(version)) => u32::try_from(*version).map_err(|_| invalid_version()),
+        Some(_) => Err(invalid_version()),
+    }
+}
+
+fn invalid_version() -> NetworkConfigError {
+    NetworkConfigError::Invalid(vec![ConfigIssue::new(VERSION_KEY, "must be a non-negative integer")])
+}
+
+/// 依次执行迁移函数，把配置表升级到当前版本
+///
+/// # Returns
+///
+/// 升级前的版本，已经是当前版本时返回 None
+pub fn migrate(table: &mut toml::Table) -> Result<Option<u32>, NetworkConfigError> {
+    let version = file_version(table)?;
+    if version > CONFIG_VERSION {
+        return Err(NetworkConfigError::UnsupportedVersion { found: version, supported: CONFIG_VERSION });
+    }
+    if version == CONFIG_VERSION {
+        return Ok(None);
+    }
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(table);
+    }
+    table.insert(VERSION_KEY.to_string(), toml::Value::Integer(CONFIG_VERSION.into()));
+    Ok(Some(version))
+}
+
+/// 序列化为带版本号的 TOML，版本键写在第一行
+pub fn to_versioned_string<T: Serialize>(value: &T) -> Result<String, NetworkConfigError> {
+    let mut table = match toml::Value::try_from(value)? {
+        toml::Value::Table(table) => table,
+        _ => unreachable!("config serializes to a table"),
+    };
+    table.remove(VERSION_KEY);
+    Ok(format!("{} = {}\n{}", VERSION_KEY, CONFIG_VERSION, toml::to_string(&table)?))
+}
+
+/// 备份文件路径：在原文件名后加原版本号和 `.bak`，例如 `config.toml.v0.bak`
+pub fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".v{}.bak", version));
+    PathBuf::from(backup)
+}
+
+/// 读取配置文件，必要时就地升级
+///
+/// 旧版本的文件先按原版本号备份（见 `backup_path`），再写回升级后的内容；备份文件已存在时
+/// 拒绝迁移，不覆盖旧备份。未知键保留在文件中，但在返回的配置表中被去掉，并记录为警告。
+pub fn load_file(path: &Path) -> Result<LoadedFile, NetworkConfigError> {
+    read(path, true)
+}
+
+/// 读取配置文件，旧版本只在内存中升级，不修改文件也不创建备份
+pub fn read_file(path: &Path) -> Result<LoadedFile, NetworkConfigError> {
+    read(path, false)
+}
+
+// write_back 为 true 时把升级后的内容写回文件
+fn read(path: &Path, write_back: bool) -> Result<LoadedFile, NetworkConfigError> {
+    let text = fs::read_to_string(path)?;
+    let mut table: toml::Table = toml::from_str(&text)?;
+    let mut warnings = Vec::new();
+
+    let migrated_from = migrate(&mut table)?;
+    if let Some(version) = migrated_from {
+        let message = if write_back {
+            let backup = backup_path(path, version);
+            // 只创建新文件，已有的备份可能是用户唯一的旧配置
+            let mut file = match fs::OpenOptions::new().write(true).create_new(true).open(&backup) {
+                Ok(file) => file,
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    return Err(NetworkConfigError::BackupExists(backup));
+                }
+                Err(e) => return Err(e.into()),
+            };
+            file.write_all(text.as_bytes())?;
+            file.sync_all()?;
+            // 先写临时文件再重命名，避免中途失败留下半个配置文件
+            let mut tmp = path.as_os_str().to_owned();
+            tmp.push(".tmp");
+            fs::write(&tmp, to_versioned_string(&table)?)?;
+            fs::rename(&tmp, path)?;
+            format!(
+                "migrated {} from version {} to {}, original saved as {}",
+                path.display(),
+                version,
+                CONFIG_VERSION,
+                backup.display()
+            )
+        } else {
+            format!("{} is version {}, upgraded to {} in memory only", path.display(), version, CONFIG_VERSION)
+        };
+        warnings.push(ConfigIssue::new(VERSION_KEY, message));
+    }
+    table.remove(VERSION_KEY);
+
+    // 未知键不再导致加载失败，只给出警告
+    let unknown: Vec<String> = table.keys().filter(|key| !FIELDS.contains(&key.as_str())).cloned().collect();
+    for key in unknown {
+        table.remove(&key);
+        warnings.push(ConfigIssue::new(key, "unknown key, ignored"));
+    }
+
+    Ok(LoadedFile { table, warnings, migrated_from })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::config::NetworkConfig;
+
+    const LEGACY: &str = r#"local_peer_id = "12D3KooWLyEavPji9n9qaGcoe5j4qoJYkDwHLN5MU7o26d54fVMD"
+listen_addresses = ["/ip4/127.0.0.1/tcp/8080"]
+bootstrap_nodes = []
+max_connections = 50
+connection_timeout = 20
+heartbeat_interval = 30
+"#;
+
+    #[test]
+    fn test_migrates_legacy_file_in_place() {
+        let path = Path::new("test_migrate_legacy.toml");
+        fs::write(path, LEGACY).unwrap();
+
+        let loaded = load_file(path).unwrap();
+        assert_eq!(loaded.migrated_from, Some(0));
+        assert_eq!(loaded.warnings.len(), 1);
+        assert_eq!(loaded.warnings[0].field, VERSION_KEY);
+        assert_eq!(loaded.table.get("identity_file").and_then(|v| v.as_str()), Some(DEFAULT_IDENTITY_FILE));
+        assert!(!loaded.table.contains_key(VERSION_KEY));
+
+        // 原文件保存为 .bak，新文件带有当前版本号
+        assert_eq!(fs::read_to_string(backup_path(path, 0)).unwrap(), LEGACY);
+        let upgraded = fs::read_to_string(path).unwrap();
+        assert!(upgraded.starts_with("config_version = 1\n"));
+
+        // 再次加载不会重复迁移
+        let reloaded = load_file(path).unwrap();
+        assert_eq!(reloaded.migrated_from, None);
+        assert!(reloaded.warnings.is_empty());
+        assert_eq!(reloaded.table, loaded.table);
+        assert_eq!(NetworkConfig::load_from_file(path.to_str().unwrap()).unwrap().max_connections, 50);
+
+        fs::remove_file(path).unwrap();
+        fs::remove_file(backup_path(path, 0)).unwrap();
+    }
+
+    #[test]
+    fn test_existing_backup_is_not_overwritten() {
+        let path = Path::new("test_migrate_existing_backup.toml");
+        let backup = backup_path(path, 0);
+        fs::write(path, LEGACY).unwrap();
+        fs::write(&backup, "previous backup").unwrap();
+
+        // 拒绝迁移，原文件和旧备份都保持不变
+        let result = load_file(path);
+        assert!(matches!(result, Err(NetworkConfigError::BackupExists(ref p)) if *p == backup));
+        assert_eq!(fs::read_to_string(path).unwrap(), LEGACY);
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "previous backup");
+
+        // 移走旧备份后可以正常迁移
+        fs::remove_file(&backup).unwrap();
+        assert_eq!(load_file(path).unwrap().migrated_from, Some(0));
+        assert_eq!(fs::read_to_string(&backup).unwrap(), LEGACY);
+
+        fs::remove_file(path).unwrap();
+        fs::remove_file(&backup).unwrap();
+    }
+
+    #[test]
+    fn test_read_file_does_not_write() {
+        let path = Path::new("test_migrate_read_only.toml");
+        fs::write(path, LEGACY).unwrap();
+
+        let loaded = read_file(path).unwrap();
+        assert_eq!(loaded.migrated_from, Some(0));
+        assert_eq!(loaded.warnings[0].field, VERSION_KEY);
+        assert_eq!(loaded.table.get("identity_file").and_then(|v| v.as_str()), Some(DEFAULT_IDENTITY_FILE));
+
+        // 文件保持原样，也没有备份
+        assert_eq!(fs::read_to_string(path).unwrap(), LEGACY);
+        assert!(!backup_path(path, 0).exists());
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_unknown_keys_are_warnings() {
+        let path = Path::new("test_migrate_unknown.toml");
+        let text = format!("config_version = 1\n{}identity_file = \"x.key\"\ngossip = true\n", LEGACY);
+        fs::write(path, &text).unwrap();
+
+        let loaded = load_file(path).unwrap();
+        assert_eq!(loaded.migrated_from, None);
+        assert_eq!(loaded.warnings, vec![ConfigIssue::new("gossip", "unknown key, ignored")]);
+        assert!(!loaded.table.contains_key("gossip"));
+        // 当前版本的文件不会被改写，也没有备份
+        assert_eq!(fs::read_to_string(path).unwrap(), text);
+        assert!(!backup_path(path, 0).exists());
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_unsupported_versions() {
+        let path = Path::new("test_migrate_future.toml");
+        let text = format!("config_version = {}\n{}", CONFIG_VERSION + 1, LEGACY);
+        fs::write(path, &text).unwrap();
+
+        let result = load_file(path);
+        assert!(matches!(result, Err(NetworkConfigError::UnsupportedVersion { found: 2, supported: 1 })));
+        assert_eq!(fs::read_to_string(path).unwrap(), text);
+
+        fs::write(path, "config_version = \"one\"\n").unwrap();
+        let result = load_file(path);
+        assert!(matches!(result, Err(NetworkConfigError::Invalid(ref issues)) if issues[0].field == VERSION_KEY));
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -2,3 +2,7 @@ pub mod config;
 pub mod types;
 pub mod error;
 pub mod protocol;
+pub mod node;
+pub mod keyfile;
+pub mod loader;
+pub mod migrate;
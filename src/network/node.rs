@@ -0,0 +1,492 @@
+use crate::network::config::NetworkConfig;
+use crate::network::error::Error;
+use crate::network::protocol::{create_faic_network_behaviour, handle_request, FaicCodec};
+use crate::network::types::{NodeInfo, Request, Response};
+use crate::state::Ledger;
+use futures::StreamExt;
+use libp2p::{
+    connection_limits::{self, ConnectionLimits},
+    identity,
+    request_response::{self, OutboundRequestId, ResponseChannel},
+    swarm::{dial_opts::DialOpts, NetworkBehaviour, SwarmEvent},
+    noise, tcp, yamux, Multiaddr, PeerId, Swarm, SwarmBuilder,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot, RwLock};
+
+/// 命令和事件通道的容量
+const CHANNEL_CAPACITY: usize = 64;
+
+/// 节点的网络行为：FAIC 请求-响应协议加上连接数限制
+#[derive(NetworkBehaviour)]
+pub struct FaicBehaviour {
+    /// FAIC 请求-响应协议
+    pub request_response: request_response::Behaviour<FaicCodec>,
+    /// 限制已建立的连接数，对应 `max_connections`
+    pub limits: connection_limits::Behaviour,
+}
+
+/// 节点向外报告的事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeEvent {
+    /// 开始在某个地址上监听
+    Listening(Multiaddr),
+    /// 与节点建立了连接
+    ConnectionEstablished(PeerId),
+    /// 与节点的最后一个连接已关闭
+    ConnectionClosed(PeerId),
+    /// 收到并已应答一个请求
+    InboundRequest {
+        /// 发起请求的节点
+        peer: PeerId,
+        /// 请求内容
+        request: Request,
+    },
+    /// 发往某个节点的请求失败
+    OutboundFailure {
+        /// 目标节点
+        peer: PeerId,
+        /// 失败原因
+        error: String,
+    },
+    /// 拨号失败
+    DialFailure {
+        /// 目标节点，未知时为 None
+        peer: Option<PeerId>,
+        /// 失败原因
+        error: String,
+    },
+}
+
+// 句柄发往事件循环的命令
+enum Command {
+    SendRequest {
+        peer: PeerId,
+        request: Request,
+        reply: oneshot::Sender<Result<Response, Error>>,
+    },
+    Dial {
+        peer: PeerId,
+        address: Multiaddr,
+        reply: oneshot::Sender<Result<(), Error>>,
+    },
+    Shutdown,
+}
+
+impl std::fmt::Debug for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Command::SendRequest { peer, request, .. } => {
+                f.debug_struct("SendRequest").field("peer", peer).field("request", request).finish()
+            }
+            Command::Dial { peer, address, .. } => {
+                f.debug_struct("Dial").field("peer", peer).field("address", address).finish()
+            }
+            Command::Shutdown => f.write_str("Shutdown"),
+        }
+    }
+}
+
+/// 正在运行的节点的句柄，可以在多个任务之间克隆
+#[derive(Debug, Clone)]
+pub struct NodeHandle {
+    local_peer_id: PeerId,
+    commands: mpsc::Sender<Command>,
+}
+
+impl NodeHandle {
+    /// 本地节点的 PeerId
+    pub fn local_peer_id(&self) -> PeerId {
+        self.local_peer_id
+    }
+
+    /// 向节点发送请求并等待响应
+    ///
+    /// 目标节点必须已经连接，或者之前通过 `dial` / 引导节点拨号过。
+    pub async fn send_request(&self, peer: PeerId, request: Request) -> Result<Response, Error> {
+        let (reply, response) = oneshot::channel();
+        self.command(Command::SendRequest { peer, request, reply }).await?;
+        response.await.map_err(|_| stopped())?
+    }
+
+    /// 拨号连接到指定地址上的节点
+    pub async fn dial(&self, peer: PeerId, address: Multiaddr) -> Result<(), Error> {
+        let (reply, result) = oneshot::channel();
+        self.command(Command::Dial { peer, address, reply }).await?;
+        result.await.map_err(|_| stopped())?
+    }
+
+    /// 停止节点，尚未完成的请求会以错误返回
+    pub async fn shutdown(&self) -> Result<(), Error> {
+        self.command(Command::Shutdown).await
+    }
+
+    async fn command(&self, command: Command) -> Result<(), Error> {
+        self.commands.send(command).await.map_err(|_| stopped())
+    }
+}
+
+fn stopped() -> Error {
+    Error::Network("Node has stopped".to_string())
+}
+
+/// 按照 `NetworkConfig` 构建 Swarm（TCP+Noise+Yamux 以及 QUIC）
+///
+/// 配置必须通过 `validate`，`keypair` 必须与配置中的 `local_peer_id` 一致。
+/// 连接超时用作请求超时，心跳间隔用作空闲连接的保持时间。
+pub fn build_swarm(config: &NetworkConfig, keypair: identity::Keypair) -> Result<Swarm<FaicBehaviour>, Error> {
+    config.validate()?;
+    config.check_identity(&keypair)?;
+
+    let request_timeout = config.connection_timeout;
+    let limits = ConnectionLimits::default().with_max_established(Some(config.max_connections));
+    let swarm = SwarmBuilder::with_existing_identity(keypair)
+        .with_tokio()
+        .with_tcp(tcp::Config::default(), noise::Config::new, yamux::Config::default)
+        .map_err(|e| Error::Network(e.to_string()))?
+        .with_quic()
+        .with_behaviour(|_| FaicBehaviour {
+            request_response: create_faic_network_behaviour(request_timeout),
+            limits: connection_limits::Behaviour::new(limits),
+        })
+        .map_err(|e| Error::Network(e.to_string()))?
+        .with_swarm_config(|c| c.with_idle_connection_timeout(config.heartbeat_interval))
+        .build();
+    Ok(swarm)
+}
+
+/// 启动节点：构建 Swarm，监听所有 `listen_addresses`，拨号所有 `bootstrap_nodes`，
+/// 然后在后台任务中运行事件循环
+///
+/// 收到的请求在单独的任务中用 `ledger` 应答，等待账本锁不会阻塞事件循环。返回的事件接收端需要
+/// 及时读取，否则通道写满后事件循环会等待；丢弃接收端则不再报告事件。
+pub fn spawn<L>(
+    config: &NetworkConfig,
+    keypair: identity::Keypair,
+    ledger: Arc<RwLock<L>>,
+) -> Result<(NodeHandle, mpsc::Receiver<NodeEvent>), Error>
+where
+    L: Ledger + Send + Sync + 'static,
+{
+    let mut swarm = build_swarm(config, keypair)?;
+    for address in &config.listen_addresses {
+        swarm.listen_on(address.clone())?;
+    }
+    for (peer, address) in &config.bootstrap_nodes {
+        swarm.add_peer_address(*peer, address.clone());
+        swarm
+            .dial(DialOpts::peer_id(*peer).addresses(vec![address.clone()]).build())
+            .map_err(|e| Error::Network(e.to_string()))?;
+    }
+
+    let (command_tx, command_rx) = mpsc::channel(CHANNEL_CAPACITY);
+    let (event_tx, event_rx) = mpsc::channel(CHANNEL_CAPACITY);
+    let (answer_tx, answer_rx) = mpsc::channel(CHANNEL_CAPACITY);
+    let handle = NodeHandle { local_peer_id: config.local_peer_id, commands: command_tx };
+    let event_loop = EventLoop {
+        swarm,
+        ledger,
+        commands: command_rx,
+        events: event_tx,
+        answers: (answer_tx, answer_rx),
+        pending: HashMap::new(),
+    };
+    tokio::spawn(event_loop.run());
+    Ok((handle, event_rx))
+}
+
+// 应答任务交回事件循环的结果：应答通道、响应，以及用于报告事件的请求方和请求
+struct Answer {
+    channel: ResponseChannel<Response>,
+    response: Response,
+    peer: PeerId,
+    request: Request,
+}
+
+// 驱动 Swarm 并处理句柄命令的事件循环
+struct EventLoop<L> {
+    swarm: Swarm<FaicBehaviour>,
+    ledger: Arc<RwLock<L>>,
+    commands: mpsc::Receiver<Command>,
+    events: mpsc::Sender<NodeEvent>,
+    // 事件循环自己持有发送端，因此接收端不会关闭
+    answers: (mpsc::Sender<Answer>, mpsc::Receiver<Answer>),
+    pending: HashMap<OutboundRequestId, oneshot::Sender<Result<Response, Error>>>,
+}
+
+impl<L: Ledger + Send + Sync + 'static> EventLoop<L> {
+    async fn run(mut self) {
+        loop {
+            tokio::select! {
+                event = self.swarm.select_next_some() => self.handle_swarm_event(event).await,
+                command = self.commands.recv() => match command {
+                    Some(Command::Shutdown) | None => break,
+                    Some(command) => self.handle_command(command),
+                },
+                Some(answer) = self.answers.1.recv() => self.send_answer(answer).await,
+            }
+        }
+        // 通知所有等待中的请求
+        for (_, reply) in self.pending.drain() {
+            let _ = reply.send(Err(stopped()));
+        }
+    }
+
+    fn handle_command(&mut self, command: Command) {
+        match command {
+            Command::SendRequest { peer, request, reply } => {
+                let request_id = self.swarm.behaviour_mut().request_response.send_request(&peer, request);
+                self.pending.insert(request_id, reply);
+            }
+            Command::Dial { peer, address, reply } => {
+                self.swarm.add_peer_address(peer, address.clone());
+                let result = self
+                    .swarm
+                    .dial(DialOpts::peer_id(peer).addresses(vec![address]).build())
+                    .map_err(|e| Error::Network(e.to_string()));
+                let _ = reply.send(result);
+            }
+            Command::Shutdown => {}
+        }
+    }
+
+    async fn handle_swarm_event(&mut self, event: SwarmEvent<FaicBehaviourEvent>) {
+        match event {
+            SwarmEvent::NewListenAddr { address, .. } => emit(&self.events, NodeEvent::Listening(address)).await,
+            // 只报告与每个节点的第一个连接和最后一个连接
+            SwarmEvent::ConnectionEstablished { peer_id, num_established, .. } if num_established.get() == 1 => {
+                emit(&self.events, NodeEvent::ConnectionEstablished(peer_id)).await;
+            }
+            SwarmEvent::ConnectionClosed { peer_id, num_established: 0, .. } => {
+                emit(&self.events, NodeEvent::ConnectionClosed(peer_id)).await;
+            }
+            SwarmEvent::OutgoingConnectionError { peer_id, error, .. } => {
+                emit(&self.events, NodeEvent::DialFailure { peer: peer_id, error: error.to_string() }).await;
+            }
+            SwarmEvent::Behaviour(FaicBehaviourEvent::RequestResponse(event)) => {
+                self.handle_request_response(event).await;
+            }
+            SwarmEvent::Behaviour(FaicBehaviourEvent::Limits(never)) => match never {},
+            _ => {}
+        }
+    }
+
+    async fn handle_request_response(&mut self, event: request_response::Event<Request, Response>) {
+        match event {
+            request_response::Event::Message { peer, message, .. } => match message {
+                request_response::Message::Request { request, channel, .. } => {
+                    let node_info = NodeInfo {
+                        peer_id: *self.swarm.local_peer_id(),
+                        addresses: self.swarm.listeners().cloned().collect(),
+                        is_online: true,
+                    };
+                    let ledger = Arc::clone(&self.ledger);
+                    let answers = self.answers.0.clone();
+                    tokio::spawn(async move {
+                        // 账本出错时返回错误响应，而不是让对方等到超时
+                        let response = {
+                            let ledger = ledger.read().await;
+                            handle_request(&*ledger, node_info, request.clone()).await
+                        }
+                        .unwrap_or_else(|e| Response::Error { message: e.to_string() });
+                        // 事件循环已停止时无需应答
+                        let _ = answers.send(Answer { channel, response, peer, request }).await;
+                    });
+                }
+                request_response::Message::Response { request_id, response } => {
+                    if let Some(reply) = self.pending.remove(&request_id) {
+                        let _ = reply.send(Ok(response));
+                    }
+                }
+            },
+            request_response::Event::OutboundFailure { peer, request_id, error, .. } => {
+                let error = error.to_string();
+                if let Some(reply) = self.pending.remove(&request_id) {
+                    let _ = reply.send(Err(Error::Network(error.clone())));
+                }
+                emit(&self.events, NodeEvent::OutboundFailure { peer, error }).await;
+            }
+            request_response::Event::InboundFailure { .. } | request_response::Event::ResponseSent { .. } => {}
+        }
+    }
+}
+
+impl<L> EventLoop<L> {
+    async fn send_answer(&mut self, answer: Answer) {
+        let Answer { channel, response, peer, request } = answer;
+        // 对方已断开时无法应答，忽略即可
+        let _ = self.swarm.behaviour_mut().request_response.send_response(channel, response);
+        emit(&self.events, NodeEvent::InboundRequest { peer, request }).await;
+    }
+}
+
+// 报告事件；Swarm 不是 Sync，所以这里只借用发送端
+async fn emit(events: &mpsc::Sender<NodeEvent>, event: NodeEvent) {
+    // 接收端已丢弃时不再报告事件
+    let _ = events.send(event).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::config::NetworkConfigError;
+    use crate::state::{BalanceProof, StateError};
+    use crate::types::{Address, Amount, KeyPair, NetworkKind};
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    // 测试用账本：所有地址的余额都相同
+    struct FixedLedger(Amount);
+
+    impl Ledger for FixedLedger {
+        fn chain_id(&self) -> u32 {
+            1
+        }
+
+        fn network(&self) -> NetworkKind {
+            NetworkKind::Regtest
+        }
+
+        fn balance(&self, _address: &Address) -> Result<Amount, StateError> {
+            Ok(self.0)
+        }
+
+        fn balance_proof(&self, _address: &Address) -> Result<Option<BalanceProof>, StateError> {
+            Ok(None)
+        }
+    }
+
+    fn node_config(keypair: &identity::Keypair, listen: &str) -> NetworkConfig {
+        let mut config = NetworkConfig::new(keypair.public().to_peer_id());
+        config.listen_addresses = vec![listen.parse().unwrap()];
+        config
+    }
+
+    fn ledger() -> Arc<RwLock<FixedLedger>> {
+        Arc::new(RwLock::new(FixedLedger(Amount::from_u128(42))))
+    }
+
+    async fn next_event(events: &mut mpsc::Receiver<NodeEvent>) -> NodeEvent {
+        timeout(Duration::from_secs(10), events.recv()).await.expect("timed out").expect("node stopped")
+    }
+
+    // 等待第一个监听地址
+    async fn listen_address(events: &mut mpsc::Receiver<NodeEvent>) -> Multiaddr {
+        loop {
+            if let NodeEvent::Listening(address) = next_event(events).await {
+                return address;
+            }
+        }
+    }
+
+    // 启动两个节点，第二个节点把第一个节点作为引导节点，等到连接建立后返回服务端监听地址
+    async fn connected_pair(
+        listen: &str,
+        server_ledger: Arc<RwLock<FixedLedger>>,
+    ) -> (NodeHandle, mpsc::Receiver<NodeEvent>, Multiaddr, NodeHandle) {
+        let server_key = identity::Keypair::generate_ed25519();
+        let (server, mut server_events) = spawn(&node_config(&server_key, listen), server_key, server_ledger).unwrap();
+        let server_address = listen_address(&mut server_events).await;
+
+        let client_key = identity::Keypair::generate_ed25519();
+        let mut client_config = node_config(&client_key, listen);
+        client_config.bootstrap_nodes = vec![(server.local_peer_id(), server_address.clone())];
+        let (client, mut client_events) = spawn(&client_config, client_key, ledger()).unwrap();
+        loop {
+            if next_event(&mut client_events).await == NodeEvent::ConnectionEstablished(server.local_peer_id()) {
+                break;
+            }
+        }
+        (server, server_events, server_address, client)
+    }
+
+    // 两个节点建立连接后查询余额
+    async fn exchange(listen: &str) {
+        let (server, mut server_events, _, client) = connected_pair(listen, ledger()).await;
+
+        let address = Address::from_public_key(&KeyPair::generate().public_key(), NetworkKind::Regtest);
+        let request = Request::GetBalance { address };
+        let response = timeout(Duration::from_secs(10), client.send_request(server.local_peer_id(), request.clone()))
+            .await
+            .expect("timed out")
+            .unwrap();
+        assert_eq!(response, Response::GetBalanceResponse { balance: Amount::from_u128(42), proof: None });
+
+        // 服务端报告收到的请求
+        loop {
+            if let NodeEvent::InboundRequest { peer, request: received } = next_event(&mut server_events).await {
+                assert_eq!(peer, client.local_peer_id());
+                assert_eq!(received, request);
+                break;
+            }
+        }
+
+        client.shutdown().await.unwrap();
+        server.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_request_over_tcp() {
+        exchange("/ip4/127.0.0.1/tcp/0").await;
+    }
+
+    #[tokio::test]
+    async fn test_request_over_quic() {
+        exchange("/ip4/127.0.0.1/udp/0/quic-v1").await;
+    }
+
+    #[tokio::test]
+    async fn test_node_info_while_ledger_locked() {
+        let server_ledger = ledger();
+        let (server, _server_events, server_address, client) =
+            connected_pair("/ip4/127.0.0.1/tcp/0", Arc::clone(&server_ledger)).await;
+
+        // 账本被写锁占用时请求等待应答，但服务端事件循环仍然处理命令
+        let guard = server_ledger.write().await;
+        let peer = server.local_peer_id();
+        let pending = tokio::spawn(async move { client.send_request(peer, Request::GetNodeInfo).await });
+        let result = timeout(Duration::from_secs(10), server.send_request(PeerId::random(), Request::GetNodeInfo))
+            .await
+            .expect("event loop blocked");
+        assert!(matches!(result, Err(Error::Network(_))));
+        assert!(!pending.is_finished());
+        drop(guard);
+
+        // 节点信息来自服务端自己的标识和监听地址
+        let response = timeout(Duration::from_secs(10), pending).await.expect("timed out").unwrap().unwrap();
+        let Response::GetNodeInfoResponse { node_info } = response else {
+            panic!("unexpected response: {response:?}");
+        };
+        assert_eq!(node_info.peer_id, server.local_peer_id());
+        assert!(node_info.addresses.contains(&server_address));
+        assert!(node_info.is_online);
+
+        server.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rejects_mismatched_identity() {
+        let keypair = identity::Keypair::generate_ed25519();
+        let config = NetworkConfig::new(PeerId::random());
+        assert!(matches!(
+            build_swarm(&config, keypair),
+            Err(Error::NetworkConfig(NetworkConfigError::IdentityMismatch { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_request_to_unknown_peer_fails() {
+        let keypair = identity::Keypair::generate_ed25519();
+        let (node, _events) = spawn(&node_config(&keypair, "/ip4/127.0.0.1/tcp/0"), keypair, ledger()).unwrap();
+        let result = timeout(Duration::from_secs(10), node.send_request(PeerId::random(), Request::GetNodeInfo))
+            .await
+            .expect("timed out");
+        assert!(matches!(result, Err(Error::Network(_))));
+
+        // 停止后句柄不再可用
+        node.shutdown().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(node.dial(PeerId::random(), "/ip4/127.0.0.1/tcp/1".parse().unwrap()).await.is_err());
+    }
+}
@@ -1,205 +1,136 @@
-use crate::network::{types::{Request, Response}, error::Error};
+use crate::network::{types::{NodeInfo, Request, Response}, error::Error};
+use crate::state::Ledger;
 use libp2p::{
-    core::{
-        upgrade::{read_length_prefixed, write_length_prefixed},
-        ProtocolName,
-    },
-    futures::{AsyncRead, AsyncWrite, AsyncWriteExt, StreamExt},
-    request_response::{
-        ProtocolSupport, RequestResponse, RequestResponseCodec, RequestResponseConfig,
-        RequestResponseEvent,
-    },
+    futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    request_response::{self, ProtocolSupport},
+    StreamProtocol,
 };
+use serde::{de::DeserializeOwned, Serialize};
 use std::io;
 use std::iter;
-use tokio::time::timeout;
 use std::time::Duration;
 
-#[derive(Debug, Clone)]
-pub struct FaicProtocol();
-#[derive(Clone)]
+/// FAIC 请求-响应协议名
+pub const FAIC_PROTOCOL: StreamProtocol = StreamProtocol::new("/faic/1");
+
+/// 单条消息的最大长度，例如 1MB
+const MAX_MESSAGE_SIZE: usize = 1_024_000;
+
+#[derive(Debug, Clone, Default)]
 pub struct FaicCodec();
 
-impl ProtocolName for FaicProtocol {
-    fn protocol_name(&self) -> &[u8] {
-        "/faic/1".as_bytes()
+// 读取一条长度前缀（4 字节大端序）的 JSON 消息
+async fn read_message<T, M>(io: &mut T) -> io::Result<M>
+where
+    T: AsyncRead + Unpin + Send,
+    M: DeserializeOwned,
+{
+    let mut len = [0u8; 4];
+    io.read_exact(&mut len).await?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    if len > MAX_MESSAGE_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Message too large: {} bytes", len)));
+    }
+    let mut data = vec![0u8; len];
+    io.read_exact(&mut data).await?;
+    // 反序列化消息
+    serde_json::from_slice(&data).map_err(|e| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("Deserialize error: {}", e))
+    })
+}
+
+// 写入一条长度前缀的 JSON 消息并关闭写端
+async fn write_message<T, M>(io: &mut T, message: &M) -> io::Result<()>
+where
+    T: AsyncWrite + Unpin + Send,
+    M: Serialize,
+{
+    // 序列化消息
+    let data = serde_json::to_vec(message).map_err(|e| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("Serialize error: {}", e))
+    })?;
+    if data.len() > MAX_MESSAGE_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Message too large: {} bytes", data.len())));
     }
+    io.write_all(&(data.len() as u32).to_be_bytes()).await?;
+    io.write_all(&data).await?;
+    io.close().await?;
+    Ok(())
 }
 
 #[async_trait::async_trait]
-impl RequestResponseCodec for FaicCodec {
-    type Protocol = FaicProtocol;
+impl request_response::Codec for FaicCodec {
+    type Protocol = StreamProtocol;
     type Request = Request;
     type Response = Response;
 
-    async fn read_request<T>(
-        &mut self,
-        _: &Self::Protocol,
-        io: &mut T,
-    ) -> io::Result<Self::Request>
+    async fn read_request<T>(&mut self, _: &Self::Protocol, io: &mut T) -> io::Result<Self::Request>
     where
         T: AsyncRead + Unpin + Send,
     {
-        // 读取请求数据
-        let data = read_length_prefixed(io, 1_024_000).await?; // 设置一个最大长度限制，例如 1MB
-        if data.is_empty() {
-            return Err(io::ErrorKind::UnexpectedEof.into());
-        }
-        // 反序列化请求
-        let request: Request = serde_json::from_slice(&data).map_err(|e| {
-            io::Error::new(io::ErrorKind::InvalidData, format!("Deserialize error: {}", e))
-        })?;
-        Ok(request)
+        read_message(io).await
     }
 
-    async fn read_response<T>(
-        &mut self,
-        _: &Self::Protocol,
-        io: &mut T,
-    ) -> io::Result<Self::Response>
+    async fn read_response<T>(&mut self, _: &Self::Protocol, io: &mut T) -> io::Result<Self::Response>
     where
         T: AsyncRead + Unpin + Send,
     {
-        // 读取响应数据
-        let data = read_length_prefixed(io, 1_024_000).await?; // 设置一个最大长度限制，例如 1MB
-        if data.is_empty() {
-            return Err(io::ErrorKind::UnexpectedEof.into());
-        }
-        // 反序列化响应
-        let response: Response = serde_json::from_slice(&data).map_err(|e| {
-            io::Error::new(io::ErrorKind::InvalidData, format!("Deserialize error: {}", e))
-        })?;
-        Ok(response)
+        read_message(io).await
     }
 
-    async fn write_request<T>(
-        &mut self,
-        _: &Self::Protocol,
-        io: &mut T,
-        req: Self::Request,
-    ) -> io::Result<()>
+    async fn write_request<T>(&mut self, _: &Self::Protocol, io: &mut T, req: Self::Request) -> io::Result<()>
     where
         T: AsyncWrite + Unpin + Send,
     {
-        // 序列化请求
-        let data = serde_json::to_vec(&req).map_err(|e| {
-            io::Error::new(io::ErrorKind::InvalidData, format!("Serialize error: {}", e))
-        })?;
-        // 写入请求数据
-        write_length_prefixed(io, data).await?;
-        io.close().await?;
-        Ok(())
+        write_message(io, &req).await
     }
 
-    async fn write_response<T>(
-        &mut self,
-        _: &Self::Protocol,
-        io: &mut T,
-        res: Self::Response,
-    ) -> io::Result<()>
+    async fn write_response<T>(&mut self, _: &Self::Protocol, io: &mut T, res: Self::Response) -> io::Result<()>
     where
         T: AsyncWrite + Unpin + Send,
     {
-        // 序列化响应
-        let data = serde_json::to_vec(&res).map_err(|e| {
-            io::Error::new(io::ErrorKind::InvalidData, format!("Serialize error: {}", e))
-        })?;
-        // 写入响应数据
-        write_length_prefixed(io, data).await?;
-        io.close().await?;
-        Ok(())
+        write_message(io, &res).await
     }
 }
 
-pub fn create_faic_network_behaviour() -> RequestResponse<FaicCodec> {
-    // 创建 RequestResponseConfig
-    let config = RequestResponseConfig::default();
+/// 创建 FAIC 请求-响应行为，`request_timeout` 为等待响应的超时时间
+pub fn create_faic_network_behaviour(request_timeout: Duration) -> request_response::Behaviour<FaicCodec> {
+    // 创建请求-响应配置
+    let config = request_response::Config::default().with_request_timeout(request_timeout);
 
-    // 使用 FaicCodec 创建 RequestResponse 行为
-    RequestResponse::new(
+    // 使用 FaicCodec 创建请求-响应行为
+    request_response::Behaviour::with_codec(
         FaicCodec(),
-        iter::once((FaicProtocol(), ProtocolSupport::Full)),
+        iter::once((FAIC_PROTOCOL, ProtocolSupport::Full)),
         config,
     )
 }
 
-pub async fn handle_request(request: Request) -> Result<Response, Error> {
+/// 用账本应答请求，`node_info` 是本地节点当前的信息
+pub async fn handle_request<L: Ledger>(ledger: &L, node_info: NodeInfo, request: Request) -> Result<Response, Error> {
     match request {
         Request::GetBalance { address } => {
-            // 在这里实现查询余额的逻辑
-            // ...
-            // 假设 balance 是查询到的余额
-            let balance = crate::types::Amount::from_str("100")?; // 示例余额
-            Ok(Response::GetBalanceResponse { balance })
+            // 地址属于其他网络时直接返回错误响应
+            if address.network() != ledger.network() {
+                return Ok(Response::Error { message: format!("Address {} is not on this network", address) });
+            }
+            let balance = ledger.balance(&address)?;
+            let proof = ledger.balance_proof(&address)?;
+            Ok(Response::GetBalanceResponse { balance, proof })
         }
         Request::SendTransaction { transaction } => {
+            // 校验交易的链 ID、金额和签名，不合法的交易直接返回错误响应
+            if let Err(e) = transaction.verify_for_chain(ledger.chain_id()) {
+                return Ok(Response::Error { message: e.to_string() });
+            }
             // 在这里实现发送交易的逻辑
             // ...
-            // 假设 tx_hash 是交易哈希
-            let tx_hash = "some_tx_hash".to_string(); // 示例交易哈希
+            let tx_hash = transaction.txid();
             Ok(Response::SendTransactionResponse { tx_hash })
         }
-        Request::GetNodeInfo => {
-            // 在这里实现获取节点信息的逻辑
-            // ...
-            // 假设 node_info 是节点信息
-            let node_info = crate::network::types::NodeInfo {
-                peer_id: libp2p::PeerId::random(), // 示例 PeerId
-                addresses: vec!["/ip4/127.0.0.1/tcp/8080".parse().unwrap()], // 示例地址
-                is_online: true, // 示例在线状态
-            };
-            Ok(Response::GetNodeInfoResponse { node_info })
-        }
+        Request::GetNodeInfo => Ok(Response::GetNodeInfoResponse { node_info }),
     }
 }
-
-pub async fn start_listening(transport: libp2p::core::transport::Boxed<(libp2p::PeerId, libp2p::core::muxing::StreamMuxerBox)>) -> Result<(), Error> {
-    // 创建 NetworkBehaviour
-    let mut behaviour = create_faic_network_behaviour();
-
-    // 让 behaviour 监听所有接口的随机端口
-    behaviour.listen_on("/ip4/0.0.0.0/tcp/0".parse().unwrap()).unwrap();
-
-    // 启动一个异步任务来处理事件
-    tokio::spawn(async move {
-        loop {
-            tokio::select! {
-                event = behaviour.select_next_some() => match event {
-                    RequestResponseEvent::Message { peer, message } => {
-                        match message {
-                            libp2p::request_response::RequestResponseMessage::Request { request, channel, .. } => {
-                                println!("Received request from {:?}: {:?}", peer, request);
-                                // 处理请求并发送响应
-                                let response = match handle_request(request).await {
-                                    Ok(response) => response,
-                                    Err(e) => {
-                                        eprintln!("Error handling request: {}", e);
-                                        Response::Error { message: e.to_string() }
-                                    }
-                                };
-                                if let Err(e) = behaviour.send_response(channel, response) {
-                                    eprintln!("Failed to send response: {:?}", e);
-                                }
-                            }
-                            libp2p::request_response::RequestResponseMessage::Response { response, .. } => {
-                                println!("Received response from {:?}: {:?}", peer, response);
-                            }
-                        }
-                    }
-                    RequestResponseEvent::OutboundFailure { peer, request_id, error } => {
-                        eprintln!("Outbound failure to {:?} with request {:?}: {:?}", peer, request_id, error);
-                    }
-                    RequestResponseEvent::InboundFailure { peer, request_id, error } => {
-                        eprintln!("Inbound failure from {:?} with request {:?}: {:?}", peer, request_id, error);
-                    }
-                    RequestResponseEvent::ResponseSent { peer, request_id } => {
-                        println!("Response sent to {:?} for request {:?}", peer, request_id);
-                    }
-                }
-            }
-        }
-    });
-
-    Ok(())
-}
-
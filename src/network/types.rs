@@ -1,4 +1,5 @@
-use crate::types::Amount;
+use crate::state::BalanceProof;
+use crate::types::{Address, Amount, Transaction, TxId};
 use libp2p::{Multiaddr, PeerId};
 use serde::{Deserialize, Serialize};
 
@@ -33,12 +34,12 @@ pub enum Request {
     /// 查询余额
     GetBalance {
         /// 要查询的地址
-        address: String, // 暂时使用 String 类型，后续可以根据需要修改为更具体的地址类型
+        address: Address,
     },
     /// 发送交易
     SendTransaction {
-        /// 交易内容 (暂时使用 String 类型，后续需要定义具体的交易结构)
-        transaction: String,
+        /// 已签名的交易
+        transaction: Transaction,
     },
     /// 获取节点信息
     GetNodeInfo,
@@ -51,11 +52,13 @@ pub enum Response {
     GetBalanceResponse {
         /// 余额 
         balance: Amount,
+        /// 余额证明，账本不支持状态证明时为 None
+        proof: Option<BalanceProof>,
     },
     /// 发送交易的响应
     SendTransactionResponse {
-        /// 交易哈希 (暂时使用 String 类型，后续需要定义具体的哈希类型)
-        tx_hash: String,
+        /// 交易 ID
+        tx_hash: TxId,
     },
     /// 获取节点信息的响应
     GetNodeInfoResponse {
@@ -72,9 +75,25 @@ pub enum Response {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::Amount;
+    use crate::types::{Amount, KeyPair, NetworkKind};
     use num_bigint::BigUint;
 
+    fn some_address() -> Address {
+        Address::from_hash([1u8; 20], NetworkKind::Mainnet)
+    }
+
+    fn some_transaction() -> Transaction {
+        let keypair = KeyPair::from_secret_bytes(&[2u8; 32]);
+        Transaction::new_signed(
+            &keypair,
+            1,
+            0,
+            some_address(),
+            "1".parse().unwrap(),
+            "0.0001".parse().unwrap(),
+        )
+    }
+
     #[test]
     fn test_node_info_serialization() {
         let peer_id = PeerId::random();
@@ -96,29 +115,31 @@ mod tests {
     #[test]
     fn test_get_balance_request_serialization() {
         let request = Request::GetBalance {
-            address: "some_address".to_string(),
+            address: some_address(),
         };
 
         let serialized = serde_json::to_string(&request).unwrap();
         let deserialized: Request = serde_json::from_str(&serialized).unwrap();
 
         match deserialized {
-            Request::GetBalance { address } => assert_eq!(address, "some_address"),
+            Request::GetBalance { address } => assert_eq!(address, some_address()),
             _ => panic!("Unexpected request type"),
         }
     }
 
     #[test]
+    #[allow(deprecated)]
     fn test_get_balance_response_serialization() {
         let response = Response::GetBalanceResponse {
             balance: Amount::from_biguint(BigUint::from(100u32)).unwrap(),
+            proof: None,
         };
 
         let serialized = serde_json::to_string(&response).unwrap();
         let deserialized: Response = serde_json::from_str(&serialized).unwrap();
 
         match deserialized {
-            Response::GetBalanceResponse { balance } => {
+            Response::GetBalanceResponse { balance, .. } => {
                 assert_eq!(balance.value(), &BigUint::from(100u32))
             }
             _ => panic!("Unexpected response type"),
@@ -145,10 +166,10 @@ mod tests {
     fn test_request_serialization() {
         let requests = vec![
             Request::GetBalance {
-                address: "some_address".to_string(),
+                address: some_address(),
             },
             Request::SendTransaction {
-                transaction: "some_transaction".to_string(),
+                transaction: some_transaction(),
             },
             Request::GetNodeInfo,
         ];
@@ -165,9 +186,10 @@ mod tests {
         let responses = vec![
             Response::GetBalanceResponse {
                 balance: Amount::from_biguint(BigUint::from(100u32)).unwrap(),
+                proof: None,
             },
             Response::SendTransactionResponse {
-                tx_hash: "some_tx_hash".to_string(),
+                tx_hash: some_transaction().txid(),
             },
             Response::GetNodeInfoResponse {
                 node_info: NodeInfo {
@@ -0,0 +1,50 @@
+use crate::types::encoding::Reader;
+use crate::types::{Amount, DecodeError};
+use serde::{Deserialize, Serialize};
+
+/// 账户状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Account {
+    /// 余额
+    pub balance: Amount,
+    /// 下一笔交易应使用的序号
+    pub nonce: u64,
+}
+
+impl Default for Account {
+    fn default() -> Self {
+        Account {
+            balance: Amount::zero(),
+            nonce: 0,
+        }
+    }
+}
+
+impl Account {
+    /// 规范二进制编码的长度（字节）
+    pub const ENCODED_LEN: usize = Amount::ENCODED_LEN + 8;
+
+    /// 是否为空账户（余额和序号都为零）
+    pub fn is_empty(&self) -> bool {
+        self.balance.is_zero() && self.nonce == 0
+    }
+
+    /// 规范二进制编码：余额 + 序号
+    pub fn encode(&self) -> [u8; Account::ENCODED_LEN] {
+        let mut bytes = [0u8; Self::ENCODED_LEN];
+        bytes[..Amount::ENCODED_LEN].copy_from_slice(&self.balance.to_be_bytes());
+        bytes[Amount::ENCODED_LEN..].copy_from_slice(&self.nonce.to_be_bytes());
+        bytes
+    }
+
+    /// 从规范二进制编码解码
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader = Reader::new(bytes);
+        let account = Account {
+            balance: reader.read_amount()?,
+            nonce: reader.read_u64()?,
+        };
+        reader.finish()?;
+        Ok(account)
+    }
+}
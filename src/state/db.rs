@@ -0,0 +1,587 @@
+use crate::chain::{Block, BlockHash, ChainSpec};
+use crate::state::account::Account;
+use crate::state::error::StateError;
+use crate::state::proof::{account_key, account_value_hash, state_tree, BalanceProof};
+use crate::state::smt::{prove_stored, StoredSmtUpdate};
+use crate::storage::kv::{KvStore, WriteBatch};
+use crate::storage::StorageError;
+use crate::types::encoding::Reader;
+use crate::types::{Address, Amount, DecodeError, Hash256, NetworkKind, Transaction};
+use std::collections::HashMap;
+
+/// 账户状态数据库
+///
+/// 按地址保存余额和序号，并记录最近一次应用的区块（状态末端）。状态树的节点同样保存在键值存储中，
+/// 每个区块只更新被修改账户所在的路径。
+/// 区块内的所有修改先在内存中计算，全部成功后作为一个批次写入键值存储，
+/// 任何一笔交易失败都不会留下部分修改。
+#[derive(Debug, Clone)]
+pub struct StateDb<K> {
+    kv: K,
+    chain_id: u32,
+    network: NetworkKind,
+}
+
+impl<K: KvStore> StateDb<K> {
+    const ACCOUNT_PREFIX: &'static [u8] = b"account/";
+    const TIP_KEY: &'static [u8] = b"meta/tip";
+    const UNDO_PREFIX: &'static [u8] = b"undo/";
+    const STATE_ROOT_KEY: &'static [u8] = b"meta/state_root";
+
+    /// 在键值存储之上创建状态数据库
+    ///
+    /// # Arguments
+    ///
+    /// * `kv` - 键值存储
+    /// * `spec` - 链规格，提供链 ID 和网络类型
+    pub fn new(kv: K, spec: &ChainSpec) -> Self {
+        StateDb {
+            kv,
+            chain_id: spec.chain_id,
+            network: spec.network,
+        }
+    }
+
+    /// 链 ID
+    pub fn chain_id(&self) -> u32 {
+        self.chain_id
+    }
+
+    /// 网络类型
+    pub fn network(&self) -> NetworkKind {
+        self.network
+    }
+
+    /// 底层的键值存储
+    pub fn kv(&self) -> &K {
+        &self.kv
+    }
+
+    // 账户在键值存储中的键
+    fn account_key(address: &Address) -> Vec<u8> {
+        [Self::ACCOUNT_PREFIX, &address.to_bytes()[..]].concat()
+    }
+
+    /// 读取账户，不存在时返回空账户
+    pub fn account(&self, address: &Address) -> Result<Account, StateError> {
+        match self.kv.get(&Self::account_key(address))? {
+            Some(bytes) => Ok(Account::decode(&bytes).map_err(StorageError::from)?),
+            None => Ok(Account::default()),
+        }
+    }
+
+    /// 查询余额
+    pub fn balance(&self, address: &Address) -> Result<Amount, StateError> {
+        Ok(self.account(address)?.balance)
+    }
+
+    /// 查询下一笔交易应使用的序号
+    pub fn nonce(&self, address: &Address) -> Result<u64, StateError> {
+        Ok(self.account(address)?.nonce)
+    }
+
+    /// 状态末端：最近一次应用的区块哈希和高度
+    pub fn tip(&self) -> Result<Option<(BlockHash, u64)>, StateError> {
+        let Some(bytes) = self.kv.get(Self::TIP_KEY)? else {
+            return Ok(None);
+        };
+        let mut reader = Reader::new(&bytes);
+        let hash = BlockHash::from_array(reader.read_array().map_err(StorageError::from)?);
+        let height = reader.read_u64().map_err(StorageError::from)?;
+        reader.finish().map_err(StorageError::from)?;
+        Ok(Some((hash, height)))
+    }
+
+    // 区块撤销记录在键值存储中的键
+    fn undo_key(hash: &BlockHash) -> Vec<u8> {
+        [Self::UNDO_PREFIX, &hash.as_bytes()[..]].concat()
+    }
+
+    // 撤销记录：区块修改过的账户在区块之前的状态，[数量 u32][(地址, 账户)]...
+    fn encode_undo(previous: &HashMap<Address, Account>) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(4 + previous.len() * (Address::ENCODED_LEN + Account::ENCODED_LEN));
+        bytes.extend_from_slice(&(previous.len() as u32).to_be_bytes());
+        for (address, account) in previous {
+            bytes.extend_from_slice(&address.to_bytes());
+            bytes.extend_from_slice(&account.encode());
+        }
+        bytes
+    }
+
+    // 解码撤销记录
+    fn decode_undo(bytes: &[u8]) -> Result<HashMap<Address, Account>, DecodeError> {
+        let mut reader = Reader::new(bytes);
+        let count = reader.read_u32()? as usize;
+        if reader.remaining() != count * (Address::ENCODED_LEN + Account::ENCODED_LEN) {
+            return Err(DecodeError::Invalid("Invalid undo record length"));
+        }
+        let mut previous = HashMap::with_capacity(count);
+        for _ in 0..count {
+            let address = Address::from_bytes(reader.read_bytes(Address::ENCODED_LEN)?).map_err(DecodeError::Invalid)?;
+            let account = Account::decode(reader.read_bytes(Account::ENCODED_LEN)?)?;
+            previous.insert(address, account);
+        }
+        reader.finish()?;
+        Ok(previous)
+    }
+
+    // 把末端写入批次
+    fn put_tip(batch: &mut WriteBatch, hash: &BlockHash, height: u64) {
+        batch.put(Self::TIP_KEY, [&hash.as_bytes()[..], &height.to_be_bytes()].concat());
+    }
+
+    // 把修改过的账户写入批次，空账户直接删除
+    fn put_accounts(batch: &mut WriteBatch, accounts: HashMap<Address, Account>) {
+        for (address, account) in accounts {
+            if account.is_empty() {
+                batch.delete(Self::account_key(&address));
+            } else {
+                batch.put(Self::account_key(&address), account.encode());
+            }
+        }
+    }
+
+    // 已保存的状态树的根，尚未写入创世状态时为空树
+    fn stored_root(&self) -> Result<Hash256, StateError> {
+        match self.kv.get(Self::STATE_ROOT_KEY)? {
+            Some(bytes) => {
+                let mut reader = Reader::new(&bytes);
+                let root = Hash256::from_array(reader.read_array().map_err(StorageError::from)?);
+                reader.finish().map_err(StorageError::from)?;
+                Ok(root)
+            }
+            None => Ok(Hash256::ZERO),
+        }
+    }
+
+    // 当前状态树叠加 changes 之后的修改，空账户从树中删除
+    fn state_update(&self, changes: &HashMap<Address, Account>) -> Result<StoredSmtUpdate<'_, K>, StateError> {
+        let mut update = StoredSmtUpdate::new(&self.kv, self.stored_root()?);
+        for (address, account) in changes {
+            if account.is_empty() {
+                update.remove(&account_key(address))?;
+            } else {
+                update.insert(account_key(address), account_value_hash(account))?;
+            }
+        }
+        Ok(update)
+    }
+
+    // 把状态树的新节点和树根写入批次
+    fn put_state_tree(batch: &mut WriteBatch, update: StoredSmtUpdate<'_, K>) {
+        let root = update.commit(batch);
+        batch.put(Self::STATE_ROOT_KEY, root.as_bytes().to_vec());
+    }
+
+    /// 当前状态的状态根
+    pub fn state_root(&self) -> Result<Hash256, StateError> {
+        self.stored_root()
+    }
+
+    /// 生成账户在状态末端的余额证明
+    pub fn balance_proof(&self, address: &Address) -> Result<BalanceProof, StateError> {
+        let (block_hash, _) = self.tip()?.ok_or(StateError::NotInitialized)?;
+        let proof = prove_stored(&self.kv, &self.stored_root()?, &account_key(address))?;
+        Ok(BalanceProof {
+            block_hash,
+            account: self.account(address)?,
+            proof,
+        })
+    }
+
+    // 汇总创世分配
+    fn genesis_accounts(allocations: &[(Address, Amount)]) -> Result<HashMap<Address, Account>, StateError> {
+        let mut accounts: HashMap<Address, Account> = HashMap::new();
+        for (address, amount) in allocations {
+            let account = accounts.entry(*address).or_default();
+            account.balance = account
+                .balance
+                .checked_add(amount)
+                .ok_or(StateError::BalanceOverflow(*address))?;
+        }
+        Ok(accounts)
+    }
+
+    /// 创世分配对应的状态根，用于填写创世区块头
+    pub fn genesis_state_root(allocations: &[(Address, Amount)]) -> Result<Hash256, StateError> {
+        Ok(state_tree(&Self::genesis_accounts(allocations)?).root())
+    }
+
+    // 检查区块头中的状态根
+    fn check_state_root(block: &Block, actual: Hash256) -> Result<(), StateError> {
+        if block.header.state_root != actual {
+            return Err(StateError::StateRootMismatch {
+                expected: block.header.state_root,
+                actual,
+            });
+        }
+        Ok(())
+    }
+
+    /// 写入创世状态：创世区块的初始分配，区块头中的状态根必须与分配一致
+    pub fn apply_genesis(&mut self, genesis: &Block, allocations: &[(Address, Amount)]) -> Result<(), StateError> {
+        if self.tip()?.is_some() {
+            return Err(StateError::AlreadyInitialized);
+        }
+        if genesis.height() != 0 {
+            return Err(StateError::NotGenesis);
+        }
+        let accounts = Self::genesis_accounts(allocations)?;
+        let update = self.state_update(&accounts)?;
+        Self::check_state_root(genesis, update.root())?;
+
+        let mut batch = WriteBatch::new();
+        Self::put_state_tree(&mut batch, update);
+        Self::put_accounts(&mut batch, accounts);
+        Self::put_tip(&mut batch, &genesis.hash(), 0);
+        self.kv.write_batch(batch)?;
+        Ok(())
+    }
+
+    // 在当前状态上依次执行交易，返回修改过的账户，不写入存储
+    fn execute(&self, transactions: &[Transaction]) -> Result<HashMap<Address, Account>, StateError> {
+        let mut accounts: HashMap<Address, Account> = HashMap::new();
+        for transaction in transactions {
+            transaction
+                .verify_for_chain(self.chain_id)
+                .map_err(|error| StateError::InvalidTransaction {
+                    txid: transaction.txid(),
+                    error,
+                })?;
+
+            let sender = transaction.sender_address(self.network);
+            let mut account = match accounts.get(&sender) {
+                Some(account) => *account,
+                None => self.account(&sender)?,
+            };
+            if transaction.nonce != account.nonce {
+                return Err(StateError::NonceMismatch {
+                    address: sender,
+                    expected: account.nonce,
+                    actual: transaction.nonce,
+                });
+            }
+            // verify 已保证金额与手续费之和不会溢出
+            let required = transaction.total_cost().ok_or(StateError::BalanceOverflow(sender))?;
+            account.balance = account
+                .balance
+                .checked_sub(&required)
+                .ok_or(StateError::InsufficientBalance {
+                    address: sender,
+                    balance: account.balance,
+                    required,
+                })?;
+            account.nonce += 1;
+            accounts.insert(sender, account);
+
+            let mut recipient = match accounts.get(&transaction.recipient) {
+                Some(account) => *account,
+                None => self.account(&transaction.recipient)?,
+            };
+            recipient.balance = recipient
+                .balance
+                .checked_add(&transaction.value)
+                .ok_or(StateError::BalanceOverflow(transaction.recipient))?;
+            accounts.insert(transaction.recipient, recipient);
+        }
+        Ok(accounts)
+    }
+
+    /// 在当前状态上执行交易后的状态根，不修改状态，供出块时填写区块头
+    pub fn state_root_after(&self, transactions: &[Transaction]) -> Result<Hash256, StateError> {
+        let changes = self.execute(transactions)?;
+        Ok(self.state_update(&changes)?.root())
+    }
+
+    /// 原子地应用一个区块中的所有交易
+    ///
+    /// 区块必须是当前状态末端的子区块。每笔交易需通过签名和链 ID 校验，序号等于发送方账户的
+    /// 当前序号，且余额足以支付金额和手续费。执行后的状态根必须与区块头一致。
+    /// 手续费暂时直接销毁。同一批次中还会写入撤销记录，供 `revert_block` 回滚。
+    pub fn apply_block(&mut self, block: &Block) -> Result<(), StateError> {
+        let (tip_hash, tip_height) = self.tip()?.ok_or(StateError::NotInitialized)?;
+        if block.header.parent_hash != tip_hash || tip_height.checked_add(1) != Some(block.height()) {
+            return Err(StateError::WrongParent {
+                expected: tip_hash,
+                actual: block.header.parent_hash,
+            });
+        }
+
+        let accounts = self.execute(&block.transactions)?;
+        let update = self.state_update(&accounts)?;
+        Self::check_state_root(block, update.root())?;
+
+        let mut previous = HashMap::with_capacity(accounts.len());
+        for address in accounts.keys() {
+            previous.insert(*address, self.account(address)?);
+        }
+        let hash = block.hash();
+        let mut batch = WriteBatch::new();
+        batch.put(Self::undo_key(&hash), Self::encode_undo(&previous));
+        Self::put_state_tree(&mut batch, update);
+        Self::put_accounts(&mut batch, accounts);
+        Self::put_tip(&mut batch, &hash, block.height());
+        self.kv.write_batch(batch)?;
+        Ok(())
+    }
+
+    /// 原子地回滚状态末端的区块，末端退回到其父区块
+    ///
+    /// 使用 `apply_block` 写入的撤销记录恢复账户，回滚后撤销记录被删除。创世区块不能回滚。
+    pub fn revert_block(&mut self, block: &Block) -> Result<(), StateError> {
+        let (tip_hash, _) = self.tip()?.ok_or(StateError::NotInitialized)?;
+        let hash = block.hash();
+        if hash != tip_hash {
+            return Err(StateError::NotTip { tip: tip_hash, block: hash });
+        }
+        let undo = self
+            .kv
+            .get(&Self::undo_key(&hash))?
+            .ok_or(StateError::MissingUndo(hash))?;
+        let previous = Self::decode_undo(&undo).map_err(StorageError::from)?;
+        // 恢复原来的叶子即得到父区块的状态根，所需节点仍在存储中
+        let update = self.state_update(&previous)?;
+
+        let mut batch = WriteBatch::new();
+        batch.delete(Self::undo_key(&hash));
+        Self::put_state_tree(&mut batch, update);
+        Self::put_accounts(&mut batch, previous);
+        Self::put_tip(&mut batch, &block.header.parent_hash, block.height() - 1);
+        self.kv.write_batch(batch)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::testing::{child_header, fixture, genesis_header};
+    use crate::chain::BlockHeader;
+    use crate::storage::kv::{FileKvStore, MemoryKvStore};
+
+    fn amount(s: &str) -> Amount {
+        s.parse().unwrap()
+    }
+
+    fn genesis_allocations() -> Vec<(Address, Amount)> {
+        vec![(fixture().alice_address, amount("10"))]
+    }
+
+    // 状态根与创世分配一致的创世区块
+    fn genesis() -> Block {
+        let header = BlockHeader {
+            state_root: StateDb::<MemoryKvStore>::genesis_state_root(&genesis_allocations()).unwrap(),
+            ..genesis_header(0)
+        };
+        Block::new(header, Vec::new())
+    }
+
+    // 在 parent 之上构造区块，交易可以执行时填写正确的状态根
+    fn child<K: KvStore>(state: &StateDb<K>, parent: &Block, transactions: Vec<Transaction>) -> Block {
+        let header = BlockHeader {
+            state_root: state.state_root_after(&transactions).unwrap_or(Hash256::ZERO),
+            ..child_header(&parent.header, 60)
+        };
+        Block::new(header, transactions)
+    }
+
+    fn initialized<K: KvStore>(kv: K) -> StateDb<K> {
+        let mut state = StateDb::new(kv, &ChainSpec::regtest());
+        state.apply_genesis(&genesis(), &genesis_allocations()).unwrap();
+        state
+    }
+
+    #[test]
+    fn test_apply_block() {
+        let f = fixture();
+        let mut state = initialized(MemoryKvStore::new());
+        assert_eq!(state.balance(&f.alice_address).unwrap(), amount("10"));
+        assert_eq!(state.balance(&f.bob).unwrap(), Amount::zero());
+        assert!(matches!(
+            state.apply_genesis(&genesis(), &[]),
+            Err(StateError::AlreadyInitialized)
+        ));
+
+        let transactions = vec![
+            Transaction::new_signed(&f.alice, 3, 0, f.bob, amount("3"), amount("0.1")),
+            Transaction::new_signed(&f.alice, 3, 1, f.bob, amount("1"), amount("0.1")),
+        ];
+        let block = child(&state, &genesis(), transactions);
+        state.apply_block(&block).unwrap();
+
+        assert_eq!(state.balance(&f.alice_address).unwrap(), amount("5.8"));
+        assert_eq!(state.nonce(&f.alice_address).unwrap(), 2);
+        assert_eq!(state.balance(&f.bob).unwrap(), amount("4"));
+        assert_eq!(state.tip().unwrap(), Some((block.hash(), 1)));
+
+        // 同一个区块不能应用两次
+        assert!(matches!(state.apply_block(&block), Err(StateError::WrongParent { .. })));
+    }
+
+    #[test]
+    fn test_failed_block_leaves_state_unchanged() {
+        let f = fixture();
+        let mut state = initialized(MemoryKvStore::new());
+        let parent = genesis();
+
+        // 第一笔交易合法，第二笔余额不足：整个区块被拒绝
+        let block = child(
+            &state,
+            &parent,
+            vec![
+                Transaction::new_signed(&f.alice, 3, 0, f.bob, amount("6"), Amount::zero()),
+                Transaction::new_signed(&f.alice, 3, 1, f.bob, amount("6"), Amount::zero()),
+            ],
+        );
+        assert!(matches!(
+            state.apply_block(&block),
+            Err(StateError::InsufficientBalance { .. })
+        ));
+        assert_eq!(state.balance(&f.alice_address).unwrap(), amount("10"));
+        assert_eq!(state.balance(&f.bob).unwrap(), Amount::zero());
+        assert_eq!(state.tip().unwrap(), Some((parent.hash(), 0)));
+
+        // 序号错误
+        let block = child(
+            &state,
+            &parent,
+            vec![Transaction::new_signed(&f.alice, 3, 1, f.bob, amount("1"), Amount::zero())],
+        );
+        assert!(matches!(
+            state.apply_block(&block),
+            Err(StateError::NonceMismatch { expected: 0, actual: 1, .. })
+        ));
+
+        // 其他链的交易
+        let block = child(
+            &state,
+            &parent,
+            vec![Transaction::new_signed(&f.alice, 1, 0, f.bob, amount("1"), Amount::zero())],
+        );
+        assert!(matches!(
+            state.apply_block(&block),
+            Err(StateError::InvalidTransaction { .. })
+        ));
+        assert_eq!(state.nonce(&f.alice_address).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_state_persists_on_disk() {
+        let dir = "test_state_db_dir";
+        let _ = std::fs::remove_dir_all(dir);
+        let f = fixture();
+
+        let transactions = vec![Transaction::new_signed(&f.alice, 3, 0, f.bob, amount("2"), Amount::zero())];
+        let block = {
+            let mut state = initialized(FileKvStore::open(dir).unwrap());
+            let block = child(&state, &genesis(), transactions);
+            state.apply_block(&block).unwrap();
+            block
+        };
+
+        let state = StateDb::new(FileKvStore::open(dir).unwrap(), &ChainSpec::regtest());
+        assert_eq!(state.balance(&f.alice_address).unwrap(), amount("8"));
+        assert_eq!(state.balance(&f.bob).unwrap(), amount("2"));
+        assert_eq!(state.tip().unwrap(), Some((block.hash(), 1)));
+        assert_eq!(state.state_root().unwrap(), block.header.state_root);
+        // 状态树节点随区块一起保存，重新打开后可以直接生成证明
+        assert!(state.balance_proof(&f.bob).unwrap().verify(&block.header, &f.bob));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_state_root_is_checked() {
+        let f = fixture();
+        let mut state = initialized(MemoryKvStore::new());
+        assert_eq!(state.state_root().unwrap(), genesis().header.state_root);
+
+        // 创世区块的状态根与分配不一致
+        let mut other = StateDb::new(MemoryKvStore::new(), &ChainSpec::regtest());
+        assert!(matches!(
+            other.apply_genesis(&genesis(), &[(f.bob, amount("10"))]),
+            Err(StateError::StateRootMismatch { .. })
+        ));
+
+        let transactions = vec![Transaction::new_signed(&f.alice, 3, 0, f.bob, amount("1"), Amount::zero())];
+        let mut block = child(&state, &genesis(), transactions);
+        let correct_root = block.header.state_root;
+        block.header.state_root = Hash256::ZERO;
+        assert!(matches!(
+            state.apply_block(&block),
+            Err(StateError::StateRootMismatch { .. })
+        ));
+        assert_eq!(state.balance(&f.bob).unwrap(), Amount::zero());
+
+        block.header.state_root = correct_root;
+        state.apply_block(&block).unwrap();
+        assert_eq!(state.state_root().unwrap(), correct_root);
+    }
+
+    #[test]
+    fn test_balance_proof() {
+        let f = fixture();
+        let mut state = initialized(MemoryKvStore::new());
+        let transactions = vec![Transaction::new_signed(&f.alice, 3, 0, f.bob, amount("1"), Amount::zero())];
+        let block = child(&state, &genesis(), transactions);
+        state.apply_block(&block).unwrap();
+
+        let proof = state.balance_proof(&f.bob).unwrap();
+        assert_eq!(proof.account.balance, amount("1"));
+        assert!(proof.verify(&block.header, &f.bob));
+
+        // 不存在的账户同样可以证明（余额为零）
+        let carol = f.carol;
+        let proof = state.balance_proof(&carol).unwrap();
+        assert!(proof.account.is_empty());
+        assert!(proof.verify(&block.header, &carol));
+
+        // 篡改余额、换一个地址或对照其他区块头都无法通过校验
+        let mut forged = state.balance_proof(&f.bob).unwrap();
+        forged.account.balance = amount("100");
+        assert!(!forged.verify(&block.header, &f.bob));
+        let proof = state.balance_proof(&f.bob).unwrap();
+        assert!(!proof.verify(&block.header, &carol));
+        assert!(!proof.verify(&genesis().header, &f.bob));
+    }
+
+    #[test]
+    fn test_revert_block() {
+        let f = fixture();
+        let mut state = initialized(MemoryKvStore::new());
+        let genesis_root = state.state_root().unwrap();
+
+        let first = child(
+            &state,
+            &genesis(),
+            vec![Transaction::new_signed(&f.alice, 3, 0, f.bob, amount("2"), amount("1"))],
+        );
+        state.apply_block(&first).unwrap();
+        let first_root = state.state_root().unwrap();
+        let second = child(
+            &state,
+            &first,
+            vec![Transaction::new_signed(&f.alice, 3, 1, f.bob, amount("3"), Amount::zero())],
+        );
+        state.apply_block(&second).unwrap();
+
+        // 只能回滚末端区块
+        assert!(matches!(state.revert_block(&first), Err(StateError::NotTip { .. })));
+
+        state.revert_block(&second).unwrap();
+        assert_eq!(state.tip().unwrap(), Some((first.hash(), 1)));
+        assert_eq!(state.state_root().unwrap(), first_root);
+        assert_eq!(state.nonce(&f.alice_address).unwrap(), 1);
+
+        state.revert_block(&first).unwrap();
+        assert_eq!(state.tip().unwrap(), Some((genesis().hash(), 0)));
+        assert_eq!(state.state_root().unwrap(), genesis_root);
+        assert_eq!(state.balance(&f.alice_address).unwrap(), amount("10"));
+        assert_eq!(state.balance(&f.bob).unwrap(), Amount::zero());
+        assert!(matches!(state.revert_block(&genesis()), Err(StateError::MissingUndo(_))));
+
+        // 回滚后可以重新应用
+        state.apply_block(&first).unwrap();
+        assert_eq!(state.state_root().unwrap(), first_root);
+    }
+}
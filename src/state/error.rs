@@ -0,0 +1,92 @@
+use crate::chain::BlockHash;
+use crate::state::utxo::OutPoint;
+use crate::storage::StorageError;
+use crate::types::{Address, Amount, Hash256, TransactionError, TxId};
+use std::fmt;
+
+// 自定义错误类型
+#[derive(Debug)]
+pub enum StateError {
+    Storage(StorageError),  // 存储层错误
+    NotInitialized,  // 尚未写入创世状态
+    AlreadyInitialized,  // 重复写入创世状态
+    NotGenesis,  // 创世区块高度不为 0
+    WrongParent { expected: BlockHash, actual: BlockHash },  // 区块不是当前状态末端的子区块
+    InvalidTransaction { txid: TxId, error: TransactionError },  // 交易本身不合法
+    NonceMismatch { address: Address, expected: u64, actual: u64 },  // 交易序号不正确
+    InsufficientBalance { address: Address, balance: Amount, required: Amount },  // 余额不足
+    BalanceOverflow(Address),  // 余额溢出
+    MissingOutput(OutPoint),  // 花费的输出不存在或已被花费
+    DuplicateOutput(OutPoint),  // 创建的输出已经存在
+    OutputsExceedInputs(TxId),  // 交易输出总额超过输入总额
+    ExcessIssuance { issued: Amount, allowed: Amount },  // 新发行量超过允许值
+    CoinbaseHeightMismatch { txid: TxId, expected: u64, actual: u64 },  // 发行交易记录的高度与区块不一致，或普通交易带有高度
+    WitnessCountMismatch { txid: TxId, inputs: usize, witnesses: usize },  // 见证数量与输入数量不一致
+    UnauthorizedSpend(OutPoint),  // 见证的公钥与被花费输出的地址不符，或签名无效
+    SupplyMismatch { tracked: Amount, actual: Amount },  // 记录的总量与实际未花费输出之和不一致
+    StateRootMismatch { expected: Hash256, actual: Hash256 },  // 区块头中的状态根与执行结果不一致
+    NotTip { tip: BlockHash, block: BlockHash },  // 要回滚的区块不是状态末端
+    MissingUndo(BlockHash),  // 区块没有撤销记录
+}
+
+// 为 StateError 实现 Display trait，用于打印错误信息
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StateError::Storage(e) => write!(f, "Storage error: {}", e),
+            StateError::NotInitialized => write!(f, "State has no genesis"),
+            StateError::AlreadyInitialized => write!(f, "State already has a genesis"),
+            StateError::NotGenesis => write!(f, "Genesis block must have height 0"),
+            StateError::WrongParent { expected, actual } => {
+                write!(f, "Block parent {} does not match state tip {}", actual, expected)
+            }
+            StateError::InvalidTransaction { txid, error } => {
+                write!(f, "Invalid transaction {}: {}", txid, error)
+            }
+            StateError::NonceMismatch { address, expected, actual } => {
+                write!(f, "Wrong nonce for {}: expected {}, got {}", address, expected, actual)
+            }
+            StateError::InsufficientBalance { address, balance, required } => {
+                write!(f, "Insufficient balance for {}: has {}, needs {}", address, balance, required)
+            }
+            StateError::BalanceOverflow(address) => write!(f, "Balance of {} overflows", address),
+            StateError::MissingOutput(outpoint) => write!(f, "Output {} is missing or spent", outpoint),
+            StateError::DuplicateOutput(outpoint) => write!(f, "Output {} already exists", outpoint),
+            StateError::OutputsExceedInputs(txid) => {
+                write!(f, "Outputs of transaction {} exceed its inputs", txid)
+            }
+            StateError::ExcessIssuance { issued, allowed } => {
+                write!(f, "Block issues {}, only {} allowed", issued, allowed)
+            }
+            StateError::CoinbaseHeightMismatch { txid, expected, actual } => {
+                write!(f, "Transaction {} has coinbase height {}, expected {}", txid, actual, expected)
+            }
+            StateError::WitnessCountMismatch { txid, inputs, witnesses } => {
+                write!(f, "Transaction {} has {} inputs but {} witnesses", txid, inputs, witnesses)
+            }
+            StateError::UnauthorizedSpend(outpoint) => {
+                write!(f, "Spend of output {} is not authorized by its owner", outpoint)
+            }
+            StateError::SupplyMismatch { tracked, actual } => {
+                write!(f, "Tracked supply {} does not match unspent outputs {}", tracked, actual)
+            }
+            StateError::StateRootMismatch { expected, actual } => {
+                write!(f, "State root mismatch: header has {}, computed {}", expected, actual)
+            }
+            StateError::NotTip { tip, block } => {
+                write!(f, "Block {} is not the state tip {}", block, tip)
+            }
+            StateError::MissingUndo(hash) => write!(f, "No undo record for block {}", hash),
+        }
+    }
+}
+
+// 为 StateError 实现 Error trait
+impl std::error::Error for StateError {}
+
+// 实现从 StorageError 到 StateError 的转换
+impl From<StorageError> for StateError {
+    fn from(err: StorageError) -> Self {
+        StateError::Storage(err)
+    }
+}
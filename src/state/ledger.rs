@@ -0,0 +1,42 @@
+use crate::state::error::StateError;
+use crate::state::proof::BalanceProof;
+use crate::state::StateDb;
+use crate::storage::KvStore;
+use crate::types::{Address, Amount, NetworkKind};
+
+/// 账本：节点对外提供余额查询时依赖的最小接口
+///
+/// 账户模型（`StateDb`）和 UTXO 模型（`UtxoSet`）都实现该接口。
+pub trait Ledger {
+    /// 链 ID
+    fn chain_id(&self) -> u32;
+
+    /// 网络类型
+    fn network(&self) -> NetworkKind;
+
+    /// 查询地址的余额
+    fn balance(&self, address: &Address) -> Result<Amount, StateError>;
+
+    /// 生成余额证明，不支持状态证明的账本返回 None
+    fn balance_proof(&self, _address: &Address) -> Result<Option<BalanceProof>, StateError> {
+        Ok(None)
+    }
+}
+
+impl<K: KvStore> Ledger for StateDb<K> {
+    fn chain_id(&self) -> u32 {
+        StateDb::chain_id(self)
+    }
+
+    fn network(&self) -> NetworkKind {
+        StateDb::network(self)
+    }
+
+    fn balance(&self, address: &Address) -> Result<Amount, StateError> {
+        StateDb::balance(self, address)
+    }
+
+    fn balance_proof(&self, address: &Address) -> Result<Option<BalanceProof>, StateError> {
+        StateDb::balance_proof(self, address).map(Some)
+    }
+}
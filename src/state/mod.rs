@@ -0,0 +1,15 @@
+pub mod account;
+pub mod db;
+pub mod error;
+pub mod ledger;
+pub mod proof;
+pub mod smt;
+pub mod utxo;
+
+pub use account::Account;
+pub use db::StateDb;
+pub use error::StateError;
+pub use ledger::Ledger;
+pub use proof::BalanceProof;
+pub use smt::{SparseMerkleProof, SparseMerkleTree};
+pub use utxo::{BlockUndo, OutPoint, TxOut, UtxoSet, UtxoTransaction, Witness};
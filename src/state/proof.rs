@@ -0,0 +1,49 @@
+use crate::chain::{BlockHash, BlockHeader};
+use crate::state::account::Account;
+use crate::state::smt::{SparseMerkleProof, SparseMerkleTree};
+use crate::types::{Address, Hash256};
+use serde::{Deserialize, Serialize};
+
+/// 账户在状态树中的键：地址规范编码的双重 SHA-256
+pub fn account_key(address: &Address) -> Hash256 {
+    Hash256::double_sha256(&address.to_bytes())
+}
+
+/// 账户在状态树中的值哈希：账户规范编码的双重 SHA-256
+pub fn account_value_hash(account: &Account) -> Hash256 {
+    Hash256::double_sha256(&account.encode())
+}
+
+/// 由账户列表构建状态树，空账户不进入状态树
+pub fn state_tree<'a>(accounts: impl IntoIterator<Item = (&'a Address, &'a Account)>) -> SparseMerkleTree {
+    SparseMerkleTree::new(
+        accounts
+            .into_iter()
+            .filter(|(_, account)| !account.is_empty())
+            .map(|(address, account)| (account_key(address), account_value_hash(account))),
+    )
+}
+
+/// 余额证明：轻客户端可以对照区块头中的状态根校验账户状态，无需信任节点
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceProof {
+    /// 证明所对应的区块
+    pub block_hash: BlockHash,
+    /// 账户状态，账户不存在时为空账户
+    pub account: Account,
+    /// 状态树中的证明
+    pub proof: SparseMerkleProof,
+}
+
+impl BalanceProof {
+    /// 对照区块头校验证明：区块哈希一致，且账户状态与区块头中的状态根相符
+    pub fn verify(&self, header: &BlockHeader, address: &Address) -> bool {
+        if header.hash() != self.block_hash {
+            return false;
+        }
+        // 空账户没有叶子，以不存在证明表示
+        let value_hash = (!self.account.is_empty()).then(|| account_value_hash(&self.account));
+        self.proof
+            .verify(&header.state_root, &account_key(address), value_hash.as_ref())
+    }
+}
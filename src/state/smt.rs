@@ -0,0 +1,504 @@
+use crate::storage::kv::{KvStore, WriteBatch};
+use crate::storage::StorageError;
+use crate::types::Hash256;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// 叶子节点和内部节点使用不同的前缀，与交易默克尔树的约定一致
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// 键的位数，也是树的最大深度
+const KEY_BITS: usize = Hash256::LEN * 8;
+
+// 叶子节点哈希：H(0x00 || key || value_hash)
+fn hash_leaf(key: &Hash256, value_hash: &Hash256) -> Hash256 {
+    let mut data = [0u8; 1 + 2 * Hash256::LEN];
+    data[0] = LEAF_PREFIX;
+    data[1..1 + Hash256::LEN].copy_from_slice(key.as_bytes());
+    data[1 + Hash256::LEN..].copy_from_slice(value_hash.as_bytes());
+    Hash256::double_sha256(&data)
+}
+
+// 内部节点哈希：H(0x01 || left || right)
+fn hash_node(left: &Hash256, right: &Hash256) -> Hash256 {
+    let mut data = [0u8; 1 + 2 * Hash256::LEN];
+    data[0] = NODE_PREFIX;
+    data[1..1 + Hash256::LEN].copy_from_slice(left.as_bytes());
+    data[1 + Hash256::LEN..].copy_from_slice(right.as_bytes());
+    Hash256::double_sha256(&data)
+}
+
+// 键从高位开始的第 depth 位，1 表示走右子树
+fn bit(key: &Hash256, depth: usize) -> bool {
+    (key.as_bytes()[depth / 8] >> (7 - depth % 8)) & 1 == 1
+}
+
+// 两个键从高位开始相同的位数
+fn common_prefix_len(a: &Hash256, b: &Hash256) -> usize {
+    (0..KEY_BITS).take_while(|&depth| bit(a, depth) == bit(b, depth)).count()
+}
+
+/// 稀疏默克尔树
+///
+/// 以 256 位键寻址：从根开始，键的每一位决定走左还是右子树。空子树的哈希为全零，
+/// 只含一个叶子的子树直接用该叶子的哈希表示，因此树的实际深度约为 log2(叶子数)。
+/// 叶子哈希同时承诺键和值，证明中只需要给出路径上的兄弟节点。
+#[derive(Debug, Clone, Default)]
+pub struct SparseMerkleTree {
+    // 按键排序的 (键, 值哈希)
+    leaves: Vec<(Hash256, Hash256)>,
+}
+
+/// 稀疏默克尔树的证明，可以证明某个键存在（及其值）或不存在
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SparseMerkleProof {
+    /// 路径终点的叶子 (键, 值哈希)，None 表示终点是空子树
+    pub leaf: Option<(Hash256, Hash256)>,
+    /// 从根到终点路径上的兄弟节点
+    pub siblings: Vec<Hash256>,
+}
+
+impl SparseMerkleTree {
+    /// 由 (键, 值哈希) 构建，键重复时保留最后一个
+    pub fn new(leaves: impl IntoIterator<Item = (Hash256, Hash256)>) -> Self {
+        let mut leaves: Vec<(Hash256, Hash256)> = leaves.into_iter().collect();
+        leaves.reverse();
+        leaves.sort_by_key(|(key, _)| *key);
+        leaves.dedup_by(|a, b| a.0 == b.0);
+        SparseMerkleTree { leaves }
+    }
+
+    /// 叶子数量
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// 是否没有叶子
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// 树根，空树为全零哈希
+    pub fn root(&self) -> Hash256 {
+        Self::subtree_root(&self.leaves, 0)
+    }
+
+    // 已排序叶子在第 depth 位上的分界点
+    fn split(leaves: &[(Hash256, Hash256)], depth: usize) -> usize {
+        leaves.partition_point(|(key, _)| !bit(key, depth))
+    }
+
+    fn subtree_root(leaves: &[(Hash256, Hash256)], depth: usize) -> Hash256 {
+        match leaves {
+            [] => Hash256::ZERO,
+            [(key, value_hash)] => hash_leaf(key, value_hash),
+            _ => {
+                let (left, right) = leaves.split_at(Self::split(leaves, depth));
+                hash_node(
+                    &Self::subtree_root(left, depth + 1),
+                    &Self::subtree_root(right, depth + 1),
+                )
+            }
+        }
+    }
+
+    /// 生成键的证明：键存在时证明其值，不存在时证明其不存在
+    pub fn prove(&self, key: &Hash256) -> SparseMerkleProof {
+        let mut leaves = &self.leaves[..];
+        let mut siblings = Vec::new();
+        let mut depth = 0;
+        while leaves.len() > 1 {
+            let (left, right) = leaves.split_at(Self::split(leaves, depth));
+            if bit(key, depth) {
+                siblings.push(Self::subtree_root(left, depth + 1));
+                leaves = right;
+            } else {
+                siblings.push(Self::subtree_root(right, depth + 1));
+                leaves = left;
+            }
+            depth += 1;
+        }
+        SparseMerkleProof {
+            leaf: leaves.first().copied(),
+            siblings,
+        }
+    }
+}
+
+impl SparseMerkleProof {
+    /// 校验证明
+    ///
+    /// `value_hash` 为 Some 时校验键存在且值哈希一致，为 None 时校验键不存在。
+    pub fn verify(&self, root: &Hash256, key: &Hash256, value_hash: Option<&Hash256>) -> bool {
+        if self.siblings.len() > KEY_BITS {
+            return false;
+        }
+        let path_ok = match (&self.leaf, value_hash) {
+            (Some((leaf_key, leaf_value)), Some(value_hash)) => leaf_key == key && leaf_value == value_hash,
+            // 终点是另一个键的叶子：该叶子必须位于同一路径上
+            (Some((leaf_key, _)), None) => {
+                leaf_key != key && common_prefix_len(leaf_key, key) >= self.siblings.len()
+            }
+            (None, None) => true,
+            (None, Some(_)) => false,
+        };
+        if !path_ok {
+            return false;
+        }
+
+        let mut hash = self
+            .leaf
+            .map_or(Hash256::ZERO, |(leaf_key, leaf_value)| hash_leaf(&leaf_key, &leaf_value));
+        for (depth, sibling) in self.siblings.iter().enumerate().rev() {
+            hash = if bit(key, depth) {
+                hash_node(sibling, &hash)
+            } else {
+                hash_node(&hash, sibling)
+            };
+        }
+        hash == *root
+    }
+}
+
+/// 保存在键值存储中的稀疏默克尔树节点的键前缀：`smt/<节点哈希>` -> 节点编码
+const STORED_NODE_PREFIX: &[u8] = b"smt/";
+
+// 保存在键值存储中的节点，编码为 前缀字节 + 两个哈希
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StoredNode {
+    // (键, 值哈希)
+    Leaf(Hash256, Hash256),
+    // (左子树, 右子树)，空子树为全零哈希
+    Internal(Hash256, Hash256),
+}
+
+impl StoredNode {
+    fn hash(&self) -> Hash256 {
+        match self {
+            StoredNode::Leaf(key, value_hash) => hash_leaf(key, value_hash),
+            StoredNode::Internal(left, right) => hash_node(left, right),
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let (prefix, a, b) = match self {
+            StoredNode::Leaf(key, value_hash) => (LEAF_PREFIX, key, value_hash),
+            StoredNode::Internal(left, right) => (NODE_PREFIX, left, right),
+        };
+        [&[prefix][..], a.as_bytes(), b.as_bytes()].concat()
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != 1 + 2 * Hash256::LEN {
+            return None;
+        }
+        let a = Hash256::from_array(bytes[1..1 + Hash256::LEN].try_into().ok()?);
+        let b = Hash256::from_array(bytes[1 + Hash256::LEN..].try_into().ok()?);
+        match bytes[0] {
+            LEAF_PREFIX => Some(StoredNode::Leaf(a, b)),
+            NODE_PREFIX => Some(StoredNode::Internal(a, b)),
+            _ => None,
+        }
+    }
+}
+
+fn stored_node_key(hash: &Hash256) -> Vec<u8> {
+    [STORED_NODE_PREFIX, hash.as_bytes()].concat()
+}
+
+/// 对保存在键值存储中的稀疏默克尔树的一组修改
+///
+/// 树的结构和根与 `SparseMerkleTree` 完全一致，节点按哈希保存。每次修改只重写从根到该叶子路径上的节点，
+/// 新节点先保存在内存中，由 `commit` 写入批次。旧节点不会被删除：回滚时恢复原来的叶子即得到原来的根，
+/// 所需节点都还在存储中。
+#[derive(Debug)]
+pub struct StoredSmtUpdate<'a, K> {
+    kv: &'a K,
+    root: Hash256,
+    nodes: HashMap<Hash256, StoredNode>,
+}
+
+impl<'a, K: KvStore> StoredSmtUpdate<'a, K> {
+    /// 从根为 `root` 的已保存的树开始修改，空树的根为全零哈希
+    pub fn new(kv: &'a K, root: Hash256) -> Self {
+        StoredSmtUpdate {
+            kv,
+            root,
+            nodes: HashMap::new(),
+        }
+    }
+
+    /// 修改后的树根
+    pub fn root(&self) -> Hash256 {
+        self.root
+    }
+
+    // 读取节点，新节点优先；空子树返回 None
+    fn node(&self, hash: &Hash256) -> Result<Option<StoredNode>, StorageError> {
+        if *hash == Hash256::ZERO {
+            return Ok(None);
+        }
+        if let Some(node) = self.nodes.get(hash) {
+            return Ok(Some(*node));
+        }
+        load_node(self.kv, hash).map(Some)
+    }
+
+    fn put(&mut self, node: StoredNode) -> Hash256 {
+        let hash = node.hash();
+        self.nodes.insert(hash, node);
+        hash
+    }
+
+    // 按键的第 depth 位把两个子树放到左右两侧
+    fn put_children(&mut self, key: &Hash256, depth: usize, child: Hash256, sibling: Hash256) -> Hash256 {
+        if bit(key, depth) {
+            self.put(StoredNode::Internal(sibling, child))
+        } else {
+            self.put(StoredNode::Internal(child, sibling))
+        }
+    }
+
+    /// 写入或更新叶子
+    pub fn insert(&mut self, key: Hash256, value_hash: Hash256) -> Result<(), StorageError> {
+        self.root = self.insert_at(self.root, 0, &key, &value_hash)?;
+        Ok(())
+    }
+
+    fn insert_at(&mut self, hash: Hash256, depth: usize, key: &Hash256, value_hash: &Hash256) -> Result<Hash256, StorageError> {
+        match self.node(&hash)? {
+            None => Ok(self.put(StoredNode::Leaf(*key, *value_hash))),
+            Some(StoredNode::Leaf(leaf_key, _)) if leaf_key == *key => Ok(self.put(StoredNode::Leaf(*key, *value_hash))),
+            Some(StoredNode::Leaf(leaf_key, _)) => {
+                // 两个叶子共享前缀的部分每层只有一侧非空
+                let new_leaf = self.put(StoredNode::Leaf(*key, *value_hash));
+                let split = common_prefix_len(key, &leaf_key);
+                let mut subtree = self.put_children(key, split, new_leaf, hash);
+                for depth in (depth..split).rev() {
+                    subtree = self.put_children(key, depth, subtree, Hash256::ZERO);
+                }
+                Ok(subtree)
+            }
+            Some(StoredNode::Internal(left, right)) => {
+                let (child, sibling) = if bit(key, depth) { (right, left) } else { (left, right) };
+                let child = self.insert_at(child, depth + 1, key, value_hash)?;
+                Ok(self.put_children(key, depth, child, sibling))
+            }
+        }
+    }
+
+    /// 删除叶子，键不存在时不做修改
+    pub fn remove(&mut self, key: &Hash256) -> Result<(), StorageError> {
+        self.root = self.remove_at(self.root, 0, key)?;
+        Ok(())
+    }
+
+    fn remove_at(&mut self, hash: Hash256, depth: usize, key: &Hash256) -> Result<Hash256, StorageError> {
+        match self.node(&hash)? {
+            None => Ok(hash),
+            Some(StoredNode::Leaf(leaf_key, _)) => Ok(if leaf_key == *key { Hash256::ZERO } else { hash }),
+            Some(StoredNode::Internal(left, right)) => {
+                let (child, sibling) = if bit(key, depth) { (right, left) } else { (left, right) };
+                let child = self.remove_at(child, depth + 1, key)?;
+                // 只剩一个叶子的子树直接用该叶子表示
+                let remaining = match (child == Hash256::ZERO, sibling == Hash256::ZERO) {
+                    (true, _) => Some(sibling),
+                    (_, true) => Some(child),
+                    _ => None,
+                };
+                if let Some(remaining) = remaining {
+                    if let Some(StoredNode::Leaf(..)) | None = self.node(&remaining)? {
+                        return Ok(remaining);
+                    }
+                }
+                Ok(self.put_children(key, depth, child, sibling))
+            }
+        }
+    }
+
+    /// 把新节点写入批次，返回修改后的树根
+    pub fn commit(self, batch: &mut WriteBatch) -> Hash256 {
+        for (hash, node) in self.nodes {
+            batch.put(stored_node_key(&hash), node.encode());
+        }
+        self.root
+    }
+}
+
+fn load_node<K: KvStore>(kv: &K, hash: &Hash256) -> Result<StoredNode, StorageError> {
+    let bytes = kv
+        .get(&stored_node_key(hash))?
+        .ok_or_else(|| StorageError::Corrupted(format!("missing state tree node {}", hash)))?;
+    StoredNode::decode(&bytes).ok_or_else(|| StorageError::Corrupted(format!("invalid state tree node {}", hash)))
+}
+
+/// 在根为 `root` 的已保存的树中生成键的证明，与 `SparseMerkleTree::prove` 的结果相同
+pub fn prove_stored<K: KvStore>(kv: &K, root: &Hash256, key: &Hash256) -> Result<SparseMerkleProof, StorageError> {
+    let mut hash = *root;
+    let mut siblings = Vec::new();
+    let mut depth = 0;
+    loop {
+        if hash == Hash256::ZERO {
+            return Ok(SparseMerkleProof { leaf: None, siblings });
+        }
+        match load_node(kv, &hash)? {
+            StoredNode::Leaf(leaf_key, value_hash) => {
+                return Ok(SparseMerkleProof {
+                    leaf: Some((leaf_key, value_hash)),
+                    siblings,
+                })
+            }
+            StoredNode::Internal(left, right) => {
+                if depth >= KEY_BITS {
+                    return Err(StorageError::Corrupted("state tree is too deep".to_string()));
+                }
+                let (child, sibling) = if bit(key, depth) { (right, left) } else { (left, right) };
+                siblings.push(sibling);
+                hash = child;
+                depth += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::kv::MemoryKvStore;
+    use proptest::prelude::*;
+    use std::collections::BTreeMap;
+
+    fn key(i: u64) -> Hash256 {
+        Hash256::double_sha256(&i.to_be_bytes())
+    }
+
+    fn value(i: u64) -> Hash256 {
+        Hash256::double_sha256(&(i + 1_000_000).to_be_bytes())
+    }
+
+    #[test]
+    fn test_small_trees() {
+        let empty = SparseMerkleTree::default();
+        assert_eq!(empty.root(), Hash256::ZERO);
+        assert!(empty.prove(&key(1)).verify(&Hash256::ZERO, &key(1), None));
+
+        let single = SparseMerkleTree::new([(key(1), value(1))]);
+        assert_eq!(single.root(), hash_leaf(&key(1), &value(1)));
+        assert!(single.prove(&key(1)).verify(&single.root(), &key(1), Some(&value(1))));
+        assert!(single.prove(&key(2)).verify(&single.root(), &key(2), None));
+        assert!(!single.prove(&key(1)).verify(&single.root(), &key(1), None));
+
+        // 根与插入顺序无关，重复的键保留最后一个值
+        let a = SparseMerkleTree::new([(key(1), value(1)), (key(2), value(2))]);
+        let b = SparseMerkleTree::new([(key(2), value(2)), (key(1), value(0)), (key(1), value(1))]);
+        assert_eq!(a.root(), b.root());
+        assert_eq!(b.len(), 2);
+    }
+
+    #[test]
+    fn test_forged_proofs_are_rejected() {
+        let tree = SparseMerkleTree::new((0..10).map(|i| (key(i), value(i))));
+        let root = tree.root();
+        let proof = tree.prove(&key(3));
+
+        // 错误的值、把存在的键说成不存在、附加多余的兄弟节点
+        assert!(!proof.verify(&root, &key(3), Some(&value(4))));
+        assert!(!proof.verify(&root, &key(3), None));
+        let mut extended = proof.clone();
+        extended.siblings.push(Hash256::ZERO);
+        assert!(!extended.verify(&root, &key(3), Some(&value(3))));
+
+        // 用另一个键的存在证明冒充本键的不存在证明
+        let other = SparseMerkleProof {
+            leaf: Some((key(3), value(3))),
+            ..proof.clone()
+        };
+        assert!(!other.verify(&root, &key(4), None));
+    }
+
+    #[test]
+    fn test_stored_tree_updates() {
+        let mut kv = MemoryKvStore::new();
+        let mut update = StoredSmtUpdate::new(&kv, Hash256::ZERO);
+        for i in 0..10 {
+            update.insert(key(i), value(i)).unwrap();
+        }
+        let mut batch = WriteBatch::new();
+        let root = update.commit(&mut batch);
+        kv.write_batch(batch).unwrap();
+        let tree = SparseMerkleTree::new((0..10).map(|i| (key(i), value(i))));
+        assert_eq!(root, tree.root());
+        assert_eq!(prove_stored(&kv, &root, &key(3)).unwrap(), tree.prove(&key(3)));
+        assert_eq!(prove_stored(&kv, &root, &key(42)).unwrap(), tree.prove(&key(42)));
+
+        // 未提交的修改不影响已保存的树，恢复原来的叶子得到原来的根
+        let mut update = StoredSmtUpdate::new(&kv, root);
+        update.insert(key(3), value(30)).unwrap();
+        update.remove(&key(4)).unwrap();
+        assert_ne!(update.root(), root);
+        update.insert(key(4), value(4)).unwrap();
+        update.insert(key(3), value(3)).unwrap();
+        assert_eq!(update.root(), root);
+
+        // 删除全部叶子后回到空树；缺少节点时报告存储损坏
+        let mut update = StoredSmtUpdate::new(&kv, root);
+        for i in 0..10 {
+            update.remove(&key(i)).unwrap();
+        }
+        assert_eq!(update.root(), Hash256::ZERO);
+        assert!(matches!(
+            prove_stored(&kv, &value(0), &key(0)),
+            Err(StorageError::Corrupted(_))
+        ));
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn prop_stored_tree_matches_in_memory_tree(
+            batches in prop::collection::vec(prop::collection::vec((0u64..40, prop::option::of(0u64..5)), 0..20), 1..6)
+        ) {
+            let mut kv = MemoryKvStore::new();
+            let mut root = Hash256::ZERO;
+            let mut leaves = BTreeMap::new();
+            for ops in batches {
+                let mut update = StoredSmtUpdate::new(&kv, root);
+                for (i, v) in ops {
+                    match v {
+                        Some(v) => {
+                            update.insert(key(i), value(v)).unwrap();
+                            leaves.insert(key(i), value(v));
+                        }
+                        None => {
+                            update.remove(&key(i)).unwrap();
+                            leaves.remove(&key(i));
+                        }
+                    }
+                }
+                let mut batch = WriteBatch::new();
+                root = update.commit(&mut batch);
+                kv.write_batch(batch).unwrap();
+
+                let tree = SparseMerkleTree::new(leaves.iter().map(|(k, v)| (*k, *v)));
+                prop_assert_eq!(root, tree.root());
+                for i in 0..40 {
+                    prop_assert_eq!(prove_stored(&kv, &root, &key(i)).unwrap(), tree.prove(&key(i)));
+                }
+            }
+        }
+
+        #[test]
+        fn prop_proofs_verify(present in prop::collection::btree_set(0u64..1000, 0..60), absent in 1000u64..2000) {
+            let tree = SparseMerkleTree::new(present.iter().map(|&i| (key(i), value(i))));
+            let root = tree.root();
+            for &i in &present {
+                prop_assert!(tree.prove(&key(i)).verify(&root, &key(i), Some(&value(i))));
+            }
+            let proof = tree.prove(&key(absent));
+            prop_assert!(proof.verify(&root, &key(absent), None));
+            prop_assert!(!proof.verify(&root, &key(absent), Some(&value(absent))));
+        }
+    }
+}
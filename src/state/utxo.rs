@@ -0,0 +1,841 @@
+use crate::chain::{BlockHash, BlockHeader, ChainSpec};
+use crate::state::error::StateError;
+use crate::state::ledger::Ledger;
+use crate::storage::kv::{KvStore, WriteBatch};
+use crate::storage::StorageError;
+use crate::types::encoding::Reader;
+use crate::types::{Address, Amount, DecodeError, Hash256, KeyPair, NetworkKind, PublicKey, Signature, TxId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// 输出位置：交易 ID + 输出序号
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct OutPoint {
+    /// 创建该输出的交易
+    pub txid: TxId,
+    /// 输出在交易中的序号
+    pub index: u32,
+}
+
+/// 交易输出
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxOut {
+    /// 金额
+    pub value: Amount,
+    /// 接收地址
+    pub address: Address,
+}
+
+/// 输入的见证：花费者的公钥及其对交易签名原文的签名
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Witness {
+    /// 公钥，其地址必须是被花费输出的接收地址
+    pub public_key: PublicKey,
+    /// 对 `UtxoTransaction::signing_preimage` 的签名
+    pub signature: Signature,
+}
+
+/// UTXO 模型下的交易：花费若干已有输出，创建若干新输出
+///
+/// 没有输入的交易为发行交易（coinbase），其输出总额受出块奖励和手续费约束。
+/// 发行交易记录所在区块的高度，保证不同区块中内容相同的发行交易有不同的交易 ID。
+/// 每个输入都要有一个见证证明花费者持有被花费输出的地址；见证不计入交易 ID。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UtxoTransaction {
+    /// 花费的输出
+    pub inputs: Vec<OutPoint>,
+    /// 新创建的输出
+    pub outputs: Vec<TxOut>,
+    /// 发行交易所在区块的高度，普通交易必须为 0
+    pub coinbase_height: u64,
+    /// 与输入一一对应的见证
+    pub witnesses: Vec<Witness>,
+}
+
+/// 断开区块所需的撤销数据：区块花费的所有输出，按花费顺序排列
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockUndo {
+    /// 被花费的输出及其内容
+    pub spent: Vec<(OutPoint, TxOut)>,
+}
+
+impl OutPoint {
+    /// 规范二进制编码的长度（字节）
+    pub const ENCODED_LEN: usize = Hash256::LEN + 4;
+
+    /// 规范二进制编码：交易 ID + 序号
+    pub fn to_bytes(&self) -> [u8; OutPoint::ENCODED_LEN] {
+        let mut bytes = [0u8; Self::ENCODED_LEN];
+        bytes[..Hash256::LEN].copy_from_slice(self.txid.as_bytes());
+        bytes[Hash256::LEN..].copy_from_slice(&self.index.to_be_bytes());
+        bytes
+    }
+
+    // 从 reader 中读取输出位置
+    fn read_from(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(OutPoint {
+            txid: TxId::from_array(reader.read_array()?),
+            index: reader.read_u32()?,
+        })
+    }
+}
+
+impl fmt::Display for OutPoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.txid, self.index)
+    }
+}
+
+impl TxOut {
+    /// 规范二进制编码的长度（字节）
+    pub const ENCODED_LEN: usize = Amount::ENCODED_LEN + Address::ENCODED_LEN;
+
+    /// 规范二进制编码：金额 + 地址
+    pub fn to_bytes(&self) -> [u8; TxOut::ENCODED_LEN] {
+        let mut bytes = [0u8; Self::ENCODED_LEN];
+        bytes[..Amount::ENCODED_LEN].copy_from_slice(&self.value.to_be_bytes());
+        bytes[Amount::ENCODED_LEN..].copy_from_slice(&self.address.to_bytes());
+        bytes
+    }
+
+    /// 从规范二进制编码解码
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader = Reader::new(bytes);
+        let output = TxOut {
+            value: reader.read_amount()?,
+            address: Address::from_bytes(reader.read_bytes(Address::ENCODED_LEN)?).map_err(DecodeError::Invalid)?,
+        };
+        reader.finish()?;
+        Ok(output)
+    }
+}
+
+impl BlockUndo {
+    // 编码：数量（u32） + 每项（输出位置，输出内容）
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(4 + self.spent.len() * (OutPoint::ENCODED_LEN + TxOut::ENCODED_LEN));
+        bytes.extend_from_slice(&(self.spent.len() as u32).to_be_bytes());
+        for (outpoint, output) in &self.spent {
+            bytes.extend_from_slice(&outpoint.to_bytes());
+            bytes.extend_from_slice(&output.to_bytes());
+        }
+        bytes
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader = Reader::new(bytes);
+        let count = reader.read_u32()? as usize;
+        if reader.remaining() != count * (OutPoint::ENCODED_LEN + TxOut::ENCODED_LEN) {
+            return Err(DecodeError::Invalid("Invalid undo record length"));
+        }
+        let mut spent = Vec::with_capacity(count);
+        for _ in 0..count {
+            let outpoint = OutPoint::read_from(&mut reader)?;
+            let output = TxOut::from_bytes(reader.read_bytes(TxOut::ENCODED_LEN)?)?;
+            spent.push((outpoint, output));
+        }
+        reader.finish()?;
+        Ok(BlockUndo { spent })
+    }
+}
+
+impl UtxoTransaction {
+    /// 签名原文的域分隔前缀
+    pub const SIGNING_DOMAIN: &'static [u8] = b"FAIC-UTXO-TX-V1";
+
+    /// 规范二进制编码（不含见证）：输入数（u32） + 输入 + 输出数（u32） + 输出 + 发行高度（u64）
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            16 + self.inputs.len() * OutPoint::ENCODED_LEN + self.outputs.len() * TxOut::ENCODED_LEN,
+        );
+        bytes.extend_from_slice(&(self.inputs.len() as u32).to_be_bytes());
+        for input in &self.inputs {
+            bytes.extend_from_slice(&input.to_bytes());
+        }
+        bytes.extend_from_slice(&(self.outputs.len() as u32).to_be_bytes());
+        for output in &self.outputs {
+            bytes.extend_from_slice(&output.to_bytes());
+        }
+        bytes.extend_from_slice(&self.coinbase_height.to_be_bytes());
+        bytes
+    }
+
+    /// 交易 ID：规范编码的双重 SHA-256
+    pub fn txid(&self) -> TxId {
+        TxId::double_sha256(&self.encode())
+    }
+
+    /// 签名原文：域分隔前缀 + 链 ID + 不含见证的规范编码
+    pub fn signing_preimage(&self, chain_id: u32) -> Vec<u8> {
+        let mut preimage = Self::SIGNING_DOMAIN.to_vec();
+        preimage.extend_from_slice(&chain_id.to_be_bytes());
+        preimage.extend_from_slice(&self.encode());
+        preimage
+    }
+
+    /// 用与输入一一对应的密钥对签名，替换已有的见证
+    pub fn sign(&mut self, chain_id: u32, keypairs: &[&KeyPair]) {
+        let preimage = self.signing_preimage(chain_id);
+        self.witnesses = keypairs
+            .iter()
+            .map(|keypair| Witness {
+                public_key: keypair.public_key(),
+                signature: keypair.sign(&preimage),
+            })
+            .collect();
+    }
+
+    /// 是否为发行交易
+    pub fn is_coinbase(&self) -> bool {
+        self.inputs.is_empty()
+    }
+}
+
+// 区块内修改的暂存视图：记录每个被触及输出的原始内容和当前内容
+struct UtxoView<'a, K> {
+    set: &'a UtxoSet<K>,
+    changes: HashMap<OutPoint, (Option<TxOut>, Option<TxOut>)>,
+}
+
+impl<'a, K: KvStore> UtxoView<'a, K> {
+    fn new(set: &'a UtxoSet<K>) -> Self {
+        UtxoView {
+            set,
+            changes: HashMap::new(),
+        }
+    }
+
+    fn entry(&mut self, outpoint: &OutPoint) -> Result<&mut (Option<TxOut>, Option<TxOut>), StateError> {
+        if !self.changes.contains_key(outpoint) {
+            let original = self.set.get(outpoint)?;
+            self.changes.insert(*outpoint, (original, original));
+        }
+        Ok(self.changes.get_mut(outpoint).unwrap())
+    }
+
+    // 新增输出，已存在时返回错误
+    fn add(&mut self, outpoint: OutPoint, output: TxOut) -> Result<(), StateError> {
+        let (_, current) = self.entry(&outpoint)?;
+        if current.is_some() {
+            return Err(StateError::DuplicateOutput(outpoint));
+        }
+        *current = Some(output);
+        Ok(())
+    }
+
+    // 花费输出，返回其内容
+    fn spend(&mut self, outpoint: &OutPoint) -> Result<TxOut, StateError> {
+        let (_, current) = self.entry(outpoint)?;
+        current.take().ok_or(StateError::MissingOutput(*outpoint))
+    }
+
+    // 把净修改写入批次
+    fn commit(self, batch: &mut WriteBatch) {
+        for (outpoint, (original, current)) in self.changes {
+            if original == current {
+                continue;
+            }
+            if let Some(original) = original {
+                batch.delete(UtxoSet::<K>::output_key(&outpoint));
+                batch.delete(UtxoSet::<K>::address_key(&original.address, &outpoint));
+            }
+            if let Some(current) = current {
+                batch.put(UtxoSet::<K>::output_key(&outpoint), current.to_bytes());
+                batch.put(
+                    UtxoSet::<K>::address_key(&current.address, &outpoint),
+                    current.value.to_be_bytes(),
+                );
+            }
+        }
+    }
+}
+
+/// 未花费输出集合
+///
+/// 键值存储中的布局：
+/// - `utxo/<输出位置>` -> 输出内容
+/// - `address/<地址><输出位置>` -> 金额，用于按地址查询余额
+/// - `meta/supply` -> 所有未花费输出的总额
+/// - `meta/tip` -> 最近连接的区块哈希和高度
+/// - `undo/<区块哈希>` -> 断开该区块所需的撤销数据
+///
+/// 连接和断开区块都先在内存中计算全部修改，再与撤销数据和末端一起作为一个批次写入。
+#[derive(Debug)]
+pub struct UtxoSet<K> {
+    kv: K,
+    chain_id: u32,
+    network: NetworkKind,
+}
+
+impl<K: KvStore> UtxoSet<K> {
+    const OUTPUT_PREFIX: &'static [u8] = b"utxo/";
+    const ADDRESS_PREFIX: &'static [u8] = b"address/";
+    const SUPPLY_KEY: &'static [u8] = b"meta/supply";
+    const TIP_KEY: &'static [u8] = b"meta/tip";
+    const UNDO_PREFIX: &'static [u8] = b"undo/";
+
+    /// 在键值存储之上创建 UTXO 集合
+    pub fn new(kv: K, spec: &ChainSpec) -> Self {
+        UtxoSet {
+            kv,
+            chain_id: spec.chain_id,
+            network: spec.network,
+        }
+    }
+
+    fn output_key(outpoint: &OutPoint) -> Vec<u8> {
+        [Self::OUTPUT_PREFIX, &outpoint.to_bytes()[..]].concat()
+    }
+
+    fn address_prefix(address: &Address) -> Vec<u8> {
+        [Self::ADDRESS_PREFIX, &address.to_bytes()[..]].concat()
+    }
+
+    fn address_key(address: &Address, outpoint: &OutPoint) -> Vec<u8> {
+        [Self::address_prefix(address), outpoint.to_bytes().to_vec()].concat()
+    }
+
+    fn undo_key(hash: &BlockHash) -> Vec<u8> {
+        [Self::UNDO_PREFIX, &hash.as_bytes()[..]].concat()
+    }
+
+    /// 末端：最近连接的区块哈希和高度
+    pub fn tip(&self) -> Result<Option<(BlockHash, u64)>, StateError> {
+        let Some(bytes) = self.kv.get(Self::TIP_KEY)? else {
+            return Ok(None);
+        };
+        let mut reader = Reader::new(&bytes);
+        let hash = BlockHash::from_array(reader.read_array().map_err(StorageError::from)?);
+        let height = reader.read_u64().map_err(StorageError::from)?;
+        reader.finish().map_err(StorageError::from)?;
+        Ok(Some((hash, height)))
+    }
+
+    // 把末端写入批次
+    fn put_tip(batch: &mut WriteBatch, hash: &BlockHash, height: u64) {
+        batch.put(Self::TIP_KEY, [&hash.as_bytes()[..], &height.to_be_bytes()].concat());
+    }
+
+    /// 读取区块的撤销数据
+    pub fn undo(&self, hash: &BlockHash) -> Result<Option<BlockUndo>, StateError> {
+        match self.kv.get(&Self::undo_key(hash))? {
+            Some(bytes) => Ok(Some(BlockUndo::decode(&bytes).map_err(StorageError::from)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// 查询未花费输出
+    pub fn get(&self, outpoint: &OutPoint) -> Result<Option<TxOut>, StateError> {
+        match self.kv.get(&Self::output_key(outpoint))? {
+            Some(bytes) => Ok(Some(TxOut::from_bytes(&bytes).map_err(StorageError::from)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// 地址持有的所有未花费输出
+    pub fn outputs_of(&self, address: &Address) -> Result<Vec<(OutPoint, Amount)>, StateError> {
+        let prefix = Self::address_prefix(address);
+        let mut outputs = Vec::new();
+        for (key, value) in self.kv.scan_prefix(&prefix)? {
+            let mut reader = Reader::new(&key[prefix.len()..]);
+            let outpoint = OutPoint::read_from(&mut reader).map_err(StorageError::from)?;
+            let value = Amount::from_be_bytes(&value)
+                .map_err(|e| StorageError::Decode(DecodeError::Invalid(e)))?;
+            outputs.push((outpoint, value));
+        }
+        Ok(outputs)
+    }
+
+    /// 地址余额：其所有未花费输出之和
+    pub fn balance(&self, address: &Address) -> Result<Amount, StateError> {
+        self.outputs_of(address)?
+            .iter()
+            .try_fold(Amount::zero(), |total, (_, value)| total.checked_add(value))
+            .ok_or(StateError::BalanceOverflow(*address))
+    }
+
+    /// 记录的流通总量（所有未花费输出之和）
+    pub fn total_supply(&self) -> Result<Amount, StateError> {
+        match self.kv.get(Self::SUPPLY_KEY)? {
+            Some(bytes) => Ok(Amount::from_be_bytes(&bytes)
+                .map_err(|e| StorageError::Decode(DecodeError::Invalid(e)))?),
+            None => Ok(Amount::zero()),
+        }
+    }
+
+    /// 审计流通总量：遍历所有未花费输出和地址索引，检查两者之和都与记录的总量一致
+    pub fn audit_supply(&self) -> Result<Amount, StateError> {
+        let tracked = self.total_supply()?;
+        let mut outputs_total = Amount::zero();
+        for (_, bytes) in self.kv.scan_prefix(Self::OUTPUT_PREFIX)? {
+            let output = TxOut::from_bytes(&bytes).map_err(StorageError::from)?;
+            outputs_total = outputs_total
+                .checked_add(&output.value)
+                .ok_or(StateError::SupplyMismatch { tracked, actual: Amount::max_value() })?;
+        }
+        if outputs_total != tracked {
+            return Err(StateError::SupplyMismatch { tracked, actual: outputs_total });
+        }
+
+        let mut index_total = Amount::zero();
+        for (_, bytes) in self.kv.scan_prefix(Self::ADDRESS_PREFIX)? {
+            let value = Amount::from_be_bytes(&bytes).map_err(|e| StorageError::Decode(DecodeError::Invalid(e)))?;
+            index_total = index_total
+                .checked_add(&value)
+                .ok_or(StateError::SupplyMismatch { tracked, actual: Amount::max_value() })?;
+        }
+        if index_total != tracked {
+            return Err(StateError::SupplyMismatch { tracked, actual: index_total });
+        }
+        Ok(tracked)
+    }
+
+    // 按 delta 调整流通总量并写入批次
+    fn put_supply(&self, batch: &mut WriteBatch, added: Amount, removed: Amount) -> Result<(), StateError> {
+        let tracked = self.total_supply()?;
+        let supply = tracked
+            .checked_add(&added)
+            .and_then(|total| total.checked_sub(&removed))
+            .ok_or(StateError::SupplyMismatch { tracked, actual: Amount::zero() })?;
+        batch.put(Self::SUPPLY_KEY, supply.to_be_bytes());
+        Ok(())
+    }
+
+    /// 连接一个区块：依次花费每笔交易的输入并创建其输出
+    ///
+    /// 区块必须是当前末端的子区块，集合为空时必须是创世区块。每个输入的见证公钥必须对应
+    /// 被花费输出的地址，且签名对本链的签名原文有效。普通交易的输出总额不能超过
+    /// 输入总额，差额为手续费；发行交易的输出总额不能超过 `subsidy` 加上本区块的手续费，
+    /// 且必须记录本区块的高度。同一区块内后面的交易可以花费前面交易创建的输出。
+    /// 撤销数据和新的末端与输出的修改写入同一批次，供 `disconnect_block` 回滚。
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - 区块头
+    /// * `transactions` - 区块内的交易
+    /// * `subsidy` - 本区块的出块奖励
+    pub fn connect_block(
+        &mut self,
+        header: &BlockHeader,
+        transactions: &[UtxoTransaction],
+        subsidy: Amount,
+    ) -> Result<(), StateError> {
+        match self.tip()? {
+            None if header.height != 0 => return Err(StateError::NotGenesis),
+            Some((tip_hash, tip_height))
+                if header.parent_hash != tip_hash || tip_height.checked_add(1) != Some(header.height) =>
+            {
+                return Err(StateError::WrongParent {
+                    expected: tip_hash,
+                    actual: header.parent_hash,
+                });
+            }
+            _ => {}
+        }
+
+        let mut view = UtxoView::new(self);
+        let mut undo = BlockUndo::default();
+        let mut spent_total = Amount::zero();
+        let mut created_total = Amount::zero();
+        let mut fees = Amount::zero();
+        let mut issued = Amount::zero();
+        let overflow = || StateError::ExcessIssuance { issued: Amount::max_value(), allowed: subsidy };
+
+        for transaction in transactions {
+            let txid = transaction.txid();
+            let expected_height = if transaction.is_coinbase() { header.height } else { 0 };
+            if transaction.coinbase_height != expected_height {
+                return Err(StateError::CoinbaseHeightMismatch {
+                    txid,
+                    expected: expected_height,
+                    actual: transaction.coinbase_height,
+                });
+            }
+            if transaction.witnesses.len() != transaction.inputs.len() {
+                return Err(StateError::WitnessCountMismatch {
+                    txid,
+                    inputs: transaction.inputs.len(),
+                    witnesses: transaction.witnesses.len(),
+                });
+            }
+            let preimage = transaction.signing_preimage(self.chain_id);
+            let mut inputs = Amount::zero();
+            for (input, witness) in transaction.inputs.iter().zip(&transaction.witnesses) {
+                let output = view.spend(input)?;
+                if !output.address.matches(&witness.public_key) || !witness.public_key.verify(&preimage, &witness.signature) {
+                    return Err(StateError::UnauthorizedSpend(*input));
+                }
+                inputs = inputs.checked_add(&output.value).ok_or_else(overflow)?;
+                undo.spent.push((*input, output));
+            }
+            let mut outputs = Amount::zero();
+            for (index, output) in transaction.outputs.iter().enumerate() {
+                view.add(OutPoint { txid, index: index as u32 }, *output)?;
+                outputs = outputs.checked_add(&output.value).ok_or_else(overflow)?;
+            }
+
+            if transaction.is_coinbase() {
+                issued = issued.checked_add(&outputs).ok_or_else(overflow)?;
+            } else {
+                let fee = inputs.checked_sub(&outputs).ok_or(StateError::OutputsExceedInputs(txid))?;
+                fees = fees.checked_add(&fee).ok_or_else(overflow)?;
+            }
+            spent_total = spent_total.checked_add(&inputs).ok_or_else(overflow)?;
+            created_total = created_total.checked_add(&outputs).ok_or_else(overflow)?;
+        }
+
+        let allowed = subsidy.checked_add(&fees).ok_or_else(overflow)?;
+        if issued > allowed {
+            return Err(StateError::ExcessIssuance { issued, allowed });
+        }
+
+        let hash = header.hash();
+        let mut batch = WriteBatch::new();
+        view.commit(&mut batch);
+        self.put_supply(&mut batch, created_total, spent_total)?;
+        batch.put(Self::undo_key(&hash), undo.encode());
+        Self::put_tip(&mut batch, &hash, header.height);
+        self.kv.write_batch(batch)?;
+        Ok(())
+    }
+
+    /// 断开末端区块：删除其创建的输出，并用 `connect_block` 写入的撤销数据恢复其花费的输出
+    ///
+    /// 末端退回到父区块，撤销数据被删除；断开创世区块后集合没有末端。
+    pub fn disconnect_block(&mut self, header: &BlockHeader, transactions: &[UtxoTransaction]) -> Result<(), StateError> {
+        let (tip_hash, _) = self.tip()?.ok_or(StateError::NotInitialized)?;
+        let hash = header.hash();
+        if hash != tip_hash {
+            return Err(StateError::NotTip { tip: tip_hash, block: hash });
+        }
+        let undo = self.undo(&hash)?.ok_or(StateError::MissingUndo(hash))?;
+        let mismatch = || StateError::Storage(StorageError::Corrupted("undo data does not match block".to_string()));
+        let mut view = UtxoView::new(self);
+        let mut spent = undo.spent.iter().rev();
+        let mut restored_total = Amount::zero();
+        let mut removed_total = Amount::zero();
+
+        for transaction in transactions.iter().rev() {
+            let txid = transaction.txid();
+            for index in (0..transaction.outputs.len()).rev() {
+                let output = view.spend(&OutPoint { txid, index: index as u32 })?;
+                removed_total = removed_total.checked_add(&output.value).ok_or_else(mismatch)?;
+            }
+            for input in transaction.inputs.iter().rev() {
+                let (outpoint, output) = spent.next().ok_or_else(mismatch)?;
+                if outpoint != input {
+                    return Err(mismatch());
+                }
+                view.add(*outpoint, *output)?;
+                restored_total = restored_total.checked_add(&output.value).ok_or_else(mismatch)?;
+            }
+        }
+        if spent.next().is_some() {
+            return Err(mismatch());
+        }
+
+        let mut batch = WriteBatch::new();
+        view.commit(&mut batch);
+        self.put_supply(&mut batch, restored_total, removed_total)?;
+        batch.delete(Self::undo_key(&hash));
+        match header.height.checked_sub(1) {
+            Some(height) => Self::put_tip(&mut batch, &header.parent_hash, height),
+            None => batch.delete(Self::TIP_KEY),
+        }
+        self.kv.write_batch(batch)?;
+        Ok(())
+    }
+}
+
+impl<K: KvStore> Ledger for UtxoSet<K> {
+    fn chain_id(&self) -> u32 {
+        self.chain_id
+    }
+
+    fn network(&self) -> NetworkKind {
+        self.network
+    }
+
+    fn balance(&self, address: &Address) -> Result<Amount, StateError> {
+        UtxoSet::balance(self, address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::testing::{child_header, genesis_header};
+    use crate::storage::kv::{FileKvStore, KvPairs, MemoryKvStore};
+
+    fn amount(s: &str) -> Amount {
+        s.parse().unwrap()
+    }
+
+    fn keypair(byte: u8) -> KeyPair {
+        KeyPair::from_secret_bytes(&[byte; 32])
+    }
+
+    fn address(byte: u8) -> Address {
+        Address::from_public_key(&keypair(byte).public_key(), NetworkKind::Regtest)
+    }
+
+    fn output(value: &str, owner: u8) -> TxOut {
+        TxOut {
+            value: amount(value),
+            address: address(owner),
+        }
+    }
+
+    fn coinbase(value: &str, owner: u8, height: u64) -> UtxoTransaction {
+        UtxoTransaction {
+            inputs: Vec::new(),
+            outputs: vec![output(value, owner)],
+            coinbase_height: height,
+            witnesses: Vec::new(),
+        }
+    }
+
+    // 由 owner 对应的密钥对签名所有输入
+    fn transfer(owner: u8, inputs: Vec<OutPoint>, outputs: Vec<TxOut>) -> UtxoTransaction {
+        let mut transaction = UtxoTransaction {
+            inputs,
+            outputs,
+            coinbase_height: 0,
+            witnesses: Vec::new(),
+        };
+        let signer = keypair(owner);
+        let keypairs = vec![&signer; transaction.inputs.len()];
+        transaction.sign(ChainSpec::regtest().chain_id, &keypairs);
+        transaction
+    }
+
+    // parent 为 None 时构造创世区块头
+    fn header(parent: Option<&BlockHeader>) -> BlockHeader {
+        parent.map_or_else(|| genesis_header(0), |parent| child_header(parent, 60))
+    }
+
+    fn snapshot(set: &UtxoSet<MemoryKvStore>) -> KvPairs {
+        set.kv.scan_prefix(b"").unwrap()
+    }
+
+    #[test]
+    fn test_connect_and_disconnect() {
+        let mut set = UtxoSet::new(MemoryKvStore::new(), &ChainSpec::regtest());
+        let subsidy = amount("50");
+
+        // 区块 0：发行 50 给地址 1
+        let header0 = header(None);
+        let block0 = vec![coinbase("50", 1, 0)];
+        set.connect_block(&header0, &block0, subsidy).unwrap();
+        let funding = OutPoint { txid: block0[0].txid(), index: 0 };
+        assert_eq!(set.balance(&address(1)).unwrap(), amount("50"));
+        assert_eq!(set.audit_supply().unwrap(), amount("50"));
+        assert_eq!(set.tip().unwrap(), Some((header0.hash(), 0)));
+        let after_block0 = snapshot(&set);
+
+        // 区块 1：地址 1 转 20 给地址 2，找零 29，手续费 1 由发行交易领取；
+        // 地址 2 在同一区块内把收到的输出再转给地址 3
+        let payment = transfer(1, vec![funding], vec![output("20", 2), output("29", 1)]);
+        let forward = transfer(2, vec![OutPoint { txid: payment.txid(), index: 0 }], vec![output("20", 3)]);
+        let header1 = header(Some(&header0));
+        let block1 = vec![coinbase("51", 4, 1), payment, forward];
+        set.connect_block(&header1, &block1, subsidy).unwrap();
+        assert_eq!(set.undo(&header1.hash()).unwrap().unwrap().spent.len(), 2);
+        assert_eq!(set.tip().unwrap(), Some((header1.hash(), 1)));
+        assert_eq!(set.balance(&address(1)).unwrap(), amount("29"));
+        assert_eq!(set.balance(&address(2)).unwrap(), Amount::zero());
+        assert_eq!(set.balance(&address(3)).unwrap(), amount("20"));
+        assert_eq!(set.balance(&address(4)).unwrap(), amount("51"));
+        assert_eq!(set.get(&funding).unwrap(), None);
+        assert_eq!(set.audit_supply().unwrap(), amount("100"));
+
+        // 只能断开末端区块
+        assert!(matches!(
+            set.disconnect_block(&header0, &block0),
+            Err(StateError::NotTip { .. })
+        ));
+
+        // 断开区块 1 后存储内容与之前完全一致，撤销数据被删除
+        set.disconnect_block(&header1, &block1).unwrap();
+        assert_eq!(snapshot(&set), after_block0);
+        assert_eq!(set.audit_supply().unwrap(), amount("50"));
+        assert_eq!(set.undo(&header1.hash()).unwrap(), None);
+
+        // 交易与撤销数据不匹配时拒绝断开
+        set.connect_block(&header1, &block1, subsidy).unwrap();
+        assert!(set.disconnect_block(&header1, &block1[..1]).is_err());
+
+        // 断开全部区块后回到空集合
+        set.disconnect_block(&header1, &block1).unwrap();
+        set.disconnect_block(&header0, &block0).unwrap();
+        assert_eq!(set.tip().unwrap(), None);
+        assert!(snapshot(&set).iter().all(|(key, _)| key.starts_with(b"meta/")));
+    }
+
+    #[test]
+    fn test_undo_and_tip_survive_reopen() {
+        let dir = std::path::Path::new("test_utxo_reopen_dir");
+        let _ = std::fs::remove_dir_all(dir);
+        let subsidy = amount("50");
+        let header0 = header(None);
+        let block0 = vec![coinbase("50", 1, 0)];
+        let header1 = header(Some(&header0));
+        let block1 = vec![
+            coinbase("50", 2, 1),
+            transfer(1, vec![OutPoint { txid: block0[0].txid(), index: 0 }], vec![output("50", 3)]),
+        ];
+
+        let mut set = UtxoSet::new(FileKvStore::open(dir).unwrap(), &ChainSpec::regtest());
+        set.connect_block(&header0, &block0, subsidy).unwrap();
+        set.connect_block(&header1, &block1, subsidy).unwrap();
+        drop(set);
+
+        // 重新打开后仍能断开末端区块
+        let mut set = UtxoSet::new(FileKvStore::open(dir).unwrap(), &ChainSpec::regtest());
+        assert_eq!(set.tip().unwrap(), Some((header1.hash(), 1)));
+        set.disconnect_block(&header1, &block1).unwrap();
+        assert_eq!(set.balance(&address(1)).unwrap(), amount("50"));
+        assert_eq!(set.tip().unwrap(), Some((header0.hash(), 0)));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_coinbase_height() {
+        // 内容相同、高度不同的发行交易有不同的交易 ID
+        assert_ne!(coinbase("50", 1, 1).txid(), coinbase("50", 1, 2).txid());
+
+        let mut set = UtxoSet::new(MemoryKvStore::new(), &ChainSpec::regtest());
+        let subsidy = amount("50");
+        let header0 = header(None);
+        assert!(matches!(
+            set.connect_block(&header0, &[coinbase("50", 1, 1)], subsidy),
+            Err(StateError::CoinbaseHeightMismatch { expected: 0, actual: 1, .. })
+        ));
+        let block0 = vec![coinbase("50", 1, 0)];
+        set.connect_block(&header0, &block0, subsidy).unwrap();
+
+        // 普通交易不能带有高度
+        let mut spend = transfer(1, vec![OutPoint { txid: block0[0].txid(), index: 0 }], vec![output("50", 2)]);
+        spend.coinbase_height = 1;
+        assert!(matches!(
+            set.connect_block(&header(Some(&header0)), &[spend], subsidy),
+            Err(StateError::CoinbaseHeightMismatch { expected: 0, actual: 1, .. })
+        ));
+
+        // 不是末端的子区块
+        assert!(matches!(
+            set.connect_block(&header0, &[coinbase("50", 1, 0)], subsidy),
+            Err(StateError::WrongParent { .. })
+        ));
+        let mut empty = UtxoSet::new(MemoryKvStore::new(), &ChainSpec::regtest());
+        assert!(matches!(
+            empty.connect_block(&header(Some(&header0)), &[], subsidy),
+            Err(StateError::NotGenesis)
+        ));
+    }
+
+    #[test]
+    fn test_invalid_blocks_are_rejected_atomically() {
+        let mut set = UtxoSet::new(MemoryKvStore::new(), &ChainSpec::regtest());
+        let subsidy = amount("50");
+        let header0 = header(None);
+        let block0 = vec![coinbase("50", 1, 0)];
+        set.connect_block(&header0, &block0, subsidy).unwrap();
+        let funding = OutPoint { txid: block0[0].txid(), index: 0 };
+        let header1 = header(Some(&header0));
+        let before = snapshot(&set);
+
+        // 发行超过出块奖励
+        assert!(matches!(
+            set.connect_block(&header1, &[coinbase("50.00000001", 1, 1)], subsidy),
+            Err(StateError::ExcessIssuance { .. })
+        ));
+
+        // 输出超过输入
+        let overspend = transfer(1, vec![funding], vec![output("51", 2)]);
+        assert!(matches!(
+            set.connect_block(&header1, &[overspend], subsidy),
+            Err(StateError::OutputsExceedInputs(_))
+        ));
+
+        // 双花：第一笔合法，第二笔花费同一个输出
+        let spend = |to| transfer(1, vec![funding], vec![output("50", to)]);
+        assert!(matches!(
+            set.connect_block(&header1, &[spend(2), spend(3)], subsidy),
+            Err(StateError::MissingOutput(_))
+        ));
+
+        // 同一区块内重复的发行交易
+        assert!(matches!(
+            set.connect_block(&header1, &[coinbase("25", 1, 1), coinbase("25", 1, 1)], subsidy),
+            Err(StateError::DuplicateOutput(_))
+        ));
+
+        assert_eq!(snapshot(&set), before);
+        assert_eq!(set.audit_supply().unwrap(), amount("50"));
+    }
+
+    #[test]
+    fn test_spends_require_owner_witness() {
+        let spec = ChainSpec::regtest();
+        let mut set = UtxoSet::new(MemoryKvStore::new(), &spec);
+        let subsidy = amount("50");
+        let header0 = header(None);
+        let block0 = vec![coinbase("50", 1, 0)];
+        set.connect_block(&header0, &block0, subsidy).unwrap();
+        let funding = OutPoint { txid: block0[0].txid(), index: 0 };
+        let header1 = header(Some(&header0));
+        let before = snapshot(&set);
+
+        // 用错误的密钥签名
+        assert!(matches!(
+            set.connect_block(&header1, &[transfer(2, vec![funding], vec![output("50", 2)])], subsidy),
+            Err(StateError::UnauthorizedSpend(outpoint)) if outpoint == funding
+        ));
+
+        // 公钥正确但签名后改动了输出
+        let mut tampered = transfer(1, vec![funding], vec![output("50", 2)]);
+        tampered.outputs[0].address = address(3);
+        assert!(matches!(
+            set.connect_block(&header1, &[tampered], subsidy),
+            Err(StateError::UnauthorizedSpend(_))
+        ));
+
+        // 其他链上的签名
+        let mut foreign = transfer(1, vec![funding], vec![output("50", 2)]);
+        foreign.sign(spec.chain_id.wrapping_add(1), &[&keypair(1)]);
+        assert!(matches!(
+            set.connect_block(&header1, &[foreign], subsidy),
+            Err(StateError::UnauthorizedSpend(_))
+        ));
+
+        // 缺少见证
+        let mut unsigned = transfer(1, vec![funding], vec![output("50", 2)]);
+        unsigned.witnesses.clear();
+        assert!(matches!(
+            set.connect_block(&header1, &[unsigned], subsidy),
+            Err(StateError::WitnessCountMismatch { inputs: 1, witnesses: 0, .. })
+        ));
+
+        assert_eq!(snapshot(&set), before);
+        set.connect_block(&header1, &[transfer(1, vec![funding], vec![output("50", 2)])], subsidy)
+            .unwrap();
+        assert_eq!(set.balance(&address(2)).unwrap(), amount("50"));
+    }
+
+    #[test]
+    fn test_audit_detects_inconsistent_supply() {
+        let mut set = UtxoSet::new(MemoryKvStore::new(), &ChainSpec::regtest());
+        set.connect_block(&header(None), &[coinbase("10", 1, 0)], amount("50")).unwrap();
+
+        let mut batch = WriteBatch::new();
+        batch.put(UtxoSet::<MemoryKvStore>::SUPPLY_KEY, amount("11").to_be_bytes());
+        set.kv.write_batch(batch).unwrap();
+        assert!(matches!(
+            set.audit_supply(),
+            Err(StateError::SupplyMismatch { .. })
+        ));
+    }
+}
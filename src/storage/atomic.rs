@@ -0,0 +1,78 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// 写入过程中使用的临时文件后缀
+pub(crate) const TEMP_SUFFIX: &str = ".tmp";
+
+// 临时文件路径：在原文件名后追加后缀
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(TEMP_SUFFIX);
+    path.with_file_name(name)
+}
+
+/// 原子地写入文件
+///
+/// 先写入同目录下的临时文件并刷盘，再重命名覆盖目标文件，最后刷新目录项。
+/// 进程在任意时刻崩溃，目标文件要么是旧内容，要么是完整的新内容。
+pub(crate) fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let temp = temp_path(path);
+    {
+        let mut file = File::create(&temp)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+    }
+    fs::rename(&temp, path)?;
+    sync_parent_dir(path)
+}
+
+// 刷新父目录，保证重命名操作本身落盘
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => File::open(dir)?.sync_all(),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// 删除目录中崩溃后残留的临时文件
+pub(crate) fn remove_temp_files(dir: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.to_string_lossy().ends_with(TEMP_SUFFIX) {
+            fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomic() {
+        let dir = Path::new("test_write_atomic_dir");
+        fs::create_dir_all(dir).unwrap();
+        let path = dir.join("data.bin");
+
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+
+        // 模拟写入中途崩溃：临时文件残留，目标文件保持不变
+        fs::write(temp_path(&path), b"partial").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+        remove_temp_files(dir).unwrap();
+        assert!(!temp_path(&path).exists());
+        assert!(path.exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,424 @@
+use crate::chain::{Block, BlockHash, BlockHeader};
+use crate::storage::atomic::{remove_temp_files, write_atomic};
+use crate::storage::error::StorageError;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// 区块存储
+///
+/// 区块按哈希存储，同一高度可以有多个区块（分叉）。`set_best_tip` 选定的链为主链，
+/// 按高度查询和区块头遍历都基于主链。
+pub trait BlockStore {
+    /// 保存区块，已存在时不做任何操作
+    fn put_block(&mut self, block: &Block) -> Result<(), StorageError>;
+
+    /// 按哈希读取区块
+    fn get_block(&self, hash: &BlockHash) -> Result<Option<Block>, StorageError>;
+
+    /// 按哈希读取区块头
+    fn get_header(&self, hash: &BlockHash) -> Result<Option<BlockHeader>, StorageError>;
+
+    /// 主链末端的区块哈希，尚未设置时返回 None
+    fn best_tip(&self) -> Option<BlockHash>;
+
+    /// 主链高度，尚未设置末端时返回 None
+    fn best_height(&self) -> Option<u64>;
+
+    /// 把主链末端切换到指定区块，该区块及其所有祖先必须已经保存
+    fn set_best_tip(&mut self, hash: &BlockHash) -> Result<(), StorageError>;
+
+    /// 主链上指定高度的区块哈希
+    fn hash_at_height(&self, height: u64) -> Option<BlockHash>;
+
+    /// 区块是否已保存
+    fn contains_block(&self, hash: &BlockHash) -> Result<bool, StorageError> {
+        Ok(self.get_header(hash)?.is_some())
+    }
+
+    /// 按主链高度读取区块
+    fn get_block_by_height(&self, height: u64) -> Result<Option<Block>, StorageError> {
+        match self.hash_at_height(height) {
+            Some(hash) => self.get_block(&hash),
+            None => Ok(None),
+        }
+    }
+
+    /// 从创世区块开始按高度遍历主链的区块头，不读取交易
+    fn headers(&self) -> Headers<'_, Self>
+    where
+        Self: Sized,
+    {
+        Headers {
+            store: self,
+            next_height: 0,
+        }
+    }
+}
+
+/// 主链区块头迭代器
+pub struct Headers<'a, S: BlockStore> {
+    store: &'a S,
+    next_height: u64,
+}
+
+impl<S: BlockStore> Iterator for Headers<'_, S> {
+    type Item = Result<BlockHeader, StorageError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let hash = self.store.hash_at_height(self.next_height)?;
+        self.next_height += 1;
+        Some(
+            self.store
+                .get_header(&hash)
+                .and_then(|header| header.ok_or(StorageError::UnknownBlock(hash))),
+        )
+    }
+}
+
+// 主链索引：高度 -> 区块哈希，完全由末端区块沿父哈希回溯得到
+#[derive(Debug, Default, Clone)]
+struct CanonicalChain {
+    hashes: Vec<BlockHash>,
+}
+
+impl CanonicalChain {
+    fn tip(&self) -> Option<BlockHash> {
+        self.hashes.last().copied()
+    }
+
+    fn height(&self) -> Option<u64> {
+        (self.hashes.len() as u64).checked_sub(1)
+    }
+
+    fn hash_at(&self, height: u64) -> Option<BlockHash> {
+        self.hashes.get(usize::try_from(height).ok()?).copied()
+    }
+
+    // 从新末端向前回溯到与当前主链的分叉点，返回分叉点之上的新区块（按高度升序）
+    fn new_branch<F>(&self, tip: BlockHash, mut get_header: F) -> Result<(usize, Vec<BlockHash>), StorageError>
+    where
+        F: FnMut(&BlockHash) -> Result<Option<BlockHeader>, StorageError>,
+    {
+        let mut branch = Vec::new();
+        let mut current = tip;
+        let mut expected_height = None;
+        loop {
+            let header = get_header(&current)?.ok_or(StorageError::UnknownBlock(current))?;
+            if expected_height.is_some_and(|height| height != header.height) {
+                return Err(StorageError::Corrupted(format!(
+                    "block {} has height {}, expected {}",
+                    current,
+                    header.height,
+                    expected_height.unwrap()
+                )));
+            }
+            if self.hash_at(header.height) == Some(current) {
+                branch.reverse();
+                return Ok((header.height as usize + 1, branch));
+            }
+            branch.push(current);
+            if header.height == 0 {
+                branch.reverse();
+                return Ok((0, branch));
+            }
+            expected_height = Some(header.height - 1);
+            current = header.parent_hash;
+        }
+    }
+
+    fn apply(&mut self, (fork_len, branch): (usize, Vec<BlockHash>)) {
+        self.hashes.truncate(fork_len);
+        self.hashes.extend(branch);
+    }
+}
+
+/// 内存中的区块存储，用于测试
+#[derive(Debug, Default, Clone)]
+pub struct MemoryBlockStore {
+    blocks: HashMap<BlockHash, Block>,
+    chain: CanonicalChain,
+}
+
+impl MemoryBlockStore {
+    /// 创建空的存储
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl BlockStore for MemoryBlockStore {
+    fn put_block(&mut self, block: &Block) -> Result<(), StorageError> {
+        self.blocks.entry(block.hash()).or_insert_with(|| block.clone());
+        Ok(())
+    }
+
+    fn get_block(&self, hash: &BlockHash) -> Result<Option<Block>, StorageError> {
+        Ok(self.blocks.get(hash).cloned())
+    }
+
+    fn get_header(&self, hash: &BlockHash) -> Result<Option<BlockHeader>, StorageError> {
+        Ok(self.blocks.get(hash).map(|block| block.header.clone()))
+    }
+
+    fn best_tip(&self) -> Option<BlockHash> {
+        self.chain.tip()
+    }
+
+    fn best_height(&self) -> Option<u64> {
+        self.chain.height()
+    }
+
+    fn set_best_tip(&mut self, hash: &BlockHash) -> Result<(), StorageError> {
+        let branch = self.chain.new_branch(*hash, |hash| self.get_header(hash))?;
+        self.chain.apply(branch);
+        Ok(())
+    }
+
+    fn hash_at_height(&self, height: u64) -> Option<BlockHash> {
+        self.chain.hash_at(height)
+    }
+}
+
+/// 基于文件系统的区块存储
+///
+/// 目录结构：
+/// - `blocks/<哈希>.blk`：区块的规范编码，写入后不再修改
+/// - `TIP`：主链末端的区块哈希
+///
+/// 所有文件都通过原子写入生成。主链索引不落盘，打开时从 `TIP` 沿父哈希回溯重建，
+/// 因此崩溃时最多丢失尚未写入 `TIP` 的末端切换，不会出现索引与区块不一致。
+#[derive(Debug)]
+pub struct FileBlockStore {
+    dir: PathBuf,
+    chain: CanonicalChain,
+}
+
+impl FileBlockStore {
+    const BLOCKS_DIR: &'static str = "blocks";
+    const TIP_FILE: &'static str = "TIP";
+
+    /// 打开（或创建）指定目录下的区块存储
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - 存储目录
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, StorageError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(dir.join(Self::BLOCKS_DIR))?;
+        remove_temp_files(&dir)?;
+        remove_temp_files(&dir.join(Self::BLOCKS_DIR))?;
+
+        let mut store = FileBlockStore {
+            dir,
+            chain: CanonicalChain::default(),
+        };
+        if let Some(tip) = store.read_tip()? {
+            let branch = store.chain.new_branch(tip, |hash| store.get_header(hash))?;
+            store.chain.apply(branch);
+        }
+        Ok(store)
+    }
+
+    // 区块文件路径
+    fn block_path(&self, hash: &BlockHash) -> PathBuf {
+        self.dir.join(Self::BLOCKS_DIR).join(format!("{}.blk", hash))
+    }
+
+    // 读取 TIP 文件
+    fn read_tip(&self) -> Result<Option<BlockHash>, StorageError> {
+        match fs::read(self.dir.join(Self::TIP_FILE)) {
+            Ok(bytes) => BlockHash::from_bytes(&bytes)
+                .map(Some)
+                .map_err(|e| StorageError::Corrupted(format!("tip file: {}", e))),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // 打开区块文件，不存在时返回 None
+    fn open_block_file(&self, hash: &BlockHash) -> Result<Option<File>, StorageError> {
+        match File::open(self.block_path(hash)) {
+            Ok(file) => Ok(Some(file)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // 检查文件内容与文件名中的哈希一致
+    fn check_hash(hash: &BlockHash, header: &BlockHeader) -> Result<(), StorageError> {
+        if header.hash() == *hash {
+            Ok(())
+        } else {
+            Err(StorageError::Corrupted(format!("block file {} has mismatching content", hash)))
+        }
+    }
+}
+
+impl BlockStore for FileBlockStore {
+    fn put_block(&mut self, block: &Block) -> Result<(), StorageError> {
+        let path = self.block_path(&block.hash());
+        if path.exists() {
+            return Ok(());
+        }
+        write_atomic(&path, &block.encode())?;
+        Ok(())
+    }
+
+    fn get_block(&self, hash: &BlockHash) -> Result<Option<Block>, StorageError> {
+        let Some(mut file) = self.open_block_file(hash)? else {
+            return Ok(None);
+        };
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        let block = Block::decode(&bytes)?;
+        Self::check_hash(hash, &block.header)?;
+        Ok(Some(block))
+    }
+
+    fn get_header(&self, hash: &BlockHash) -> Result<Option<BlockHeader>, StorageError> {
+        let Some(mut file) = self.open_block_file(hash)? else {
+            return Ok(None);
+        };
+        // 区块编码以区块头开头，只读取区块头部分
+        let mut bytes = [0u8; BlockHeader::ENCODED_LEN];
+        file.read_exact(&mut bytes)?;
+        let header = BlockHeader::decode(&bytes)?;
+        Self::check_hash(hash, &header)?;
+        Ok(Some(header))
+    }
+
+    fn best_tip(&self) -> Option<BlockHash> {
+        self.chain.tip()
+    }
+
+    fn best_height(&self) -> Option<u64> {
+        self.chain.height()
+    }
+
+    fn set_best_tip(&mut self, hash: &BlockHash) -> Result<(), StorageError> {
+        let branch = self.chain.new_branch(*hash, |hash| self.get_header(hash))?;
+        // 先落盘 TIP，成功后再更新内存中的索引
+        write_atomic(&self.dir.join(Self::TIP_FILE), hash.as_bytes())?;
+        self.chain.apply(branch);
+        Ok(())
+    }
+
+    fn hash_at_height(&self, height: u64) -> Option<BlockHash> {
+        self.chain.hash_at(height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::testing::{child_header, genesis_block};
+
+    // 在 parent 之上构造 count 个区块，nonce 用于区分不同分叉
+    fn extend(parent: &Block, count: usize, nonce: u64) -> Vec<Block> {
+        let mut blocks: Vec<Block> = Vec::new();
+        for _ in 0..count {
+            let parent = blocks.last().unwrap_or(parent);
+            let header = BlockHeader {
+                nonce,
+                ..child_header(&parent.header, 60)
+            };
+            blocks.push(Block::new(header, Vec::new()));
+        }
+        blocks
+    }
+
+    // 两种实现共用的行为检查
+    fn check_store<S: BlockStore>(store: &mut S) {
+        assert_eq!(store.best_tip(), None);
+        assert_eq!(store.best_height(), None);
+
+        let genesis = genesis_block(0);
+        let main = extend(&genesis, 4, 0);
+        store.put_block(&genesis).unwrap();
+        for block in &main {
+            store.put_block(block).unwrap();
+        }
+        // 重复保存不会出错
+        store.put_block(&main[0]).unwrap();
+
+        // 末端的祖先缺失时拒绝切换
+        let unknown = extend(&main[3], 1, 0).remove(0);
+        assert!(matches!(store.set_best_tip(&unknown.hash()), Err(StorageError::UnknownBlock(_))));
+
+        store.set_best_tip(&main[3].hash()).unwrap();
+        assert_eq!(store.best_tip(), Some(main[3].hash()));
+        assert_eq!(store.best_height(), Some(4));
+        assert_eq!(store.get_block(&main[1].hash()).unwrap(), Some(main[1].clone()));
+        assert_eq!(store.get_block_by_height(0).unwrap(), Some(genesis.clone()));
+        assert_eq!(store.get_block_by_height(5).unwrap(), None);
+        assert!(store.contains_block(&main[2].hash()).unwrap());
+        assert!(!store.contains_block(&unknown.hash()).unwrap());
+
+        let headers: Vec<BlockHeader> = store.headers().collect::<Result<_, _>>().unwrap();
+        assert_eq!(headers.len(), 5);
+        assert_eq!(headers[4], main[3].header);
+
+        // 切换到从高度 2 分叉的更短分支，主链索引随之更新
+        let fork = extend(&main[1], 1, 1);
+        store.put_block(&fork[0]).unwrap();
+        store.set_best_tip(&fork[0].hash()).unwrap();
+        assert_eq!(store.best_height(), Some(3));
+        assert_eq!(store.hash_at_height(2), Some(main[1].hash()));
+        assert_eq!(store.hash_at_height(3), Some(fork[0].hash()));
+        assert_eq!(store.hash_at_height(4), None);
+        // 旧分支上的区块仍可按哈希读取
+        assert_eq!(store.get_block(&main[3].hash()).unwrap(), Some(main[3].clone()));
+    }
+
+    #[test]
+    fn test_memory_block_store() {
+        check_store(&mut MemoryBlockStore::new());
+    }
+
+    #[test]
+    fn test_file_block_store() {
+        let dir = "test_file_block_store_dir";
+        let _ = fs::remove_dir_all(dir);
+        check_store(&mut FileBlockStore::open(dir).unwrap());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_block_store_reopen() {
+        let dir = Path::new("test_file_block_store_reopen_dir");
+        let _ = fs::remove_dir_all(dir);
+
+        let genesis = genesis_block(0);
+        let blocks = extend(&genesis, 10, 0);
+        {
+            let mut store = FileBlockStore::open(dir).unwrap();
+            store.put_block(&genesis).unwrap();
+            for block in &blocks {
+                store.put_block(block).unwrap();
+            }
+            store.set_best_tip(&blocks[8].hash()).unwrap();
+        }
+
+        // 模拟崩溃：最后一个区块已写入但 TIP 未更新，另有写了一半的临时文件
+        fs::write(dir.join("TIP.tmp"), b"partial").unwrap();
+
+        let store = FileBlockStore::open(dir).unwrap();
+        assert!(!dir.join("TIP.tmp").exists());
+        assert_eq!(store.best_tip(), Some(blocks[8].hash()));
+        assert_eq!(store.best_height(), Some(9));
+        for (height, header) in store.headers().enumerate() {
+            let header = header.unwrap();
+            assert_eq!(header.height, height as u64);
+            assert_eq!(store.hash_at_height(height as u64), Some(header.hash()));
+        }
+        assert_eq!(store.get_block(&blocks[9].hash()).unwrap(), Some(blocks[9].clone()));
+
+        // 区块文件被篡改时报告数据损坏
+        fs::write(store.block_path(&blocks[9].hash()), blocks[0].encode()).unwrap();
+        assert!(matches!(store.get_block(&blocks[9].hash()), Err(StorageError::Corrupted(_))));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,45 @@
+use crate::chain::BlockHash;
+use crate::types::DecodeError;
+use std::fmt;
+
+/// 存储层错误
+#[derive(Debug)]
+pub enum StorageError {
+    /// 文件操作错误
+    Io(std::io::Error),
+    /// 存储的数据无法解码
+    Decode(DecodeError),
+    /// 引用了不存在的区块
+    UnknownBlock(BlockHash),
+    /// 存储的数据与索引不一致
+    Corrupted(String),
+}
+
+// 为 StorageError 实现 Display trait，用于打印错误信息
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StorageError::Io(e) => write!(f, "IO error: {}", e),
+            StorageError::Decode(e) => write!(f, "Decode error: {}", e),
+            StorageError::UnknownBlock(hash) => write!(f, "Unknown block {}", hash),
+            StorageError::Corrupted(e) => write!(f, "Corrupted storage: {}", e),
+        }
+    }
+}
+
+// 为 StorageError 实现 Error trait
+impl std::error::Error for StorageError {}
+
+// 实现从 std::io::Error 到 StorageError 的转换
+impl From<std::io::Error> for StorageError {
+    fn from(err: std::io::Error) -> Self {
+        StorageError::Io(err)
+    }
+}
+
+// 实现从 DecodeError 到 StorageError 的转换
+impl From<DecodeError> for StorageError {
+    fn from(err: DecodeError) -> Self {
+        StorageError::Decode(err)
+    }
+}
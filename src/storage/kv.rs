@@ -0,0 +1,415 @@
+use crate::storage::atomic::{remove_temp_files, write_atomic};
+use crate::storage::error::StorageError;
+use crate::types::encoding::Reader;
+use crate::types::DecodeError;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// 一组需要原子提交的写操作
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WriteBatch {
+    // None 表示删除
+    ops: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+}
+
+impl WriteBatch {
+    /// 创建空的写操作集合
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 写入键值
+    pub fn put(&mut self, key: impl Into<Vec<u8>>, value: impl Into<Vec<u8>>) {
+        self.ops.push((key.into(), Some(value.into())));
+    }
+
+    /// 删除键
+    pub fn delete(&mut self, key: impl Into<Vec<u8>>) {
+        self.ops.push((key.into(), None));
+    }
+
+    /// 是否没有任何操作
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    // 把操作应用到内存中的有序表
+    fn apply_to(self, map: &mut BTreeMap<Vec<u8>, Vec<u8>>) {
+        for (key, value) in self.ops {
+            match value {
+                Some(value) => map.insert(key, value),
+                None => map.remove(&key),
+            };
+        }
+    }
+
+    // 编码：操作数（u32） + 每个操作（标记 u8，键长 u32，键，[值长 u32，值]）
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&(self.ops.len() as u32).to_be_bytes());
+        for (key, value) in &self.ops {
+            bytes.push(value.is_some() as u8);
+            bytes.extend_from_slice(&(key.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(key);
+            if let Some(value) = value {
+                bytes.extend_from_slice(&(value.len() as u32).to_be_bytes());
+                bytes.extend_from_slice(value);
+            }
+        }
+        bytes
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader = Reader::new(bytes);
+        let count = reader.read_u32()?;
+        let mut batch = WriteBatch::new();
+        for _ in 0..count {
+            let is_put = match reader.read_array::<1>()?[0] {
+                0 => false,
+                1 => true,
+                _ => return Err(DecodeError::Invalid("Unknown write batch operation")),
+            };
+            let key_len = reader.read_u32()? as usize;
+            let key = reader.read_bytes(key_len)?.to_vec();
+            if is_put {
+                let value_len = reader.read_u32()? as usize;
+                batch.put(key, reader.read_bytes(value_len)?);
+            } else {
+                batch.delete(key);
+            }
+        }
+        reader.finish()?;
+        Ok(batch)
+    }
+}
+
+/// 键值对列表
+pub type KvPairs = Vec<(Vec<u8>, Vec<u8>)>;
+
+/// 键值存储
+///
+/// 所有修改都通过 `write_batch` 提交，一个批次要么全部生效，要么全部不生效。
+pub trait KvStore {
+    /// 读取键对应的值
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StorageError>;
+
+    /// 原子地提交一组写操作
+    fn write_batch(&mut self, batch: WriteBatch) -> Result<(), StorageError>;
+
+    /// 按键的字典序遍历以 `prefix` 开头的所有键值
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<KvPairs, StorageError>;
+}
+
+// 在有序表中查找以 prefix 开头的键值
+fn scan_map(map: &BTreeMap<Vec<u8>, Vec<u8>>, prefix: &[u8]) -> KvPairs {
+    map.range(prefix.to_vec()..)
+        .take_while(|(key, _)| key.starts_with(prefix))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+/// 内存中的键值存储，用于测试
+#[derive(Debug, Clone, Default)]
+pub struct MemoryKvStore {
+    map: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl MemoryKvStore {
+    /// 创建空的存储
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl KvStore for MemoryKvStore {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StorageError> {
+        Ok(self.map.get(key).cloned())
+    }
+
+    fn write_batch(&mut self, batch: WriteBatch) -> Result<(), StorageError> {
+        batch.apply_to(&mut self.map);
+        Ok(())
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<KvPairs, StorageError> {
+        Ok(scan_map(&self.map, prefix))
+    }
+}
+
+/// 基于日志文件的键值存储
+///
+/// 每个批次作为一条记录追加到日志末尾：长度（u32） + 长度的校验和 + 内容的校验和 + 批次编码，
+/// 校验和都是 SHA-256 的前 4 字节，写入后立即刷盘。打开时重放日志，只有末尾写了一半的记录
+/// （写入中途崩溃）会被截断；长度校验失败，或校验、解码失败的记录之后还有数据时，说明日志已损坏，
+/// 打开失败且不改动文件。
+/// 全部数据同时保存在内存中，`compact` 把当前内容重写为单条记录。
+#[derive(Debug)]
+pub struct FileKvStore {
+    path: PathBuf,
+    file: File,
+    // 日志中有效记录的总长度
+    len: u64,
+    map: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl FileKvStore {
+    const LOG_FILE: &'static str = "kv.log";
+    const CHECKSUM_LEN: usize = 4;
+    // 记录头：长度 + 长度的校验和 + 内容的校验和
+    const HEADER_LEN: usize = 4 + 2 * Self::CHECKSUM_LEN;
+
+    /// 打开（或创建）指定目录下的键值存储
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - 存储目录
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, StorageError> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        remove_temp_files(dir)?;
+        let path = dir.join(Self::LOG_FILE);
+
+        let log = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let mut map = BTreeMap::new();
+        let valid_len = Self::replay(&log, &mut map)?;
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        if valid_len < log.len() {
+            // 丢弃崩溃时写了一半的记录
+            file.set_len(valid_len as u64)?;
+            file.sync_all()?;
+        }
+        Ok(FileKvStore {
+            path,
+            file,
+            len: valid_len as u64,
+            map,
+        })
+    }
+
+    // 记录长度或内容的校验和
+    fn checksum(bytes: &[u8]) -> [u8; Self::CHECKSUM_LEN] {
+        let digest = Sha256::digest(bytes);
+        let mut checksum = [0u8; Self::CHECKSUM_LEN];
+        checksum.copy_from_slice(&digest[..Self::CHECKSUM_LEN]);
+        checksum
+    }
+
+    // 编码一条日志记录
+    fn encode_record(batch: &WriteBatch) -> Vec<u8> {
+        let payload = batch.encode();
+        let len = (payload.len() as u32).to_be_bytes();
+        let mut record = Vec::with_capacity(Self::HEADER_LEN + payload.len());
+        record.extend_from_slice(&len);
+        record.extend_from_slice(&Self::checksum(&len));
+        record.extend_from_slice(&Self::checksum(&payload));
+        record.extend_from_slice(&payload);
+        record
+    }
+
+    // 重放日志，返回完整有效的记录所占的长度
+    //
+    // 末尾的记录不完整（头部或内容超出文件末尾）、或者是最后一条且校验失败时，视为写入中途
+    // 崩溃，返回它之前的长度；中间的记录损坏时返回错误，避免截断时丢掉后面已提交的批次。
+    // 长度有自己的校验和，所以只有确认长度正确时，内容超出文件末尾才被当作写了一半。
+    fn replay(log: &[u8], map: &mut BTreeMap<Vec<u8>, Vec<u8>>) -> Result<usize, StorageError> {
+        let mut offset = 0;
+        while offset < log.len() {
+            let mut reader = Reader::new(&log[offset..]);
+            let (Ok(len), Ok(len_checksum), Ok(checksum)) = (
+                reader.read_array::<4>(),
+                reader.read_array::<{ Self::CHECKSUM_LEN }>(),
+                reader.read_array::<{ Self::CHECKSUM_LEN }>(),
+            ) else {
+                return Ok(offset);
+            };
+            if len_checksum != Self::checksum(&len) {
+                return Err(StorageError::Corrupted(format!(
+                    "kv log record at offset {}: length checksum mismatch, {} bytes follow",
+                    offset,
+                    log.len() - offset - Self::HEADER_LEN
+                )));
+            }
+            let len = u32::from_be_bytes(len) as usize;
+            let Ok(payload) = reader.read_bytes(len) else {
+                return Ok(offset);
+            };
+            let end = offset + Self::HEADER_LEN + len;
+            let batch = if checksum != Self::checksum(payload) {
+                Err("checksum mismatch".to_string())
+            } else {
+                WriteBatch::decode(payload).map_err(|e| e.to_string())
+            };
+            match batch {
+                Ok(batch) => batch.apply_to(map),
+                Err(_) if end == log.len() => return Ok(offset),
+                Err(e) => {
+                    return Err(StorageError::Corrupted(format!(
+                        "kv log record at offset {}: {}, {} bytes follow",
+                        offset,
+                        e,
+                        log.len() - end
+                    )))
+                }
+            }
+            offset = end;
+        }
+        Ok(offset)
+    }
+
+    /// 把当前内容重写为单条记录，回收被覆盖和删除的数据所占的空间
+    pub fn compact(&mut self) -> Result<(), StorageError> {
+        let mut batch = WriteBatch::new();
+        for (key, value) in &self.map {
+            batch.put(key.clone(), value.clone());
+        }
+        let record = Self::encode_record(&batch);
+        write_atomic(&self.path, &record)?;
+        self.file = OpenOptions::new().append(true).open(&self.path)?;
+        self.len = record.len() as u64;
+        Ok(())
+    }
+}
+
+impl KvStore for FileKvStore {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StorageError> {
+        Ok(self.map.get(key).cloned())
+    }
+
+    fn write_batch(&mut self, batch: WriteBatch) -> Result<(), StorageError> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        let record = Self::encode_record(&batch);
+        if let Err(e) = self.file.write_all(&record).and_then(|_| self.file.sync_data()) {
+            // 写入失败时截掉不完整的记录，避免后续记录在重放时被一起丢弃
+            let _ = self.file.set_len(self.len);
+            return Err(e.into());
+        }
+        self.len += record.len() as u64;
+        batch.apply_to(&mut self.map);
+        Ok(())
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<KvPairs, StorageError> {
+        Ok(scan_map(&self.map, prefix))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_store<S: KvStore>(store: &mut S) {
+        let mut batch = WriteBatch::new();
+        batch.put(b"a1".to_vec(), b"one".to_vec());
+        batch.put(b"a2".to_vec(), b"two".to_vec());
+        batch.put(b"b1".to_vec(), b"three".to_vec());
+        store.write_batch(batch).unwrap();
+
+        let mut batch = WriteBatch::new();
+        batch.delete(b"a1".to_vec());
+        batch.put(b"a2".to_vec(), b"TWO".to_vec());
+        store.write_batch(batch).unwrap();
+
+        assert_eq!(store.get(b"a1").unwrap(), None);
+        assert_eq!(store.get(b"a2").unwrap(), Some(b"TWO".to_vec()));
+        assert_eq!(
+            store.scan_prefix(b"b").unwrap(),
+            vec![(b"b1".to_vec(), b"three".to_vec())]
+        );
+    }
+
+    #[test]
+    fn test_memory_kv_store() {
+        check_store(&mut MemoryKvStore::new());
+    }
+
+    #[test]
+    fn test_file_kv_store_reopen() {
+        let dir = Path::new("test_file_kv_store_dir");
+        let _ = fs::remove_dir_all(dir);
+
+        check_store(&mut FileKvStore::open(dir).unwrap());
+        let store = FileKvStore::open(dir).unwrap();
+        assert_eq!(store.get(b"a2").unwrap(), Some(b"TWO".to_vec()));
+        assert_eq!(store.get(b"a1").unwrap(), None);
+
+        // 模拟写入中途崩溃：日志末尾只有半条记录
+        let mut batch = WriteBatch::new();
+        batch.put(b"c1".to_vec(), b"lost".to_vec());
+        let record = FileKvStore::encode_record(&batch);
+        let log_path = dir.join(FileKvStore::LOG_FILE);
+        let len = fs::metadata(&log_path).unwrap().len();
+        let mut file = OpenOptions::new().append(true).open(&log_path).unwrap();
+        file.write_all(&record[..record.len() - 2]).unwrap();
+        drop(file);
+
+        let mut store = FileKvStore::open(dir).unwrap();
+        assert_eq!(store.get(b"c1").unwrap(), None);
+        assert_eq!(fs::metadata(&log_path).unwrap().len(), len);
+
+        // 截断后可以继续写入
+        let mut batch = WriteBatch::new();
+        batch.put(b"c2".to_vec(), b"kept".to_vec());
+        store.write_batch(batch).unwrap();
+
+        // 压缩后内容不变，日志变短
+        let before = fs::metadata(&log_path).unwrap().len();
+        store.compact().unwrap();
+        assert!(fs::metadata(&log_path).unwrap().len() < before);
+        let mut batch = WriteBatch::new();
+        batch.put(b"c3".to_vec(), b"after compact".to_vec());
+        store.write_batch(batch).unwrap();
+        drop(store);
+
+        let store = FileKvStore::open(dir).unwrap();
+        assert_eq!(store.get(b"a2").unwrap(), Some(b"TWO".to_vec()));
+        assert_eq!(store.get(b"c2").unwrap(), Some(b"kept".to_vec()));
+        assert_eq!(store.get(b"c3").unwrap(), Some(b"after compact".to_vec()));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_kv_store_corrupted_record() {
+        let dir = Path::new("test_file_kv_store_corrupted_dir");
+        let _ = fs::remove_dir_all(dir);
+
+        check_store(&mut FileKvStore::open(dir).unwrap());
+        let log_path = dir.join(FileKvStore::LOG_FILE);
+        let mut log = fs::read(&log_path).unwrap();
+
+        // 翻转第一条记录长度的最高字节：长度超出文件末尾，但长度校验失败，不能当作写了一半
+        log[0] ^= 0x01;
+        fs::write(&log_path, &log).unwrap();
+        assert!(matches!(FileKvStore::open(dir), Err(StorageError::Corrupted(_))));
+        assert_eq!(fs::read(&log_path).unwrap(), log);
+        log[0] ^= 0x01;
+
+        // 翻转第一条记录内容中的一个字节：后面还有已提交的记录，不能截断
+        log[FileKvStore::HEADER_LEN + 2] ^= 0x01;
+        fs::write(&log_path, &log).unwrap();
+        assert!(matches!(FileKvStore::open(dir), Err(StorageError::Corrupted(_))));
+        assert_eq!(fs::read(&log_path).unwrap(), log);
+
+        // 最后一条记录校验失败且之后没有数据时，视为写入中途崩溃
+        log[FileKvStore::HEADER_LEN + 2] ^= 0x01;
+        let first_len = FileKvStore::HEADER_LEN + u32::from_be_bytes(log[..4].try_into().unwrap()) as usize;
+        let last = log.len() - 1;
+        log[last] ^= 0x01;
+        fs::write(&log_path, &log).unwrap();
+        let store = FileKvStore::open(dir).unwrap();
+        assert_eq!(store.get(b"a1").unwrap(), Some(b"one".to_vec()));
+        assert_eq!(fs::metadata(&log_path).unwrap().len(), first_len as u64);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,8 @@
+pub(crate) mod atomic;
+pub mod block_store;
+pub mod error;
+pub mod kv;
+
+pub use block_store::{BlockStore, FileBlockStore, MemoryBlockStore};
+pub use error::StorageError;
+pub use kv::{FileKvStore, KvStore, MemoryKvStore, WriteBatch};
@@ -0,0 +1,241 @@
+use crate::types::keys::PublicKey;
+use bech32::primitives::decode::CheckedHrpstring;
+use bech32::{Bech32m, Hrp};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+
+/// 网络类型，决定地址的可读前缀
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NetworkKind {
+    /// 主网
+    Mainnet,
+    /// 测试网
+    Testnet,
+    /// 本地回归测试网
+    Regtest,
+}
+
+impl NetworkKind {
+    /// 所有网络类型
+    pub const ALL: [NetworkKind; 3] = [NetworkKind::Mainnet, NetworkKind::Testnet, NetworkKind::Regtest];
+
+    /// 地址的可读前缀 (bech32m HRP)
+    pub const fn hrp(self) -> &'static str {
+        match self {
+            NetworkKind::Mainnet => "faic",
+            NetworkKind::Testnet => "tfaic",
+            NetworkKind::Regtest => "rfaic",
+        }
+    }
+
+    /// 根据可读前缀查找网络类型
+    pub fn from_hrp(hrp: &str) -> Option<NetworkKind> {
+        NetworkKind::ALL.into_iter().find(|kind| kind.hrp() == hrp)
+    }
+
+    /// 规范二进制编码中使用的字节
+    pub const fn to_byte(self) -> u8 {
+        match self {
+            NetworkKind::Mainnet => 0,
+            NetworkKind::Testnet => 1,
+            NetworkKind::Regtest => 2,
+        }
+    }
+
+    /// 从规范二进制编码中的字节解析网络类型
+    pub fn from_byte(byte: u8) -> Option<NetworkKind> {
+        NetworkKind::ALL.into_iter().find(|kind| kind.to_byte() == byte)
+    }
+}
+
+/// 地址：公钥哈希 + 网络类型，以 bech32m 编码显示，例如 "faic1..."
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Address {
+    network: NetworkKind,
+    hash: [u8; Address::HASH_LEN],
+}
+
+impl Address {
+    /// 公钥哈希长度（字节）
+    pub const HASH_LEN: usize = 20;
+
+    /// 规范二进制编码的长度（字节）：网络类型 1 字节 + 公钥哈希
+    pub const ENCODED_LEN: usize = 1 + Self::HASH_LEN;
+
+    /// 由公钥派生地址：取 SHA-256(公钥) 的前 20 字节
+    pub fn from_public_key(public_key: &PublicKey, network: NetworkKind) -> Self {
+        let digest = Sha256::digest(public_key.as_bytes());
+        let mut hash = [0u8; Self::HASH_LEN];
+        hash.copy_from_slice(&digest[..Self::HASH_LEN]);
+        Address { network, hash }
+    }
+
+    /// 由公钥哈希创建地址
+    pub const fn from_hash(hash: [u8; Address::HASH_LEN], network: NetworkKind) -> Self {
+        Address { network, hash }
+    }
+
+    /// 网络类型
+    pub const fn network(&self) -> NetworkKind {
+        self.network
+    }
+
+    /// 公钥哈希
+    pub const fn hash(&self) -> &[u8; Address::HASH_LEN] {
+        &self.hash
+    }
+
+    /// 规范二进制编码
+    pub fn to_bytes(&self) -> [u8; Address::ENCODED_LEN] {
+        let mut bytes = [0u8; Self::ENCODED_LEN];
+        bytes[0] = self.network.to_byte();
+        bytes[1..].copy_from_slice(&self.hash);
+        bytes
+    }
+
+    /// 从规范二进制编码解码
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        if bytes.len() != Self::ENCODED_LEN {
+            return Err("Invalid address length");
+        }
+        let network = NetworkKind::from_byte(bytes[0]).ok_or("Unknown address network")?;
+        let mut hash = [0u8; Self::HASH_LEN];
+        hash.copy_from_slice(&bytes[1..]);
+        Ok(Address { network, hash })
+    }
+
+    /// 判断公钥是否对应该地址
+    pub fn matches(&self, public_key: &PublicKey) -> bool {
+        Address::from_public_key(public_key, self.network) == *self
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hrp = Hrp::parse_unchecked(self.network.hrp());
+        bech32::encode_lower_to_fmt::<Bech32m, _>(f, hrp, &self.hash).map_err(|_| fmt::Error)
+    }
+}
+
+impl fmt::Debug for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Address({})", self)
+    }
+}
+
+/// 解析 bech32m 地址，校验校验和、网络前缀和长度
+impl FromStr for Address {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let checked = CheckedHrpstring::new::<Bech32m>(s).map_err(|_| "Invalid address checksum")?;
+        let network = NetworkKind::from_hrp(&checked.hrp().to_lowercase()).ok_or("Unknown address prefix")?;
+        let bytes: Vec<u8> = checked.byte_iter().collect();
+        let hash: [u8; Self::HASH_LEN] = bytes.try_into().map_err(|_| "Invalid address length")?;
+        Ok(Address { network, hash })
+    }
+}
+
+// 序列化 Address 为字符串
+impl Serialize for Address {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+// 反序列化字符串为 Address
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::keys::KeyPair;
+
+    #[test]
+    fn test_address_round_trip() {
+        let public_key = KeyPair::generate().public_key();
+        for network in NetworkKind::ALL {
+            let address = Address::from_public_key(&public_key, network);
+            let encoded = address.to_string();
+            assert!(encoded.starts_with(&format!("{}1", network.hrp())));
+
+            let parsed: Address = encoded.parse().unwrap();
+            assert_eq!(parsed, address);
+            assert_eq!(parsed.network(), network);
+            assert!(parsed.matches(&public_key));
+
+            // 大写形式同样合法
+            assert_eq!(encoded.to_uppercase().parse::<Address>().unwrap(), address);
+        }
+    }
+
+    #[test]
+    fn test_address_is_network_specific() {
+        let public_key = KeyPair::generate().public_key();
+        let mainnet = Address::from_public_key(&public_key, NetworkKind::Mainnet);
+        let testnet = Address::from_public_key(&public_key, NetworkKind::Testnet);
+        assert_ne!(mainnet, testnet);
+        assert_eq!(mainnet.hash(), testnet.hash());
+        assert!(!testnet.matches(&KeyPair::generate().public_key()));
+    }
+
+    #[test]
+    fn test_invalid_addresses() {
+        let address = Address::from_hash([1u8; 20], NetworkKind::Mainnet).to_string();
+
+        // 篡改一个字符导致校验和失败
+        let mut tampered = address.clone().into_bytes();
+        let last = tampered.len() - 1;
+        tampered[last] = if tampered[last] == b'q' { b'p' } else { b'q' };
+        assert!(String::from_utf8(tampered).unwrap().parse::<Address>().is_err());
+
+        // 未知前缀
+        let unknown = bech32::encode::<Bech32m>(Hrp::parse("btc").unwrap(), &[1u8; 20]).unwrap();
+        assert_eq!(unknown.parse::<Address>(), Err("Unknown address prefix"));
+
+        // 使用 bech32 而不是 bech32m 校验和
+        let legacy = bech32::encode::<bech32::Bech32>(Hrp::parse("faic").unwrap(), &[1u8; 20]).unwrap();
+        assert!(legacy.parse::<Address>().is_err());
+
+        // 长度错误
+        let short = bech32::encode::<Bech32m>(Hrp::parse("faic").unwrap(), &[1u8; 19]).unwrap();
+        assert_eq!(short.parse::<Address>(), Err("Invalid address length"));
+
+        assert!("some_address".parse::<Address>().is_err());
+    }
+
+    #[test]
+    fn test_address_binary_encoding() {
+        let address = Address::from_hash([3u8; 20], NetworkKind::Testnet);
+        let bytes = address.to_bytes();
+        assert_eq!(bytes[0], NetworkKind::Testnet.to_byte());
+        assert_eq!(Address::from_bytes(&bytes).unwrap(), address);
+
+        let mut unknown = bytes;
+        unknown[0] = 9;
+        assert!(Address::from_bytes(&unknown).is_err());
+        assert!(Address::from_bytes(&bytes[1..]).is_err());
+    }
+
+    #[test]
+    fn test_address_serde() {
+        let address = Address::from_hash([9u8; 20], NetworkKind::Regtest);
+        let serialized = serde_json::to_string(&address).unwrap();
+        assert_eq!(serialized, format!("\"{}\"", address));
+        assert_eq!(serde_json::from_str::<Address>(&serialized).unwrap(), address);
+        assert!(serde_json::from_str::<Address>("\"faic1invalid\"").is_err());
+    }
+}
@@ -0,0 +1,325 @@
+use crate::types::{format_decimal_units, parse_decimal_units, Amount};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// 货币单位
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Denomination {
+    /// 1 FAIC = 10^8 最小单位
+    Faic,
+    /// 1 mFAIC = 10^5 最小单位
+    MilliFaic,
+    /// 1 uFAIC = 10^2 最小单位
+    MicroFaic,
+    /// 最小单位: 0.00000001 FAIC
+    Base,
+}
+
+impl Denomination {
+    /// 所有单位，从大到小排列
+    pub const ALL: [Denomination; 4] = [
+        Denomination::Faic,
+        Denomination::MilliFaic,
+        Denomination::MicroFaic,
+        Denomination::Base,
+    ];
+
+    /// 该单位相对最小单位的小数位数
+    pub const fn decimals(self) -> u32 {
+        match self {
+            Denomination::Faic => Amount::DECIMALS as u32,
+            Denomination::MilliFaic => Amount::DECIMALS as u32 - 3,
+            Denomination::MicroFaic => Amount::DECIMALS as u32 - 6,
+            Denomination::Base => 0,
+        }
+    }
+
+    /// 1 个该单位等于多少最小单位
+    pub const fn base_units(self) -> u128 {
+        10u128.pow(self.decimals())
+    }
+
+    /// 单位符号
+    pub const fn symbol(self) -> &'static str {
+        match self {
+            Denomination::Faic => "FAIC",
+            Denomination::MilliFaic => "mFAIC",
+            Denomination::MicroFaic => "uFAIC",
+            Denomination::Base => "base",
+        }
+    }
+
+    /// 在不同单位之间无损换算，无法整除或溢出时返回 None
+    pub fn convert(value: u128, from: Denomination, to: Denomination) -> Option<u128> {
+        Amount::from_denomination(value, from)?.to_denomination(to)
+    }
+}
+
+impl fmt::Display for Denomination {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+impl FromStr for Denomination {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "FAIC" | "faic" => Ok(Denomination::Faic),
+            "mFAIC" | "mfaic" => Ok(Denomination::MilliFaic),
+            "uFAIC" | "µFAIC" | "ufaic" => Ok(Denomination::MicroFaic),
+            "base" => Ok(Denomination::Base),
+            _ => Err("Unknown denomination"),
+        }
+    }
+}
+
+/// 舍入方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum RoundingMode {
+    /// 向零舍入（截断）
+    #[default]
+    Down,
+    /// 远离零舍入
+    Up,
+    /// 四舍五入
+    HalfUp,
+    /// 银行家舍入：恰好一半时取偶数
+    HalfEven,
+}
+
+impl RoundingMode {
+    /// 按当前舍入方式计算 value / divisor
+    pub fn div(self, value: u128, divisor: u128) -> u128 {
+        let quotient = value / divisor;
+        let remainder = value % divisor;
+        if remainder == 0 {
+            return quotient;
+        }
+        // 与 divisor - remainder 比较，避免 remainder * 2 溢出
+        let round_up = match self {
+            RoundingMode::Down => false,
+            RoundingMode::Up => true,
+            RoundingMode::HalfUp => remainder >= divisor - remainder,
+            RoundingMode::HalfEven => {
+                remainder > divisor - remainder || (remainder == divisor - remainder && quotient % 2 == 1)
+            }
+        };
+        // quotient 不超过 u128::MAX / divisor，divisor > 1 时加一不会溢出
+        quotient + round_up as u128
+    }
+}
+
+impl Amount {
+    /// 从指定单位的数量创建 Amount，超过 MAX_AMOUNT 时返回 None
+    pub fn from_denomination(value: u128, denomination: Denomination) -> Option<Amount> {
+        value.checked_mul(denomination.base_units()).map(Amount::from_u128)
+    }
+
+    /// 无损换算为指定单位的数量，不能整除时返回 None
+    pub fn to_denomination(&self, denomination: Denomination) -> Option<u128> {
+        let base = denomination.base_units();
+        if !self.as_u128().is_multiple_of(base) {
+            return None;
+        }
+        Some(self.as_u128() / base)
+    }
+
+    /// 按舍入方式换算为指定单位的整数数量
+    pub fn to_denomination_rounded(&self, denomination: Denomination, rounding: RoundingMode) -> u128 {
+        rounding.div(self.as_u128(), denomination.base_units())
+    }
+
+    /// 以指定单位解析十进制字符串，例如 `Amount::parse_in("1.5", Denomination::MilliFaic)`
+    pub fn parse_in(s: &str, denomination: Denomination) -> Result<Amount, &'static str> {
+        let value = parse_decimal_units(s.trim(), denomination.decimals() as usize)?;
+        Ok(Amount::from_u128(value))
+    }
+}
+
+/// 小数位显示方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precision {
+    /// 显示全部精度并去掉末尾的零
+    Trim,
+    /// 固定显示指定位数的小数，多余的位数按舍入方式处理
+    Fixed(u32),
+}
+
+/// Amount 格式化器
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AmountFormatter {
+    /// 显示单位
+    pub denomination: Denomination,
+    /// 小数位显示方式
+    pub precision: Precision,
+    /// 舍入方式
+    pub rounding: RoundingMode,
+    /// 是否在末尾附加单位符号
+    pub show_symbol: bool,
+}
+
+impl AmountFormatter {
+    /// 创建一个格式化器，默认显示该单位的全部小数位
+    pub fn new(denomination: Denomination) -> Self {
+        AmountFormatter {
+            denomination,
+            precision: Precision::Fixed(denomination.decimals()),
+            rounding: RoundingMode::default(),
+            show_symbol: false,
+        }
+    }
+
+    /// 去掉末尾的零
+    pub fn trim_zeros(mut self) -> Self {
+        self.precision = Precision::Trim;
+        self
+    }
+
+    /// 固定显示 `decimals` 位小数
+    pub fn fixed(mut self, decimals: u32) -> Self {
+        self.precision = Precision::Fixed(decimals);
+        self
+    }
+
+    /// 设置舍入方式
+    pub fn rounding(mut self, rounding: RoundingMode) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// 在末尾附加单位符号
+    pub fn with_symbol(mut self) -> Self {
+        self.show_symbol = true;
+        self
+    }
+
+    /// 格式化 Amount
+    pub fn format(&self, amount: &Amount) -> String {
+        let decimals = self.denomination.decimals();
+        let mut text = match self.precision {
+            Precision::Trim => {
+                let full = format_decimal_units(amount.as_u128(), decimals as usize);
+                if full.contains('.') {
+                    full.trim_end_matches('0').trim_end_matches('.').to_string()
+                } else {
+                    full
+                }
+            }
+            Precision::Fixed(places) if places >= decimals => {
+                let mut full = format_decimal_units(amount.as_u128(), decimals as usize);
+                if places > 0 && decimals == 0 {
+                    full.push('.');
+                }
+                full.extend(std::iter::repeat_n('0', (places - decimals) as usize));
+                full
+            }
+            Precision::Fixed(places) => {
+                let divisor = 10u128.pow(decimals - places);
+                let rounded = self.rounding.div(amount.as_u128(), divisor);
+                format_decimal_units(rounded, places as usize)
+            }
+        };
+        if self.show_symbol {
+            text.push(' ');
+            text.push_str(self.denomination.symbol());
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_denomination_units() {
+        assert_eq!(Denomination::Faic.base_units(), 100_000_000);
+        assert_eq!(Denomination::MilliFaic.base_units(), 100_000);
+        assert_eq!(Denomination::MicroFaic.base_units(), 100);
+        assert_eq!(Denomination::Base.base_units(), 1);
+
+        for denomination in Denomination::ALL {
+            assert_eq!(denomination.symbol().parse::<Denomination>().unwrap(), denomination);
+        }
+        assert!("sat".parse::<Denomination>().is_err());
+    }
+
+    #[test]
+    fn test_lossless_conversion() {
+        let amount = Amount::from_denomination(3, Denomination::Faic).unwrap();
+        assert_eq!(amount.as_u128(), 300_000_000);
+        assert_eq!(amount.to_denomination(Denomination::MilliFaic), Some(3_000));
+        assert_eq!(amount.to_denomination(Denomination::Base), Some(300_000_000));
+
+        assert_eq!(Denomination::convert(1_500, Denomination::MilliFaic, Denomination::Faic), None);
+        assert_eq!(Denomination::convert(2_000, Denomination::MilliFaic, Denomination::Faic), Some(2));
+        assert_eq!(Denomination::convert(7, Denomination::MicroFaic, Denomination::Base), Some(700));
+
+        // 溢出
+        assert!(Amount::from_denomination(u128::MAX, Denomination::Faic).is_none());
+    }
+
+    #[test]
+    fn test_rounded_conversion() {
+        let amount = Amount::from_u128(250_000_000); // 2.5 FAIC
+        assert_eq!(amount.to_denomination_rounded(Denomination::Faic, RoundingMode::Down), 2);
+        assert_eq!(amount.to_denomination_rounded(Denomination::Faic, RoundingMode::Up), 3);
+        assert_eq!(amount.to_denomination_rounded(Denomination::Faic, RoundingMode::HalfUp), 3);
+        assert_eq!(amount.to_denomination_rounded(Denomination::Faic, RoundingMode::HalfEven), 2);
+
+        let amount = Amount::from_u128(350_000_000); // 3.5 FAIC
+        assert_eq!(amount.to_denomination_rounded(Denomination::Faic, RoundingMode::HalfEven), 4);
+
+        let max = Amount::max_value();
+        assert_eq!(max.to_denomination_rounded(Denomination::Faic, RoundingMode::Up), u128::MAX / 100_000_000 + 1);
+    }
+
+    #[test]
+    fn test_parse_in_denomination() {
+        let amount = Amount::parse_in("1.5", Denomination::MilliFaic).unwrap();
+        assert_eq!(amount.as_u128(), 150_000);
+        let amount = Amount::parse_in("42", Denomination::Base).unwrap();
+        assert_eq!(amount.as_u128(), 42);
+
+        assert!(Amount::parse_in("0.5", Denomination::Base).is_err());
+        assert!(Amount::parse_in("0.001", Denomination::MicroFaic).is_err());
+    }
+
+    #[test]
+    fn test_formatter() {
+        let amount = Amount::from_u128(123_450_000); // 1.2345 FAIC
+
+        assert_eq!(AmountFormatter::new(Denomination::Faic).format(&amount), "1.23450000");
+        assert_eq!(AmountFormatter::new(Denomination::Faic).trim_zeros().format(&amount), "1.2345");
+        assert_eq!(AmountFormatter::new(Denomination::Faic).fixed(2).format(&amount), "1.23");
+        assert_eq!(
+            AmountFormatter::new(Denomination::Faic)
+                .fixed(3)
+                .rounding(RoundingMode::HalfEven)
+                .format(&amount),
+            "1.234"
+        );
+        assert_eq!(
+            AmountFormatter::new(Denomination::Faic)
+                .fixed(3)
+                .rounding(RoundingMode::HalfUp)
+                .format(&amount),
+            "1.235"
+        );
+        assert_eq!(AmountFormatter::new(Denomination::Faic).fixed(10).format(&amount), "1.2345000000");
+        assert_eq!(
+            AmountFormatter::new(Denomination::MilliFaic).with_symbol().format(&amount),
+            "1234.50000 mFAIC"
+        );
+        assert_eq!(AmountFormatter::new(Denomination::Base).format(&amount), "123450000");
+        assert_eq!(AmountFormatter::new(Denomination::Base).fixed(2).format(&amount), "123450000.00");
+        assert_eq!(AmountFormatter::new(Denomination::Faic).fixed(0).rounding(RoundingMode::Up).format(&amount), "2");
+
+        let whole = Amount::from_u128(100_000_000);
+        assert_eq!(AmountFormatter::new(Denomination::Faic).trim_zeros().with_symbol().format(&whole), "1 FAIC");
+        assert_eq!(AmountFormatter::new(Denomination::Faic).trim_zeros().format(&Amount::zero()), "0");
+    }
+}
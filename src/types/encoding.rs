@@ -0,0 +1,106 @@
+use crate::types::Amount;
+use std::fmt;
+
+/// 规范二进制编码的解码错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// 输入提前结束
+    UnexpectedEnd,
+    /// 解码完成后仍有多余的字节
+    TrailingBytes,
+    /// 字段内容不合法
+    Invalid(&'static str),
+}
+
+// 为 DecodeError 实现 Display trait，用于打印错误信息
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedEnd => write!(f, "Unexpected end of input"),
+            DecodeError::TrailingBytes => write!(f, "Trailing bytes after decoding"),
+            DecodeError::Invalid(e) => write!(f, "Invalid encoding: {}", e),
+        }
+    }
+}
+
+// 为 DecodeError 实现 Error trait
+impl std::error::Error for DecodeError {}
+
+/// 按顺序读取规范编码的字段，所有整数均为大端序
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes }
+    }
+
+    /// 读取指定长度的字节
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if self.bytes.len() < len {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    /// 读取定长字节数组
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.read_bytes(N)?);
+        Ok(array)
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, DecodeError> {
+        Ok(u32::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, DecodeError> {
+        Ok(u64::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_amount(&mut self) -> Result<Amount, DecodeError> {
+        Amount::from_be_bytes(self.read_bytes(Amount::ENCODED_LEN)?).map_err(DecodeError::Invalid)
+    }
+
+    /// 剩余未读取的字节数
+    pub fn remaining(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// 结束解码，存在多余字节时返回错误
+    pub fn finish(self) -> Result<(), DecodeError> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(DecodeError::TrailingBytes)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reader() {
+        let mut bytes = 7u32.to_be_bytes().to_vec();
+        bytes.extend_from_slice(&42u32.to_be_bytes());
+        bytes.extend_from_slice(&Amount::from_u128(5).to_be_bytes());
+
+        let mut reader = Reader::new(&bytes);
+        assert_eq!(reader.read_u32(), Ok(7));
+        assert_eq!(reader.read_u32(), Ok(42));
+        assert_eq!(reader.read_amount(), Ok(Amount::from_u128(5)));
+        reader.finish().unwrap();
+
+        let mut reader = Reader::new(&bytes[..bytes.len() - 1]);
+        assert_eq!(reader.read_u64(), Ok((7u64 << 32) | 42));
+        assert_eq!(reader.read_amount(), Err(DecodeError::UnexpectedEnd));
+
+        let reader = Reader::new(&bytes);
+        assert_eq!(reader.finish(), Err(DecodeError::TrailingBytes));
+    }
+}
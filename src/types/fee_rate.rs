@@ -0,0 +1,207 @@
+use crate::types::{format_decimal_units, parse_decimal_units, Amount, Denomination};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// 手续费率，内部以「最小单位 / 1000 字节」存储，以便表示不足 1 个最小单位每字节的费率
+///
+/// 也可以按权重单位（WU）计价，1 字节 = 4 个权重单位。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FeeRate {
+    per_kb: Amount,
+}
+
+impl FeeRate {
+    /// 费率为零
+    pub const ZERO: FeeRate = FeeRate { per_kb: Amount::zero() };
+
+    /// 每千字节对应的字节数
+    const BYTES_PER_KB: u64 = 1000;
+
+    /// 每字节对应的权重单位数
+    pub const WEIGHT_UNITS_PER_BYTE: u64 = 4;
+
+    /// 从每千字节的手续费创建
+    pub const fn from_per_kb(per_kb: Amount) -> Self {
+        FeeRate { per_kb }
+    }
+
+    /// 从每字节的手续费创建，溢出时返回 None
+    pub fn from_per_byte(per_byte: Amount) -> Option<Self> {
+        per_byte.checked_mul_u64(Self::BYTES_PER_KB).map(Self::from_per_kb)
+    }
+
+    /// 从每千权重单位的手续费创建，溢出时返回 None
+    pub fn from_per_kwu(per_kwu: Amount) -> Option<Self> {
+        per_kwu.checked_mul_u64(Self::WEIGHT_UNITS_PER_BYTE).map(Self::from_per_kb)
+    }
+
+    /// 从每权重单位的手续费创建，溢出时返回 None
+    pub fn from_per_weight_unit(per_wu: Amount) -> Option<Self> {
+        per_wu
+            .checked_mul_u64(Self::BYTES_PER_KB * Self::WEIGHT_UNITS_PER_BYTE)
+            .map(Self::from_per_kb)
+    }
+
+    /// 每千字节的手续费
+    pub const fn per_kb(&self) -> Amount {
+        self.per_kb
+    }
+
+    /// 计算指定大小（字节）的交易所需的手续费，不足 1 个最小单位的部分向上取整
+    pub fn fee_for_size(&self, size: u64) -> Option<Amount> {
+        let total = self.per_kb.as_u128().checked_mul(size as u128)?;
+        Some(Amount::from_u128(total.div_ceil(Self::BYTES_PER_KB as u128)))
+    }
+
+    /// 计算指定权重（权重单位）的交易所需的手续费，不足 1 个最小单位的部分向上取整
+    pub fn fee_for_weight(&self, weight: u64) -> Option<Amount> {
+        let total = self.per_kb.as_u128().checked_mul(weight as u128)?;
+        let divisor = (Self::BYTES_PER_KB * Self::WEIGHT_UNITS_PER_BYTE) as u128;
+        Some(Amount::from_u128(total.div_ceil(divisor)))
+    }
+
+    /// 根据手续费和交易权重反推费率（向下取整），weight 为零时返回 None
+    pub fn from_fee_and_weight(fee: &Amount, weight: u64) -> Option<Self> {
+        if weight == 0 {
+            return None;
+        }
+        let scale = (Self::BYTES_PER_KB * Self::WEIGHT_UNITS_PER_BYTE) as u128;
+        let per_kb = fee.as_u128().checked_mul(scale)? / weight as u128;
+        Some(Self::from_per_kb(Amount::from_u128(per_kb)))
+    }
+
+    /// 根据手续费和交易大小反推费率（向下取整），size 为零时返回 None
+    pub fn from_fee_and_size(fee: &Amount, size: u64) -> Option<Self> {
+        if size == 0 {
+            return None;
+        }
+        let per_kb = fee.as_u128().checked_mul(Self::BYTES_PER_KB as u128)? / size as u128;
+        Some(Self::from_per_kb(Amount::from_u128(per_kb)))
+    }
+}
+
+/// 以「最小单位 / 字节」显示，例如 "5 base/B"、"0.25 base/B"
+impl fmt::Display for FeeRate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = format_decimal_units(self.per_kb.as_u128(), 3);
+        let text = text.trim_end_matches('0').trim_end_matches('.');
+        write!(f, "{} {}/B", text, Denomination::Base)
+    }
+}
+
+/// 解析形如 "<数量> <单位>/<B|kB|WU|kWU>" 的字符串，例如 "5 base/B"、"0.00001 FAIC/kB"、"2 base/WU"
+impl FromStr for FeeRate {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (amount_part, size_unit) = s.trim().split_once('/').ok_or("Invalid fee rate string")?;
+        let (value, denomination) = amount_part
+            .trim()
+            .rsplit_once(char::is_whitespace)
+            .ok_or("Invalid fee rate string")?;
+        let denomination: Denomination = denomination.parse()?;
+
+        // 按字节计价时多解析三位小数，结果直接就是每千字节的最小单位数量；
+        // 按权重单位计价时再乘以每字节的权重单位数
+        let (extra_decimals, multiplier) = match size_unit.trim() {
+            "B" => (3, 1),
+            "kB" => (0, 1),
+            "WU" => (3, Self::WEIGHT_UNITS_PER_BYTE),
+            "kWU" => (0, Self::WEIGHT_UNITS_PER_BYTE),
+            _ => return Err("Unknown fee rate size unit"),
+        };
+        let units = parse_decimal_units(value.trim(), denomination.decimals() as usize + extra_decimals)?;
+        let per_kb = Amount::from_u128(units)
+            .checked_mul_u64(multiplier)
+            .ok_or("Fee rate exceeds maximum value")?;
+        Ok(FeeRate::from_per_kb(per_kb))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_for_size() {
+        let rate = FeeRate::from_per_byte(Amount::from_u128(5)).unwrap();
+        assert_eq!(rate.fee_for_size(250), Some(Amount::from_u128(1_250)));
+        assert_eq!(FeeRate::ZERO.fee_for_size(250), Some(Amount::zero()));
+
+        // 不足 1 个最小单位的部分向上取整
+        let rate = FeeRate::from_per_kb(Amount::from_u128(1_500));
+        assert_eq!(rate.fee_for_size(1), Some(Amount::from_u128(2)));
+
+        // 溢出
+        let rate = FeeRate::from_per_kb(Amount::max_value());
+        assert_eq!(rate.fee_for_size(2), None);
+        assert!(FeeRate::from_per_byte(Amount::max_value()).is_none());
+
+        let rate = FeeRate::from_fee_and_size(&Amount::from_u128(1_250), 250).unwrap();
+        assert_eq!(rate, FeeRate::from_per_byte(Amount::from_u128(5)).unwrap());
+        assert!(FeeRate::from_fee_and_size(&Amount::from_u128(1), 0).is_none());
+    }
+
+    #[test]
+    fn test_fee_rate_parse_and_display() {
+        let rate: FeeRate = "5 base/B".parse().unwrap();
+        assert_eq!(rate.per_kb(), Amount::from_u128(5_000));
+        assert_eq!(rate.to_string(), "5 base/B");
+
+        let rate: FeeRate = "0.00001 FAIC/kB".parse().unwrap();
+        assert_eq!(rate.per_kb(), Amount::from_u128(1_000));
+        assert_eq!(rate.to_string(), "1 base/B");
+
+        let rate: FeeRate = "0.25 base/B".parse().unwrap();
+        assert_eq!(rate.per_kb(), Amount::from_u128(250));
+        assert_eq!(rate.to_string(), "0.25 base/B");
+        assert_eq!(rate.to_string().parse::<FeeRate>().unwrap(), rate);
+
+        let rate: FeeRate = "2 mFAIC/kB".parse().unwrap();
+        assert_eq!(rate.per_kb(), Amount::from_u128(200_000));
+
+        for invalid in ["5", "5 base", "5 sat/B", "5 base/MB", "0.0001 base/B", "abc base/B"] {
+            assert!(invalid.parse::<FeeRate>().is_err(), "{:?} should be rejected", invalid);
+        }
+    }
+
+    #[test]
+    fn test_fee_rate_per_weight_unit() {
+        // 1 base/WU 等于 4 base/B
+        let rate = FeeRate::from_per_weight_unit(Amount::from_u128(1)).unwrap();
+        assert_eq!(rate, FeeRate::from_per_byte(Amount::from_u128(4)).unwrap());
+        assert_eq!(rate, FeeRate::from_per_kwu(Amount::from_u128(1_000)).unwrap());
+        assert_eq!(rate.fee_for_weight(1_000), Some(Amount::from_u128(1_000)));
+        assert_eq!(rate.fee_for_size(250), Some(Amount::from_u128(1_000)));
+
+        // 不足 1 个最小单位的部分向上取整
+        let rate: FeeRate = "0.25 base/WU".parse().unwrap();
+        assert_eq!(rate, "1 base/B".parse().unwrap());
+        assert_eq!(rate.fee_for_weight(3), Some(Amount::from_u128(1)));
+
+        let rate: FeeRate = "2 base/kWU".parse().unwrap();
+        assert_eq!(rate.per_kb(), Amount::from_u128(8));
+
+        let rate = FeeRate::from_fee_and_weight(&Amount::from_u128(1_000), 1_000).unwrap();
+        assert_eq!(rate, FeeRate::from_per_weight_unit(Amount::from_u128(1)).unwrap());
+        assert!(FeeRate::from_fee_and_weight(&Amount::from_u128(1), 0).is_none());
+
+        // 溢出
+        assert!(FeeRate::from_per_weight_unit(Amount::max_value()).is_none());
+        assert!(FeeRate::from_per_kwu(Amount::max_value()).is_none());
+        assert!(FeeRate::from_per_kb(Amount::max_value()).fee_for_weight(2).is_none());
+        assert!("340282366920938463463374607431768211455 base/kWU".parse::<FeeRate>().is_err());
+    }
+
+    #[test]
+    fn test_fee_rate_ordering_and_serde() {
+        let low: FeeRate = "1 base/B".parse().unwrap();
+        let high: FeeRate = "2 base/B".parse().unwrap();
+        assert!(low < high);
+
+        let serialized = serde_json::to_string(&high).unwrap();
+        let deserialized: FeeRate = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, high);
+    }
+}
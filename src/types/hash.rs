@@ -0,0 +1,97 @@
+use crate::types::keys::serde_hex_bytes;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+
+/// 32 字节哈希值，以十六进制显示
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+pub struct Hash256(#[serde(with = "serde_hex_bytes")] [u8; Hash256::LEN]);
+
+/// 交易 ID：交易规范编码的双重 SHA-256
+pub type TxId = Hash256;
+
+impl Hash256 {
+    /// 哈希长度（字节）
+    pub const LEN: usize = 32;
+
+    /// 全零哈希
+    pub const ZERO: Hash256 = Hash256([0u8; Hash256::LEN]);
+
+    /// 计算 SHA-256(SHA-256(data))
+    pub fn double_sha256(data: &[u8]) -> Self {
+        let first = Sha256::digest(data);
+        Hash256(Sha256::digest(first).into())
+    }
+
+    /// 由 32 字节数组创建
+    pub const fn from_array(bytes: [u8; Hash256::LEN]) -> Self {
+        Hash256(bytes)
+    }
+
+    /// 从字节创建，长度必须为 32
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        let bytes: [u8; Self::LEN] = bytes.try_into().map_err(|_| "Invalid hash length")?;
+        Ok(Hash256(bytes))
+    }
+
+    /// 哈希字节
+    pub const fn as_bytes(&self) -> &[u8; Hash256::LEN] {
+        &self.0
+    }
+}
+
+impl fmt::Display for Hash256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+impl fmt::Debug for Hash256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Hash256({})", self)
+    }
+}
+
+/// 解析 64 位十六进制字符串
+impl FromStr for Hash256 {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bytes = [0u8; Self::LEN];
+        hex::decode_to_slice(s, &mut bytes).map_err(|_| "Invalid hash hex")?;
+        Ok(Hash256(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_double_sha256() {
+        // SHA-256(SHA-256("")) 的已知结果
+        assert_eq!(
+            Hash256::double_sha256(b"").to_string(),
+            "5df6e0e2761359d30a8275058e299fcc0381534545f55cf43e41983f5d4c9456"
+        );
+        assert_ne!(Hash256::double_sha256(b"a"), Hash256::double_sha256(b"b"));
+    }
+
+    #[test]
+    fn test_hash_encoding() {
+        let hash = Hash256::double_sha256(b"faic");
+        let parsed: Hash256 = hash.to_string().parse().unwrap();
+        assert_eq!(parsed, hash);
+        assert_eq!(Hash256::from_bytes(hash.as_bytes()).unwrap(), hash);
+
+        let serialized = serde_json::to_string(&hash).unwrap();
+        assert_eq!(serialized, format!("\"{}\"", hash));
+        assert_eq!(serde_json::from_str::<Hash256>(&serialized).unwrap(), hash);
+
+        assert!("abcd".parse::<Hash256>().is_err());
+        assert!("zz".repeat(32).parse::<Hash256>().is_err());
+        assert!(Hash256::from_bytes(&[0u8; 31]).is_err());
+        assert!(Hash256::ZERO < hash);
+    }
+}
@@ -0,0 +1,197 @@
+use ed25519_dalek::{Signer, SigningKey, Verifier, VerifyingKey};
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+// 以十六进制字符串的形式序列化定长字节数组
+pub(crate) mod serde_hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    // 序列化字节数组为十六进制字符串
+    pub fn serialize<S, const N: usize>(bytes: &[u8; N], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    // 从十六进制字符串反序列化字节数组
+    pub fn deserialize<'de, D, const N: usize>(deserializer: D) -> Result<[u8; N], D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        let mut bytes = [0u8; N];
+        hex::decode_to_slice(&s, &mut bytes).map_err(serde::de::Error::custom)?;
+        Ok(bytes)
+    }
+}
+
+/// Ed25519 公钥
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct PublicKey(#[serde(with = "serde_hex_bytes")] [u8; PublicKey::LEN]);
+
+/// Ed25519 签名
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Signature(#[serde(with = "serde_hex_bytes")] [u8; Signature::LEN]);
+
+/// Ed25519 密钥对
+#[derive(Clone)]
+pub struct KeyPair {
+    signing_key: SigningKey,
+}
+
+impl PublicKey {
+    /// 公钥长度（字节）
+    pub const LEN: usize = 32;
+
+    /// 从字节创建公钥，不是合法的曲线点时返回错误
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        let bytes: [u8; Self::LEN] = bytes.try_into().map_err(|_| "Invalid public key length")?;
+        VerifyingKey::from_bytes(&bytes).map_err(|_| "Invalid public key")?;
+        Ok(PublicKey(bytes))
+    }
+
+    /// 公钥字节
+    pub const fn as_bytes(&self) -> &[u8; Self::LEN] {
+        &self.0
+    }
+
+    /// 校验签名
+    pub fn verify(&self, message: &[u8], signature: &Signature) -> bool {
+        let Ok(key) = VerifyingKey::from_bytes(&self.0) else {
+            return false;
+        };
+        let signature = ed25519_dalek::Signature::from_bytes(&signature.0);
+        key.verify(message, &signature).is_ok()
+    }
+}
+
+impl Signature {
+    /// 签名长度（字节）
+    pub const LEN: usize = 64;
+
+    /// 从字节创建签名
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        let bytes: [u8; Self::LEN] = bytes.try_into().map_err(|_| "Invalid signature length")?;
+        Ok(Signature(bytes))
+    }
+
+    /// 签名字节
+    pub const fn as_bytes(&self) -> &[u8; Self::LEN] {
+        &self.0
+    }
+}
+
+impl From<[u8; Signature::LEN]> for Signature {
+    fn from(bytes: [u8; Signature::LEN]) -> Self {
+        Signature(bytes)
+    }
+}
+
+impl KeyPair {
+    /// 随机生成密钥对
+    pub fn generate() -> Self {
+        KeyPair {
+            signing_key: SigningKey::generate(&mut OsRng),
+        }
+    }
+
+    /// 从 32 字节私钥恢复密钥对
+    pub fn from_secret_bytes(secret: &[u8; 32]) -> Self {
+        KeyPair {
+            signing_key: SigningKey::from_bytes(secret),
+        }
+    }
+
+    /// 私钥字节
+    pub fn secret_bytes(&self) -> [u8; 32] {
+        self.signing_key.to_bytes()
+    }
+
+    /// 公钥
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(self.signing_key.verifying_key().to_bytes())
+    }
+
+    /// 对消息签名
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        Signature(self.signing_key.sign(message).to_bytes())
+    }
+}
+
+// 不打印私钥
+impl fmt::Debug for KeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("KeyPair").field("public_key", &self.public_key()).finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PublicKey({})", self)
+    }
+}
+
+impl fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+impl FromStr for PublicKey {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s).map_err(|_| "Invalid public key hex")?;
+        PublicKey::from_bytes(&bytes)
+    }
+}
+
+impl fmt::Debug for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Signature({})", hex::encode(self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let keypair = KeyPair::generate();
+        let signature = keypair.sign(b"hello faic");
+
+        assert!(keypair.public_key().verify(b"hello faic", &signature));
+        assert!(!keypair.public_key().verify(b"hello faic!", &signature));
+        assert!(!KeyPair::generate().public_key().verify(b"hello faic", &signature));
+
+        // 从私钥恢复后公钥相同
+        let restored = KeyPair::from_secret_bytes(&keypair.secret_bytes());
+        assert_eq!(restored.public_key(), keypair.public_key());
+    }
+
+    #[test]
+    fn test_public_key_encoding() {
+        let public_key = KeyPair::generate().public_key();
+        let parsed: PublicKey = public_key.to_string().parse().unwrap();
+        assert_eq!(parsed, public_key);
+
+        let serialized = serde_json::to_string(&public_key).unwrap();
+        assert_eq!(serialized, format!("\"{}\"", public_key));
+        assert_eq!(serde_json::from_str::<PublicKey>(&serialized).unwrap(), public_key);
+
+        assert!(PublicKey::from_bytes(&[0u8; 31]).is_err());
+        assert!("zz".parse::<PublicKey>().is_err());
+    }
+
+    #[test]
+    fn test_keypair_debug_hides_secret() {
+        let keypair = KeyPair::from_secret_bytes(&[7u8; 32]);
+        let debug = format!("{:?}", keypair);
+        assert!(debug.contains(&keypair.public_key().to_string()));
+        assert!(!debug.contains(&hex::encode([7u8; 32])));
+    }
+}
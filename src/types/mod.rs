@@ -1,7 +1,26 @@
+pub mod address;
+pub mod denomination;
+pub mod encoding;
+pub mod fee_rate;
+pub mod hash;
+pub mod keys;
+pub mod signed_amount;
+pub mod transaction;
+
+pub use address::{Address, NetworkKind};
+pub use denomination::{AmountFormatter, Denomination, Precision, RoundingMode};
+pub use encoding::DecodeError;
+pub use fee_rate::FeeRate;
+pub use hash::{Hash256, TxId};
+pub use keys::{KeyPair, PublicKey, Signature};
+pub use signed_amount::SignedAmount;
+pub use transaction::{Transaction, TransactionError};
 
 use lazy_static::lazy_static;
 use num_bigint::BigUint;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::iter::Sum;
 use std::str::FromStr;
 
 lazy_static! {
@@ -11,27 +30,131 @@ lazy_static! {
     pub static ref MAX_AMOUNT: BigUint = BigUint::parse_bytes(b"340282366920938463463374607431768211455", 10).unwrap();
 }
 
+// 兼容 BigUint 的序列化格式：以 u32 为单位的小端序数组，去掉高位的零
+mod serde_u32_digits {
+    use serde::de::{SeqAccess, Visitor};
+    use serde::ser::SerializeSeq;
+    use serde::{Deserializer, Serializer};
+    use std::fmt;
+
+    // 序列化 u128 为 u32 数组
+    pub fn serialize<S>(value: &u128, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let len = (128 - value.leading_zeros()).div_ceil(32) as usize;
+        let mut seq = serializer.serialize_seq(Some(len))?;
+        for i in 0..len {
+            seq.serialize_element(&((*value >> (32 * i)) as u32))?;
+        }
+        seq.end()
+    }
+
+    struct DigitsVisitor;
+
+    impl<'de> Visitor<'de> for DigitsVisitor {
+        type Value = u128;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a sequence of u32 digits")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<u128, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut value: u128 = 0;
+            let mut i = 0;
+            while let Some(digit) = seq.next_element::<u32>()? {
+                if digit != 0 {
+                    if i >= 4 {
+                        return Err(serde::de::Error::custom("Amount exceeds maximum value"));
+                    }
+                    value |= (digit as u128) << (32 * i);
+                }
+                i += 1;
+            }
+            Ok(value)
+        }
+    }
+
+    // 从 u32 数组反序列化 u128
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<u128, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(DigitsVisitor)
+    }
+}
+
+/// `Amount::value` 的返回值，按值持有的 `BigUint`
+#[derive(Clone, PartialEq, Eq)]
+pub struct AmountValue(BigUint);
+
+impl AmountValue {
+    /// 取出内部的 BigUint
+    pub fn into_inner(self) -> BigUint {
+        self.0
+    }
+}
+
+impl std::ops::Deref for AmountValue {
+    type Target = BigUint;
+
+    fn deref(&self) -> &BigUint {
+        &self.0
+    }
+}
+
+// 按 BigUint 的格式打印，断言失败时的输出与旧接口一致
+impl fmt::Debug for AmountValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl PartialEq<BigUint> for AmountValue {
+    fn eq(&self, other: &BigUint) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<&BigUint> for AmountValue {
+    fn eq(&self, other: &&BigUint) -> bool {
+        self.0 == **other
+    }
+}
+
 /// Amount 数据类型
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Amount {
-    value: BigUint,
+    #[serde(with = "serde_u32_digits")]
+    value: u128,
 }
 
 impl Amount {
     /// 最小单位: 1 (0.00000001 FAIC), 实际精度: 8位小数。参考来源doge
     pub const DECIMALS: u64 = 8;
 
+    /// 规范二进制编码的长度（字节）
+    pub const ENCODED_LEN: usize = 16;
+
     /// 从 BigUint 创建 Amount
     pub fn from_biguint(value: BigUint) -> Result<Self, &'static str> {
-        if value > *MAX_AMOUNT {
-            return Err("Amount exceeds maximum value");
+        match u128::try_from(&value) {
+            Ok(value) => Ok(Amount { value }),
+            Err(_) => Err("Amount exceeds maximum value"),
         }
-        Ok(Amount { value })
     }
 
-    /// 从字符串创建 Amount
-    pub fn from_str(value: &str) -> Result<Self, &'static str> {
+    /// 从最小单位的数量创建 Amount（MAX_AMOUNT 恰好是 u128::MAX，因此总是合法的）
+    pub const fn from_u128(value: u128) -> Self {
+        Amount { value }
+    }
+
+    /// 从最小单位的整数字符串创建 Amount，例如 "100" 表示 0.00000100 FAIC
+    pub fn from_base_units_str(value: &str) -> Result<Self, &'static str> {
         let parsed_value = match BigUint::from_str(value) {
             Ok(v) => v,
             Err(_) => return Err("Invalid amount string"),
@@ -40,32 +163,193 @@ impl Amount {
     }
 
     /// 获取 Amount 的值
-    pub fn value(&self) -> &BigUint {
-        &self.value
-    }
-
-    /// 将 Amount 转换为字符串，包含八位小数
-    pub fn to_string(&self) -> String {
-        let value_str = self.value.to_string();
-        let len = value_str.len();
-
-        if len <= Self::DECIMALS as usize {
-            // 如果数值小于 1 FAIC，需要在前面补零
-            format!("0.{:0>8}", value_str)
-        } else {
-            // 插入小数点
-            let (integer_part, decimal_part) = value_str.split_at(len - Self::DECIMALS as usize);
-            format!("{}.{}", integer_part, decimal_part)
+    ///
+    /// 兼容旧接口：Amount 改用 u128 存储后没有可以借出的 `BigUint`，
+    /// 因此返回类型从 `&BigUint` 变为 [`AmountValue`]。它解引用为 `BigUint`，
+    /// 也可以直接与 `&BigUint` 比较，但不能再绑定为 `&BigUint` 的引用。
+    /// 新代码请使用 `as_u128`。
+    #[deprecated(note = "Amount is backed by u128 now; use `as_u128()` instead")]
+    pub fn value(&self) -> AmountValue {
+        AmountValue(BigUint::from(self.value))
+    }
+
+    /// 获取以最小单位表示的数量
+    pub const fn as_u128(&self) -> u128 {
+        self.value
+    }
+
+    /// 零值 Amount
+    pub const fn zero() -> Self {
+        Amount { value: 0 }
+    }
+
+    /// 是否为零
+    pub const fn is_zero(&self) -> bool {
+        self.value == 0
+    }
+
+    /// 加法，结果超过 MAX_AMOUNT 时返回 None
+    pub fn checked_add(&self, other: &Amount) -> Option<Amount> {
+        self.value.checked_add(other.value).map(Amount::from_u128)
+    }
+
+    /// 减法，结果为负时返回 None
+    pub fn checked_sub(&self, other: &Amount) -> Option<Amount> {
+        self.value.checked_sub(other.value).map(Amount::from_u128)
+    }
+
+    /// 乘以整数，结果超过 MAX_AMOUNT 时返回 None
+    pub fn checked_mul_u64(&self, rhs: u64) -> Option<Amount> {
+        self.value.checked_mul(rhs as u128).map(Amount::from_u128)
+    }
+
+    /// 除以整数（向下取整），除数为零时返回 None
+    pub fn checked_div_u64(&self, rhs: u64) -> Option<Amount> {
+        self.value.checked_div(rhs as u128).map(Amount::from_u128)
+    }
+
+    /// 加法，溢出时截断为 MAX_AMOUNT
+    pub fn saturating_add(&self, other: &Amount) -> Amount {
+        Amount::from_u128(self.value.saturating_add(other.value))
+    }
+
+    /// 减法，结果为负时截断为零
+    pub fn saturating_sub(&self, other: &Amount) -> Amount {
+        Amount::from_u128(self.value.saturating_sub(other.value))
+    }
+
+    /// 乘以整数，溢出时截断为 MAX_AMOUNT
+    pub fn saturating_mul_u64(&self, rhs: u64) -> Amount {
+        Amount::from_u128(self.value.saturating_mul(rhs as u128))
+    }
+
+    /// 最大值 Amount
+    pub const fn max_value() -> Self {
+        Amount { value: u128::MAX }
+    }
+
+    /// 规范二进制编码：固定 16 字节的大端序整数，用于交易签名和区块哈希
+    pub const fn to_be_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+        self.value.to_be_bytes()
+    }
+
+    /// 从规范二进制编码解码，长度必须恰好为 16 字节
+    pub fn from_be_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        let bytes: [u8; Self::ENCODED_LEN] = bytes
+            .try_into()
+            .map_err(|_| "Invalid amount encoding length")?;
+        // 16 字节能表示的最大值恰好是 MAX_AMOUNT，因此任何 16 字节的输入都不会超出上限
+        Ok(Amount::from_u128(u128::from_be_bytes(bytes)))
+    }
+}
+
+/// 将十进制字符串解析为最小单位的整数
+///
+/// 整数部分允许使用逗号作为千位分隔符（必须三位一组），小数位数不能超过 `decimals`。
+pub(crate) fn parse_decimal_units(input: &str, decimals: usize) -> Result<u128, &'static str> {
+    let (integer_part, fraction_part) = match input.split_once('.') {
+        Some((i, f)) => (i, Some(f)),
+        None => (input, None),
+    };
+
+    if integer_part.is_empty() {
+        return Err("Invalid amount string");
+    }
+
+    // 校验千位分隔符的位置
+    if integer_part.contains(',') {
+        let groups: Vec<&str> = integer_part.split(',').collect();
+        let first_ok = !groups[0].is_empty() && groups[0].len() <= 3;
+        if !first_ok || groups[1..].iter().any(|g| g.len() != 3) {
+            return Err("Invalid thousands separator");
         }
     }
 
+    let fraction = match fraction_part {
+        Some("") => return Err("Invalid amount string"),
+        Some(f) if f.len() > decimals => return Err("Too many decimal places"),
+        Some(f) => f,
+        None => "",
+    };
+
+    let digits = integer_part.bytes().filter(|&b| b != b',').chain(fraction.bytes());
+    let mut value: u128 = 0;
+    for b in digits {
+        if !b.is_ascii_digit() {
+            return Err("Invalid amount string");
+        }
+        value = value
+            .checked_mul(10)
+            .and_then(|v| v.checked_add((b - b'0') as u128))
+            .ok_or("Amount exceeds maximum value")?;
+    }
+    // 补齐剩余的小数位
+    for _ in fraction.len()..decimals {
+        value = value.checked_mul(10).ok_or("Amount exceeds maximum value")?;
+    }
+    Ok(value)
+}
+
+/// 将最小单位的整数格式化为带 `decimals` 位小数的字符串
+pub(crate) fn format_decimal_units(value: u128, decimals: usize) -> String {
+    let value_str = value.to_string();
+    if decimals == 0 {
+        return value_str;
+    }
+    let len = value_str.len();
+
+    if len <= decimals {
+        // 如果数值小于 1 个单位，需要在前面补零
+        format!("0.{:0>width$}", value_str, width = decimals)
+    } else {
+        // 插入小数点
+        let (integer_part, decimal_part) = value_str.split_at(len - decimals);
+        format!("{}.{}", integer_part, decimal_part)
+    }
+}
+
+/// 解析 FAIC 字符串，例如 "1.5"、"0.00000001"、"1,000.25 FAIC"
+impl FromStr for Amount {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_suffix("FAIC").map(str::trim_end).unwrap_or(s);
+        let value = parse_decimal_units(s, Self::DECIMALS as usize)?;
+        Ok(Self::from_u128(value))
+    }
+}
+
+/// 以 FAIC 为单位显示，包含八位小数
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&format_decimal_units(self.value, Self::DECIMALS as usize))
+    }
+}
+
+/// 求和，任意一步超过 MAX_AMOUNT 时返回 None
+///
+/// 不提供直接求和为 `Amount` 的实现，调用方必须处理溢出。
+impl Sum<Amount> for Option<Amount> {
+    fn sum<I: Iterator<Item = Amount>>(mut iter: I) -> Self {
+        iter.try_fold(Amount::zero(), |acc, x| acc.checked_add(&x))
+    }
+}
+
+impl<'a> Sum<&'a Amount> for Option<Amount> {
+    fn sum<I: Iterator<Item = &'a Amount>>(mut iter: I) -> Self {
+        iter.try_fold(Amount::zero(), |acc, x| acc.checked_add(x))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const ONE_FAIC_U128: u128 = 100_000_000;
+
     #[test]
+    #[allow(deprecated)]
     fn test_amount_from_biguint() {
         let amount = Amount::from_biguint(BigUint::from(100u32)).unwrap();
         assert_eq!(amount.value(), &BigUint::from(100u32));
@@ -78,14 +362,55 @@ mod tests {
 
     }
 
+    #[test]
+    fn test_amount_from_base_units_str() {
+        let amount = Amount::from_base_units_str("100").unwrap();
+        assert_eq!(amount.as_u128(), 100);
+
+        let invalid_amount = Amount::from_base_units_str("abc");
+        assert!(invalid_amount.is_err());
+    }
+
     #[test]
     fn test_amount_from_str() {
         let amount = Amount::from_str("100").unwrap();
-        assert_eq!(amount.value(), &BigUint::from(100u32));
+        assert_eq!(amount.as_u128(), 10_000_000_000);
+
+        let amount: Amount = "1.5".parse().unwrap();
+        assert_eq!(amount.as_u128(), 150_000_000);
+
+        let amount: Amount = "0.00000001".parse().unwrap();
+        assert_eq!(amount.as_u128(), 1);
+
+        let amount: Amount = "1,000.25 FAIC".parse().unwrap();
+        assert_eq!(amount.as_u128(), 100_025_000_000);
+
+        let amount: Amount = "  12FAIC ".parse().unwrap();
+        assert_eq!(amount.as_u128(), 1_200_000_000);
 
         let invalid_amount = Amount::from_str("abc");
         assert!(invalid_amount.is_err());
 
+        // 超过 8 位小数
+        assert!(Amount::from_str("0.000000001").is_err());
+        // 格式错误
+        for invalid in ["", ".5", "1.", "1.2.3", "-1", "+1", "1,00", "1,0000", ",100", "1 000", "1.5 BTC"] {
+            assert!(Amount::from_str(invalid).is_err(), "{:?} should be rejected", invalid);
+        }
+        // 超过 MAX_AMOUNT
+        assert!(Amount::from_str("3402823669209384634633746074317.68211456").is_err());
+    }
+
+    #[test]
+    fn test_amount_string_round_trip() {
+        for value in [0u64, 1, 99_999_999, 100_000_000, 123_456_789, u64::MAX] {
+            let amount = Amount::from_biguint(BigUint::from(value)).unwrap();
+            let parsed: Amount = amount.to_string().parse().unwrap();
+            assert_eq!(parsed, amount);
+        }
+
+        let max = Amount::max_value();
+        assert_eq!(max.to_string().parse::<Amount>().unwrap(), max);
     }
 
     #[test]
@@ -101,5 +426,126 @@ mod tests {
         // 测试数值为整数 FAIC 的情况
         let amount = Amount::from_biguint(BigUint::from(100_000_000u64)).unwrap();
         assert_eq!(amount.to_string(), "1.00000000");
-    }    
+    }
+
+    #[test]
+    fn test_amount_checked_arithmetic() {
+        let a = Amount::from_biguint(BigUint::from(300u32)).unwrap();
+        let b = Amount::from_biguint(BigUint::from(100u32)).unwrap();
+
+        assert_eq!(a.checked_add(&b).unwrap().as_u128(), 400);
+        assert_eq!(a.checked_sub(&b).unwrap().as_u128(), 200);
+        assert_eq!(a.checked_mul_u64(3).unwrap().as_u128(), 900);
+        assert_eq!(a.checked_div_u64(7).unwrap().as_u128(), 42);
+
+        // 不允许出现负数
+        assert!(b.checked_sub(&a).is_none());
+        // 除数为零
+        assert!(a.checked_div_u64(0).is_none());
+
+        // 超过 MAX_AMOUNT
+        let max = Amount::max_value();
+        let one = Amount::from_biguint(BigUint::from(1u32)).unwrap();
+        assert!(max.checked_add(&one).is_none());
+        assert!(max.checked_mul_u64(2).is_none());
+        assert_eq!(max.checked_mul_u64(1).unwrap(), max);
+    }
+
+    #[test]
+    fn test_amount_saturating_arithmetic() {
+        let a = Amount::from_biguint(BigUint::from(300u32)).unwrap();
+        let b = Amount::from_biguint(BigUint::from(100u32)).unwrap();
+        let max = Amount::max_value();
+
+        assert_eq!(b.saturating_sub(&a), Amount::zero());
+        assert_eq!(max.saturating_add(&a), max);
+        assert_eq!(max.saturating_mul_u64(u64::MAX), max);
+        assert_eq!(a.saturating_add(&b).as_u128(), 400);
+    }
+
+    #[test]
+    fn test_amount_sum() {
+        let amounts: Vec<Amount> = (1u32..=4)
+            .map(|v| Amount::from_biguint(BigUint::from(v)).unwrap())
+            .collect();
+
+        let total: Option<Amount> = amounts.iter().sum();
+        assert_eq!(total.unwrap().as_u128(), 10);
+
+        let checked: Option<Amount> = amounts.into_iter().sum();
+        assert_eq!(checked.unwrap().as_u128(), 10);
+
+        // 溢出时返回 None
+        let overflow: Option<Amount> = vec![Amount::max_value(), Amount::max_value()].into_iter().sum();
+        assert!(overflow.is_none());
+
+        let empty: Option<Amount> = Vec::<Amount>::new().into_iter().sum();
+        assert_eq!(empty, Some(Amount::zero()));
+    }
+
+    #[test]
+    fn test_amount_ordering_and_hash() {
+        use std::collections::{BTreeMap, HashSet};
+
+        let small = Amount::from_u128(1);
+        let large = Amount::from_u128(ONE_FAIC_U128);
+        assert!(small < large);
+        assert_eq!(small.max(large), large);
+
+        let mut fees = vec![large, Amount::zero(), Amount::max_value(), small];
+        fees.sort();
+        assert_eq!(fees, vec![Amount::zero(), small, large, Amount::max_value()]);
+
+        let mut map = BTreeMap::new();
+        map.insert(large, "large");
+        map.insert(small, "small");
+        assert_eq!(map.keys().next(), Some(&small));
+
+        let set: HashSet<Amount> = [small, small, large].into_iter().collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_amount_binary_encoding() {
+        let amount = Amount::from_u128(0x0102);
+        let bytes = amount.to_be_bytes();
+        assert_eq!(bytes.len(), Amount::ENCODED_LEN);
+        assert_eq!(&bytes[..14], &[0u8; 14]);
+        assert_eq!(&bytes[14..], &[0x01, 0x02]);
+        assert_eq!(Amount::from_be_bytes(&bytes).unwrap(), amount);
+
+        let max = Amount::max_value();
+        assert_eq!(max.to_be_bytes(), [0xff; 16]);
+        assert_eq!(Amount::from_be_bytes(&max.to_be_bytes()).unwrap(), max);
+
+        // 编码的字节序与数值大小顺序一致
+        assert!(Amount::from_u128(255).to_be_bytes() < Amount::from_u128(256).to_be_bytes());
+
+        // 长度不对的输入被拒绝
+        assert!(Amount::from_be_bytes(&[0u8; 15]).is_err());
+        assert!(Amount::from_be_bytes(&[0u8; 17]).is_err());
+    }
+
+    #[test]
+    fn test_amount_serde_matches_biguint() {
+        // u128 存储必须与原先 BigUint 的 JSON 格式保持一致
+        let values = [0u128, 1, 100, u32::MAX as u128, u32::MAX as u128 + 1, u64::MAX as u128 * 3, u128::MAX];
+        for value in values {
+            let amount = Amount::from_u128(value);
+            let serialized = serde_json::to_string(&amount).unwrap();
+            let expected = format!("{{\"value\":{}}}", serde_json::to_string(&BigUint::from(value)).unwrap());
+            assert_eq!(serialized, expected);
+
+            let deserialized: Amount = serde_json::from_str(&serialized).unwrap();
+            assert_eq!(deserialized, amount);
+        }
+
+        // 带有高位零的数组也能被解析
+        let amount: Amount = serde_json::from_str(r#"{"value":[5,0,0,0,0]}"#).unwrap();
+        assert_eq!(amount.as_u128(), 5);
+
+        // 超过 2^128 - 1 的值被拒绝
+        let overflow: Result<Amount, _> = serde_json::from_str(r#"{"value":[0,0,0,0,1]}"#);
+        assert!(overflow.is_err());
+    }
 }
\ No newline at end of file
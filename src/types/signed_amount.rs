@@ -0,0 +1,259 @@
+use crate::types::Amount;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Neg;
+use std::str::FromStr;
+
+/// 带符号的数量，用于表示余额的变化（借记为负，贷记为正）
+///
+/// 绝对值的范围与 Amount 相同，零总是非负的。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "SignedAmountRepr", into = "SignedAmountRepr")]
+pub struct SignedAmount {
+    negative: bool,
+    magnitude: Amount,
+}
+
+// 序列化格式：符号位 + 绝对值
+#[derive(Serialize, Deserialize)]
+struct SignedAmountRepr {
+    negative: bool,
+    magnitude: Amount,
+}
+
+impl From<SignedAmountRepr> for SignedAmount {
+    fn from(repr: SignedAmountRepr) -> Self {
+        SignedAmount::new(repr.negative, repr.magnitude)
+    }
+}
+
+impl From<SignedAmount> for SignedAmountRepr {
+    fn from(value: SignedAmount) -> Self {
+        SignedAmountRepr {
+            negative: value.negative,
+            magnitude: value.magnitude,
+        }
+    }
+}
+
+impl SignedAmount {
+    /// 由符号和绝对值创建，负零会被规范化为零
+    pub const fn new(negative: bool, magnitude: Amount) -> Self {
+        SignedAmount {
+            negative: negative && !magnitude.is_zero(),
+            magnitude,
+        }
+    }
+
+    /// 零
+    pub const fn zero() -> Self {
+        SignedAmount::new(false, Amount::zero())
+    }
+
+    /// 贷记（增加余额）
+    pub const fn credit(amount: Amount) -> Self {
+        SignedAmount::new(false, amount)
+    }
+
+    /// 借记（减少余额）
+    pub const fn debit(amount: Amount) -> Self {
+        SignedAmount::new(true, amount)
+    }
+
+    /// 是否为负
+    pub const fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// 是否为零
+    pub const fn is_zero(&self) -> bool {
+        self.magnitude.is_zero()
+    }
+
+    /// 绝对值
+    pub const fn magnitude(&self) -> Amount {
+        self.magnitude
+    }
+
+    /// 非负时转换为 Amount，否则返回 None
+    pub const fn to_amount(&self) -> Option<Amount> {
+        if self.negative {
+            None
+        } else {
+            Some(self.magnitude)
+        }
+    }
+
+    /// 加法，结果的绝对值超过 MAX_AMOUNT 时返回 None
+    pub fn checked_add(&self, other: &SignedAmount) -> Option<SignedAmount> {
+        if self.negative == other.negative {
+            let magnitude = self.magnitude.checked_add(&other.magnitude)?;
+            return Some(SignedAmount::new(self.negative, magnitude));
+        }
+        // 符号相反时，结果的符号取绝对值较大的一方
+        match self.magnitude.cmp(&other.magnitude) {
+            Ordering::Less => Some(SignedAmount::new(other.negative, other.magnitude.checked_sub(&self.magnitude)?)),
+            _ => Some(SignedAmount::new(self.negative, self.magnitude.checked_sub(&other.magnitude)?)),
+        }
+    }
+
+    /// 减法，结果的绝对值超过 MAX_AMOUNT 时返回 None
+    pub fn checked_sub(&self, other: &SignedAmount) -> Option<SignedAmount> {
+        self.checked_add(&-*other)
+    }
+
+    /// 将变化应用到余额上，结果为负或溢出时返回 None
+    pub fn apply_to(&self, balance: &Amount) -> Option<Amount> {
+        if self.negative {
+            balance.checked_sub(&self.magnitude)
+        } else {
+            balance.checked_add(&self.magnitude)
+        }
+    }
+
+    /// 计算从 `before` 变为 `after` 的差值
+    pub fn difference(before: &Amount, after: &Amount) -> SignedAmount {
+        match after.checked_sub(before) {
+            Some(increase) => SignedAmount::credit(increase),
+            None => SignedAmount::debit(before.saturating_sub(after)),
+        }
+    }
+}
+
+impl Default for SignedAmount {
+    fn default() -> Self {
+        SignedAmount::zero()
+    }
+}
+
+impl From<Amount> for SignedAmount {
+    fn from(amount: Amount) -> Self {
+        SignedAmount::credit(amount)
+    }
+}
+
+impl TryFrom<SignedAmount> for Amount {
+    type Error = &'static str;
+
+    fn try_from(value: SignedAmount) -> Result<Self, Self::Error> {
+        value.to_amount().ok_or("Negative amount")
+    }
+}
+
+impl Neg for SignedAmount {
+    type Output = SignedAmount;
+
+    fn neg(self) -> SignedAmount {
+        SignedAmount::new(!self.negative, self.magnitude)
+    }
+}
+
+impl Ord for SignedAmount {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, false) => self.magnitude.cmp(&other.magnitude),
+            (true, true) => other.magnitude.cmp(&self.magnitude),
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+        }
+    }
+}
+
+impl PartialOrd for SignedAmount {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// 以 FAIC 为单位显示，负数带 "-" 前缀
+impl fmt::Display for SignedAmount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.negative {
+            f.write_str("-")?;
+        }
+        write!(f, "{}", self.magnitude)
+    }
+}
+
+/// 解析 FAIC 字符串，允许 "+" 或 "-" 前缀
+impl FromStr for SignedAmount {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(rest) = s.strip_prefix('-') {
+            Ok(SignedAmount::debit(rest.parse()?))
+        } else {
+            Ok(SignedAmount::credit(s.strip_prefix('+').unwrap_or(s).parse()?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signed(s: &str) -> SignedAmount {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_signed_amount_arithmetic() {
+        assert_eq!(signed("1.5").checked_add(&signed("-2")).unwrap(), signed("-0.5"));
+        assert_eq!(signed("-1.5").checked_add(&signed("2")).unwrap(), signed("0.5"));
+        assert_eq!(signed("-1").checked_add(&signed("-2")).unwrap(), signed("-3"));
+        assert_eq!(signed("1").checked_sub(&signed("1")).unwrap(), SignedAmount::zero());
+        assert_eq!(signed("1").checked_sub(&signed("3")).unwrap(), signed("-2"));
+
+        // 绝对值溢出
+        let max = SignedAmount::credit(Amount::max_value());
+        assert!(max.checked_add(&signed("0.00000001")).is_none());
+        assert!((-max).checked_sub(&signed("0.00000001")).is_none());
+        assert_eq!(max.checked_add(&-max).unwrap(), SignedAmount::zero());
+    }
+
+    #[test]
+    fn test_signed_amount_conversion() {
+        let amount: Amount = "2".parse().unwrap();
+        assert_eq!(SignedAmount::from(amount).to_amount(), Some(amount));
+        assert_eq!(Amount::try_from(SignedAmount::debit(amount)), Err("Negative amount"));
+
+        // 负零被规范化
+        let zero = SignedAmount::debit(Amount::zero());
+        assert!(!zero.is_negative());
+        assert_eq!(zero, SignedAmount::zero());
+        assert_eq!(Amount::try_from(zero), Ok(Amount::zero()));
+
+        let before: Amount = "5".parse().unwrap();
+        let after: Amount = "3".parse().unwrap();
+        let delta = SignedAmount::difference(&before, &after);
+        assert_eq!(delta, signed("-2"));
+        assert_eq!(delta.apply_to(&before), Some(after));
+        assert_eq!((-delta).apply_to(&after), Some(before));
+        assert_eq!(delta.apply_to(&"1".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn test_signed_amount_ordering_and_display() {
+        let mut values = vec![signed("1"), signed("-2"), SignedAmount::zero(), signed("-1")];
+        values.sort();
+        assert_eq!(values, vec![signed("-2"), signed("-1"), SignedAmount::zero(), signed("1")]);
+
+        assert_eq!(signed("-0.5").to_string(), "-0.50000000");
+        assert_eq!(signed("+0.5").to_string(), "0.50000000");
+        assert!("--1".parse::<SignedAmount>().is_err());
+    }
+
+    #[test]
+    fn test_signed_amount_serde() {
+        let delta = signed("-1.25");
+        let serialized = serde_json::to_string(&delta).unwrap();
+        let deserialized: SignedAmount = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, delta);
+
+        // 反序列化时负零被规范化
+        let zero: SignedAmount = serde_json::from_str(r#"{"negative":true,"magnitude":{"value":[]}}"#).unwrap();
+        assert_eq!(zero, SignedAmount::zero());
+    }
+}
@@ -0,0 +1,262 @@
+use crate::types::encoding::{DecodeError, Reader};
+use crate::types::{Address, Amount, KeyPair, NetworkKind, PublicKey, Signature, TxId};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// 转账交易（账户模型）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Transaction {
+    /// 链 ID，防止交易在其他链上重放
+    pub chain_id: u32,
+    /// 发送方公钥
+    pub sender: PublicKey,
+    /// 发送方账户的交易序号
+    pub nonce: u64,
+    /// 接收方地址
+    pub recipient: Address,
+    /// 转账金额
+    pub value: Amount,
+    /// 手续费
+    pub fee: Amount,
+    /// 发送方对签名原文的签名
+    pub signature: Signature,
+}
+
+// 自定义错误类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionError {
+    InvalidSignature,  // 签名校验失败
+    WrongChainId { expected: u32, actual: u32 },  // 链 ID 不匹配
+    ZeroValue,  // 转账金额为零
+    AmountOverflow,  // 金额与手续费之和溢出
+    Decode(DecodeError),  // 二进制解码错误
+}
+
+// 为 TransactionError 实现 Display trait，用于打印错误信息
+impl fmt::Display for TransactionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransactionError::InvalidSignature => write!(f, "Invalid transaction signature"),
+            TransactionError::WrongChainId { expected, actual } => {
+                write!(f, "Wrong chain id: expected {}, got {}", expected, actual)
+            }
+            TransactionError::ZeroValue => write!(f, "Transaction value must be greater than zero"),
+            TransactionError::AmountOverflow => write!(f, "Transaction value plus fee overflows"),
+            TransactionError::Decode(e) => write!(f, "Transaction decode error: {}", e),
+        }
+    }
+}
+
+// 为 TransactionError 实现 Error trait
+impl std::error::Error for TransactionError {}
+
+// 实现从 DecodeError 到 TransactionError 的转换
+impl From<DecodeError> for TransactionError {
+    fn from(err: DecodeError) -> Self {
+        TransactionError::Decode(err)
+    }
+}
+
+impl Transaction {
+    /// 签名原文的域分隔前缀
+    const SIGNING_DOMAIN: &'static [u8] = b"FAIC-TX-V1";
+
+    /// 不含签名部分的规范编码长度（字节）
+    const UNSIGNED_LEN: usize = 4 + PublicKey::LEN + 8 + Address::ENCODED_LEN + 2 * Amount::ENCODED_LEN;
+
+    /// 规范二进制编码的长度（字节）
+    pub const ENCODED_LEN: usize = Self::UNSIGNED_LEN + Signature::LEN;
+
+    /// 创建并签名一笔交易
+    pub fn new_signed(
+        keypair: &KeyPair,
+        chain_id: u32,
+        nonce: u64,
+        recipient: Address,
+        value: Amount,
+        fee: Amount,
+    ) -> Self {
+        let mut transaction = Transaction {
+            chain_id,
+            sender: keypair.public_key(),
+            nonce,
+            recipient,
+            value,
+            fee,
+            signature: Signature::from([0u8; Signature::LEN]),
+        };
+        transaction.signature = keypair.sign(&transaction.signing_preimage());
+        transaction
+    }
+
+    // 不含签名部分的规范编码
+    fn encode_unsigned(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.chain_id.to_be_bytes());
+        out.extend_from_slice(self.sender.as_bytes());
+        out.extend_from_slice(&self.nonce.to_be_bytes());
+        out.extend_from_slice(&self.recipient.to_bytes());
+        out.extend_from_slice(&self.value.to_be_bytes());
+        out.extend_from_slice(&self.fee.to_be_bytes());
+    }
+
+    /// 签名原文：域分隔前缀 + 不含签名部分的规范编码
+    pub fn signing_preimage(&self) -> Vec<u8> {
+        let mut preimage = Vec::with_capacity(Self::SIGNING_DOMAIN.len() + Self::UNSIGNED_LEN);
+        preimage.extend_from_slice(Self::SIGNING_DOMAIN);
+        self.encode_unsigned(&mut preimage);
+        preimage
+    }
+
+    /// 规范二进制编码：所有字段按声明顺序定长拼接，整数为大端序
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::ENCODED_LEN);
+        self.encode_unsigned(&mut bytes);
+        bytes.extend_from_slice(self.signature.as_bytes());
+        bytes
+    }
+
+    /// 交易 ID：完整规范编码（含签名）的双重 SHA-256
+    pub fn txid(&self) -> TxId {
+        TxId::double_sha256(&self.encode())
+    }
+
+    /// 从规范二进制编码解码，不校验签名
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader = Reader::new(bytes);
+        let transaction = Self::read_from(&mut reader)?;
+        reader.finish()?;
+        Ok(transaction)
+    }
+
+    // 从 reader 中读取一笔交易
+    pub(crate) fn read_from(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(Transaction {
+            chain_id: reader.read_u32()?,
+            sender: PublicKey::from_bytes(reader.read_bytes(PublicKey::LEN)?).map_err(DecodeError::Invalid)?,
+            nonce: reader.read_u64()?,
+            recipient: Address::from_bytes(reader.read_bytes(Address::ENCODED_LEN)?).map_err(DecodeError::Invalid)?,
+            value: reader.read_amount()?,
+            fee: reader.read_amount()?,
+            signature: Signature::from_bytes(reader.read_bytes(Signature::LEN)?).map_err(DecodeError::Invalid)?,
+        })
+    }
+
+    /// 发送方在指定网络上的地址
+    pub fn sender_address(&self, network: NetworkKind) -> Address {
+        Address::from_public_key(&self.sender, network)
+    }
+
+    /// 发送方需要支付的总额（金额 + 手续费）
+    pub fn total_cost(&self) -> Option<Amount> {
+        self.value.checked_add(&self.fee)
+    }
+
+    /// 校验签名
+    pub fn verify_signature(&self) -> Result<(), TransactionError> {
+        if self.sender.verify(&self.signing_preimage(), &self.signature) {
+            Ok(())
+        } else {
+            Err(TransactionError::InvalidSignature)
+        }
+    }
+
+    /// 与链无关的基本校验：金额合法且签名正确
+    pub fn verify(&self) -> Result<(), TransactionError> {
+        if self.value.is_zero() {
+            return Err(TransactionError::ZeroValue);
+        }
+        self.total_cost().ok_or(TransactionError::AmountOverflow)?;
+        self.verify_signature()
+    }
+
+    /// 在基本校验之外检查链 ID
+    pub fn verify_for_chain(&self, chain_id: u32) -> Result<(), TransactionError> {
+        if self.chain_id != chain_id {
+            return Err(TransactionError::WrongChainId {
+                expected: chain_id,
+                actual: self.chain_id,
+            });
+        }
+        self.verify()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_transaction(keypair: &KeyPair) -> Transaction {
+        let recipient = Address::from_public_key(&KeyPair::generate().public_key(), NetworkKind::Regtest);
+        Transaction::new_signed(
+            keypair,
+            3,
+            7,
+            recipient,
+            "1.5".parse().unwrap(),
+            "0.001".parse().unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_signed_transaction_verifies() {
+        let keypair = KeyPair::generate();
+        let transaction = sample_transaction(&keypair);
+        transaction.verify().unwrap();
+        transaction.verify_for_chain(3).unwrap();
+        assert_eq!(
+            transaction.verify_for_chain(1),
+            Err(TransactionError::WrongChainId { expected: 1, actual: 3 })
+        );
+        assert!(transaction.sender_address(NetworkKind::Regtest).matches(&keypair.public_key()));
+    }
+
+    #[test]
+    fn test_tampered_transaction_is_rejected() {
+        let keypair = KeyPair::generate();
+        let transaction = sample_transaction(&keypair);
+
+        let mut tampered = transaction.clone();
+        tampered.value = "100".parse().unwrap();
+        assert_eq!(tampered.verify(), Err(TransactionError::InvalidSignature));
+        assert_ne!(tampered.txid(), transaction.txid());
+
+        let mut tampered = transaction.clone();
+        tampered.nonce += 1;
+        assert_eq!(tampered.verify(), Err(TransactionError::InvalidSignature));
+
+        let mut tampered = transaction.clone();
+        tampered.sender = KeyPair::generate().public_key();
+        assert_eq!(tampered.verify(), Err(TransactionError::InvalidSignature));
+
+        let mut zero = Transaction::new_signed(&keypair, 3, 0, transaction.recipient, Amount::zero(), Amount::zero());
+        assert_eq!(zero.verify(), Err(TransactionError::ZeroValue));
+        zero = Transaction::new_signed(&keypair, 3, 0, transaction.recipient, Amount::max_value(), "1".parse().unwrap());
+        assert_eq!(zero.verify(), Err(TransactionError::AmountOverflow));
+    }
+
+    #[test]
+    fn test_transaction_binary_encoding() {
+        let transaction = sample_transaction(&KeyPair::generate());
+        let bytes = transaction.encode();
+        assert_eq!(bytes.len(), Transaction::ENCODED_LEN);
+        assert!(bytes.starts_with(&transaction.signing_preimage()[Transaction::SIGNING_DOMAIN.len()..]));
+
+        let decoded = Transaction::decode(&bytes).unwrap();
+        assert_eq!(decoded, transaction);
+        assert_eq!(decoded.txid(), transaction.txid());
+        decoded.verify().unwrap();
+
+        assert_eq!(Transaction::decode(&bytes[..bytes.len() - 1]), Err(DecodeError::UnexpectedEnd));
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert_eq!(Transaction::decode(&extended), Err(DecodeError::TrailingBytes));
+    }
+
+    #[test]
+    fn test_transaction_serde() {
+        let transaction = sample_transaction(&KeyPair::generate());
+        let serialized = serde_json::to_string(&transaction).unwrap();
+        let deserialized: Transaction = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, transaction);
+    }
+}